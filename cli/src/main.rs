@@ -8,15 +8,18 @@ extern crate clap_complete;
 use human_panic::setup_panic;
 
 extern crate imagenex831l;
-use imagenex831l::Result;
-
-use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
-use clap_complete::shells::PowerShell;
-use clap_complete::{
-    generate,
-    shells::{Bash, Fish, Zsh},
+use imagenex831l::{
+    CircleFit, CloudFormat, Colormap, EllipseFit, Error, PointCloud, RenderOptions, Result,
+    StreamReader, Sweep, SweepAssembler, Writer,
 };
 
+use clap::{Parser, Subcommand, ValueEnum};
+use imagenex831l::types::primitive::sound_velocity::coppens;
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{stdin, BufWriter, Read};
+use std::path::{Path, PathBuf};
+
 #[derive(Parser, Debug)]
 #[command(
     name = "i831",
@@ -47,15 +50,295 @@ enum Commands {
     // },
     #[clap(name = "convert", about = "Convert sonar files to other formats.")]
     Convert { to: ToFormats },
+
+    #[clap(name = "info", about = "Summarize the shots in a sonar file.")]
+    Info {
+        /// The sonar file to read, or `-` for standard input.
+        #[clap(default_value = "-")]
+        input: PathBuf,
+    },
+
+    #[clap(name = "cloud", about = "Export the profiles of a sonar file as a 3D point cloud.")]
+    Cloud {
+        /// The sonar file to read, or `-` for standard input.
+        input: PathBuf,
+
+        /// The point cloud to write, in the format of its extension (`.ply`, `.xyz` or `.las`).
+        #[clap(short, long)]
+        output: PathBuf,
+
+        /// Write PLY as text rather than binary.
+        #[clap(long)]
+        ascii: bool,
+
+        /// Include the echo intensity of each point.
+        #[clap(long)]
+        intensity: bool,
+
+        /// Rotate the points into a level pipe frame by the pitch and roll of each shot.
+        #[clap(long)]
+        level: bool,
+    },
+
+    #[clap(
+        name = "correct",
+        about = "Correct the ranges of a sonar file for the true sound velocity."
+    )]
+    Correct {
+        /// The sonar file to read, or `-` for standard input.
+        input: PathBuf,
+
+        /// The sonar file to write the corrected shots to, or only summarize if omitted.
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        /// The true sound velocity, in m/s.
+        #[clap(long, conflicts_with = "temperature", required_unless_present = "temperature")]
+        sound_velocity: Option<f32>,
+
+        /// The water temperature in °C, to compute the sound velocity from.
+        #[clap(long, allow_negative_numbers = true)]
+        temperature: Option<f32>,
+
+        /// The salinity in parts per thousand, to compute the sound velocity from.
+        #[clap(long, default_value_t = 0.0, requires = "temperature")]
+        salinity: f32,
+
+        /// The depth in meters, to compute the sound velocity from.
+        #[clap(long, default_value_t = 0.0, requires = "temperature")]
+        depth: f32,
+    },
+
+    #[clap(name = "fit", about = "Fit the pipe cross-section of each sweep, printing CSV.")]
+    Fit {
+        /// The sonar file to read, or `-` for standard input.
+        #[clap(default_value = "-")]
+        input: PathBuf,
+    },
+
+    #[clap(name = "render", about = "Render a sweep of a sonar file to a PNG or PGM image.")]
+    Render {
+        /// The sonar file to read, or `-` for standard input.
+        input: PathBuf,
+
+        /// The image to write, in the format of its extension (`.png` or `.pgm`).
+        #[clap(short, long)]
+        output: PathBuf,
+
+        /// The index of the sweep to render, or the first complete sweep if omitted.
+        #[clap(long)]
+        sweep: Option<usize>,
+
+        /// The width and height of the image, in pixels.
+        #[clap(long, default_value_t = 512)]
+        size: u32,
+
+        /// The range at the edge of the image in meters, or that of the sweep if omitted.
+        #[clap(long)]
+        range: Option<f32>,
+
+        /// The colormap of the echo intensities: grayscale, amber or jet.
+        #[clap(long, default_value_t = Colormap::Grayscale)]
+        colormap: Colormap,
+
+        /// The gain added to the echo intensities, in dB.
+        #[clap(long, default_value_t = 0.0, allow_negative_numbers = true)]
+        gain: f32,
+
+        /// The factor the displayed intensities are stretched by about mid-scale.
+        #[clap(long, default_value_t = 1.0)]
+        contrast: f32,
+
+        /// The spacing of the range rings in meters.
+        #[clap(long)]
+        rings: Option<f32>,
+
+        /// Do not overlay the detected profile.
+        #[clap(long)]
+        no_profile: bool,
+    },
+}
+
+/// Open a streaming shot reader over a file, or over standard input for `-`.
+fn open_input(input: &Path) -> Result<StreamReader<Box<dyn Read>>> {
+    let source: Box<dyn Read> = if input == Path::new("-") {
+        Box::new(stdin().lock())
+    } else {
+        Box::new(File::open(input)?)
+    };
+
+    Ok(StreamReader::new(source))
+}
+
+/// Assemble the shots of `reader` into sweeps as they are read, passing each with its index to
+/// `f` until it returns `false`, so that only one sweep is held in memory at a time.
+fn for_each_sweep<R: Read>(
+    reader: &mut StreamReader<R>,
+    mut f: impl FnMut(usize, Sweep) -> Result<bool>,
+) -> Result<()> {
+    let mut assembler = SweepAssembler::new();
+    let mut index = 0;
+
+    for shot in reader.shots() {
+        if let Some(sweep) = assembler.push(shot?) {
+            if !f(index, sweep)? {
+                return Ok(());
+            }
+            index += 1;
+        }
+    }
+
+    if let Some(sweep) = assembler.finish() {
+        f(index, sweep)?;
+    }
+    Ok(())
+}
+
+fn info(input: &Path) -> Result<()> {
+    let mut reader = open_input(input)?;
+    let mut count = 0usize;
+
+    for shot in reader.shots() {
+        shot?;
+        count += 1;
+    }
+
+    println!("shots: {count}");
+    println!("bytes: {}", reader.position());
+    Ok(())
+}
+
+fn cloud(input: &Path, output: &Path, ascii: bool, intensity: bool, level: bool) -> Result<()> {
+    let mut reader = open_input(input)?;
+    let mut cloud = PointCloud::new(intensity).with_level(level);
+    for shot in reader.shots() {
+        cloud.push(&shot?);
+    }
+
+    let format = match CloudFormat::from_path(output) {
+        Some(CloudFormat::PlyBinary) if ascii => Some(CloudFormat::PlyAscii),
+        format => format,
+    };
+    cloud.save(output, format)?;
+    println!("exported {} points to {}", cloud.len(), output.display());
+    Ok(())
+}
+
+fn correct(input: &Path, output: Option<&Path>, sound_velocity: f32) -> Result<()> {
+    let mut reader = open_input(input)?;
+    let mut writer =
+        output.map(File::create).transpose()?.map(|file| Writer::new(BufWriter::new(file)));
+    let mut recorded = BTreeSet::new();
+
+    for shot in reader.shots() {
+        let mut shot = shot?;
+        recorded.insert(shot.header.sound_velocity().to_bits());
+        shot.correct_sound_velocity(sound_velocity)?;
+        if let Some(writer) = writer.as_mut() {
+            writer.write_shot(&shot)?;
+        }
+    }
+
+    for velocity in recorded.into_iter().map(f32::from_bits) {
+        println!("recorded {velocity} m/s, scaled by {}", sound_velocity / velocity);
+    }
+    match writer {
+        Some(writer) => {
+            let count = writer.count();
+            writer.into_inner()?;
+            println!("corrected {count} shots to {sound_velocity} m/s");
+        },
+        None => println!("corrected to {sound_velocity} m/s"),
+    }
+    Ok(())
+}
+
+fn fit(input: &Path) -> Result<()> {
+    let mut reader = open_input(input)?;
+
+    println!(
+        "sweep,time,distance,status,points,diameter,center_x,center_y,offset,rms,quality,\
+         max_diameter,min_diameter,ovality,angle,ellipse_rms,ellipse_quality"
+    );
+    for_each_sweep(&mut reader, |index, sweep| {
+        let header = &sweep.shots()[0].header;
+        let distance = match header.sensor_information().distance_valid {
+            true => header.distance().to_string(),
+            false => String::new(),
+        };
+
+        let points = sweep.profile_points();
+        let circle = match CircleFit::fit(&points) {
+            Some(fit) => format!(
+                "{},{},{},{},{},{}",
+                fit.diameter(),
+                fit.center().0,
+                fit.center().1,
+                fit.offset(),
+                fit.rms(),
+                fit.quality()
+            ),
+            None => ",,,,,".to_string(),
+        };
+        let ellipse = match EllipseFit::fit(&points) {
+            Some(fit) => format!(
+                "{},{},{},{},{},{}",
+                fit.max_diameter(),
+                fit.min_diameter(),
+                fit.ovality(),
+                fit.angle(),
+                fit.rms(),
+                fit.quality()
+            ),
+            None => ",,,,,".to_string(),
+        };
+
+        println!(
+            "{index},{},{distance},{},{},{circle},{ellipse}",
+            header.datetime().to_rfc3339(),
+            sweep.status(),
+            points.len()
+        );
+        Ok(true)
+    })
+}
+
+fn render(
+    input: &Path,
+    output: &Path,
+    sweep: Option<usize>,
+    options: &RenderOptions,
+) -> Result<()> {
+    let mut reader = open_input(input)?;
+    let mut selected = None;
+    for_each_sweep(&mut reader, |index, candidate| {
+        let found = match sweep {
+            Some(wanted) => index == wanted,
+            None => candidate.is_complete(),
+        };
+        if found {
+            selected = Some(candidate);
+        }
+        Ok(!found)
+    })?;
+
+    let selected =
+        selected.ok_or_else(|| Error::new(String::from("No such sweep in the input")))?;
+
+    selected.render(options).save(output)?;
+    println!(
+        "rendered {} sweep of {} shots to {}",
+        selected.status(),
+        selected.len(),
+        output.display()
+    );
+    Ok(())
 }
 
 pub fn cli_match() -> Result<()> {
     // Parse the incoming command-line arguments
     let cli = Cli::parse();
 
-    let app = Cli::command();
-    let matches = app.get_matches();
-
     match &cli.command {
         Commands::GUI => panic!("Hello"),
         // Commands::Completion { subcommand } => {
@@ -76,14 +359,50 @@ pub fn cli_match() -> Result<()> {
         //         _ => (),
         //     }
         // },
-        Commands::Convert => {},
+        Commands::Convert { .. } => {},
+        Commands::Info { input } => info(input)?,
+        Commands::Cloud { input, output, ascii, intensity, level } => {
+            cloud(input, output, *ascii, *intensity, *level)?
+        },
+        Commands::Correct { input, output, sound_velocity, temperature, salinity, depth } => {
+            let sound_velocity = match (sound_velocity, temperature) {
+                (Some(sound_velocity), _) => *sound_velocity,
+                (None, Some(temperature)) => coppens(*temperature, *salinity, *depth),
+                (None, None) => unreachable!("clap requires one of the velocity arguments"),
+            };
+            correct(input, output.as_deref(), sound_velocity)?
+        },
+        Commands::Fit { input } => fit(input)?,
+        Commands::Render {
+            input,
+            output,
+            sweep,
+            size,
+            range,
+            colormap,
+            gain,
+            contrast,
+            rings,
+            no_profile,
+        } => {
+            let options = RenderOptions {
+                size: *size,
+                range: *range,
+                colormap: *colormap,
+                gain: *gain,
+                contrast: *contrast,
+                rings: *rings,
+                profile: !no_profile,
+            };
+            render(input, output, *sweep, &options)?
+        },
     }
 
     Ok(())
 }
 
 /// The main entry point of the application.
-fn main() -> () {
+fn main() {
     // Human Panic. Only enabled when *not* debugging.
     #[cfg(not(debug_assertions))]
     {
@@ -100,5 +419,8 @@ fn main() -> () {
             .install();
     }
 
-    let cli = Cli::parse();
+    if let Err(e) = cli_match() {
+        eprintln!("i831: {e}");
+        std::process::exit(1);
+    }
 }
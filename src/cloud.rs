@@ -0,0 +1,468 @@
+//! Stacking of profiles along the pipe into 3D point clouds, and their export.
+use crate::{Error, Result, Shot};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// The size of a LAS 1.2 public header block.
+const LAS_HEADER_SIZE: u16 = 227;
+
+/// The size of a LAS point data record in format 0.
+const LAS_RECORD_SIZE: u16 = 20;
+
+/// The resolution of LAS coordinates, in meters.
+const LAS_SCALE: f64 = 0.0001;
+
+/// A point of the pipe interior.
+///
+/// Clouds use a right-handed frame with `x` along the pipe, `y` to the left and `z` up, so the
+/// [ProfilePoint](crate::ProfilePoint) at (`x`, `y`) recorded at `distance` is placed at
+/// (`distance`, `-x`, `y`).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CloudPoint {
+    /// The distance along the pipe, in meters.
+    pub x: f32,
+
+    /// The distance to the left of the pipe axis of the sonar, in meters.
+    pub y: f32,
+
+    /// The distance above the pipe axis of the sonar, in meters.
+    pub z: f32,
+
+    /// The normalized echo intensity at the point.
+    pub intensity: f32,
+}
+
+/// The encodings a [PointCloud] can be written in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CloudFormat {
+    /// Stanford polygon file, as text.
+    PlyAscii,
+
+    /// Stanford polygon file, as little-endian binary.
+    PlyBinary,
+
+    /// One line of space-separated coordinates per point.
+    Xyz,
+
+    /// ASPRS LAS 1.2, point data format 0.
+    Las,
+}
+
+impl CloudFormat {
+    /// The format named by the extension of `path`, if known, with `.ply` written as binary.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "ply" => Some(Self::PlyBinary),
+            "xyz" => Some(Self::Xyz),
+            "las" => Some(Self::Las),
+            _ => None,
+        }
+    }
+}
+
+/// Profile points stacked along the pipe by the `distance` recorded with each shot.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PointCloud {
+    points: Vec<CloudPoint>,
+    intensity: bool,
+    level: bool,
+}
+
+impl PointCloud {
+    /// Create an empty cloud, writing the intensity of each point if `intensity` is set.
+    pub fn new(intensity: bool) -> Self {
+        Self { points: Vec::new(), intensity, level: false }
+    }
+
+    /// Rotate the points pushed from now on into the level pipe frame by the
+    /// [Attitude](crate::Attitude) of their shots if `level` is set.
+    pub fn with_level(mut self, level: bool) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Stack the profile points of `shots`.
+    pub fn from_shots<'a, I>(shots: I, intensity: bool) -> Self
+    where
+        I: IntoIterator<Item = &'a Shot>,
+    {
+        let mut cloud = Self::new(intensity);
+        for shot in shots {
+            cloud.push(shot);
+        }
+        cloud
+    }
+
+    /// Add the profile point of `shot`, returning whether it had both a profile point and a
+    /// valid distance.
+    pub fn push(&mut self, shot: &Shot) -> bool {
+        let header = &shot.header;
+        if !header.sensor_information().distance_valid {
+            return false;
+        }
+        let Some(point) = shot.profile_point() else {
+            return false;
+        };
+
+        let intensity = shot
+            .echo_profile()
+            .iter()
+            .find(|&(range, _)| range >= point.range)
+            .map_or(0.0, |(_, intensity)| intensity);
+
+        let [x, y, z] = match self.level {
+            true => shot.attitude().rotate(&point),
+            false => [point.x, point.y, 0.0],
+        };
+        self.points.push(CloudPoint { x: header.distance() + z, y: -x, z: y, intensity });
+        true
+    }
+
+    #[inline]
+    pub fn points(&self) -> &[CloudPoint] {
+        &self.points
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Whether the intensity of each point is written.
+    #[inline]
+    pub fn has_intensity(&self) -> bool {
+        self.intensity
+    }
+
+    fn ply_header(&self, format: &str) -> String {
+        let mut header = format!(
+            "ply\nformat {format} 1.0\nelement vertex {}\n\
+             property float x\nproperty float y\nproperty float z\n",
+            self.len()
+        );
+        if self.intensity {
+            header.push_str("property float intensity\n");
+        }
+        header.push_str("end_header\n");
+        header
+    }
+
+    /// Encode the cloud as a text PLY to `writer`.
+    pub fn write_ply_ascii<W: Write>(&self, mut writer: W) -> Result<()> {
+        writer.write_all(self.ply_header("ascii").as_bytes())?;
+        self.write_lines(writer)
+    }
+
+    /// Encode the cloud as a little-endian binary PLY to `writer`.
+    pub fn write_ply_binary<W: Write>(&self, mut writer: W) -> Result<()> {
+        writer.write_all(self.ply_header("binary_little_endian").as_bytes())?;
+        for point in &self.points {
+            writer.write_all(&point.x.to_le_bytes())?;
+            writer.write_all(&point.y.to_le_bytes())?;
+            writer.write_all(&point.z.to_le_bytes())?;
+            if self.intensity {
+                writer.write_all(&point.intensity.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Encode the cloud as XYZ text to `writer`.
+    pub fn write_xyz<W: Write>(&self, writer: W) -> Result<()> {
+        self.write_lines(writer)
+    }
+
+    fn write_lines<W: Write>(&self, mut writer: W) -> Result<()> {
+        for point in &self.points {
+            match self.intensity {
+                true => {
+                    writeln!(writer, "{} {} {} {}", point.x, point.y, point.z, point.intensity)?
+                },
+                false => writeln!(writer, "{} {} {}", point.x, point.y, point.z)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Encode the cloud as LAS 1.2 in point data format 0 to `writer`.
+    ///
+    /// Coordinates are stored to 0.1 mm and intensities scaled to the full 16 bits.
+    pub fn write_las<W: Write>(&self, mut writer: W) -> Result<()> {
+        let count = u32::try_from(self.len())
+            .map_err(|_| Error::new(String::from("Too many points for LAS")))?;
+
+        let bounds = |axis: fn(&CloudPoint) -> f32| {
+            let values = self.points.iter().map(|point| axis(point) as f64);
+            let min = values.clone().fold(f64::INFINITY, f64::min);
+            let max = values.fold(f64::NEG_INFINITY, f64::max);
+            match count {
+                0 => (0.0, 0.0),
+                _ => (min, max),
+            }
+        };
+        let (min_x, max_x) = bounds(|point| point.x);
+        let (min_y, max_y) = bounds(|point| point.y);
+        let (min_z, max_z) = bounds(|point| point.z);
+
+        let mut header = Vec::with_capacity(LAS_HEADER_SIZE as usize);
+        header.extend_from_slice(b"LASF");
+        header.extend_from_slice(&0u16.to_le_bytes()); // file source ID
+        header.extend_from_slice(&0u16.to_le_bytes()); // global encoding
+        header.extend_from_slice(&[0; 16]); // project ID
+        header.extend_from_slice(&[1, 2]); // version
+        header.extend_from_slice(&padded(b"")); // system identifier
+        header.extend_from_slice(&padded(format!("imagenex831l {}", crate::VERSION).as_bytes()));
+        header.extend_from_slice(&0u16.to_le_bytes()); // creation day of year
+        header.extend_from_slice(&0u16.to_le_bytes()); // creation year
+        header.extend_from_slice(&LAS_HEADER_SIZE.to_le_bytes());
+        header.extend_from_slice(&(LAS_HEADER_SIZE as u32).to_le_bytes()); // offset to points
+        header.extend_from_slice(&0u32.to_le_bytes()); // variable length records
+        header.push(0); // point data format
+        header.extend_from_slice(&LAS_RECORD_SIZE.to_le_bytes());
+        header.extend_from_slice(&count.to_le_bytes());
+        header.extend_from_slice(&count.to_le_bytes()); // points of the first return
+        header.extend_from_slice(&[0; 16]); // points of the other returns
+        for value in [LAS_SCALE, LAS_SCALE, LAS_SCALE, 0.0, 0.0, 0.0] {
+            header.extend_from_slice(&value.to_le_bytes()); // scales and offsets
+        }
+        for value in [max_x, min_x, max_y, min_y, max_z, min_z] {
+            header.extend_from_slice(&value.to_le_bytes());
+        }
+        debug_assert_eq!(LAS_HEADER_SIZE as usize, header.len());
+        writer.write_all(&header)?;
+
+        let coordinate = |value: f32| ((value as f64 / LAS_SCALE).round() as i32).to_le_bytes();
+        for point in &self.points {
+            writer.write_all(&coordinate(point.x))?;
+            writer.write_all(&coordinate(point.y))?;
+            writer.write_all(&coordinate(point.z))?;
+            let intensity = match self.intensity {
+                true => (point.intensity.clamp(0.0, 1.0) * u16::MAX as f32).round() as u16,
+                false => 0,
+            };
+            writer.write_all(&intensity.to_le_bytes())?;
+            writer.write_all(&[0b0000_1001, 0, 0, 0])?; // return 1 of 1, unclassified
+            writer.write_all(&0u16.to_le_bytes())?; // point source ID
+        }
+        Ok(())
+    }
+
+    /// Encode the cloud in `format` to `writer`.
+    pub fn write<W: Write>(&self, writer: W, format: CloudFormat) -> Result<()> {
+        match format {
+            CloudFormat::PlyAscii => self.write_ply_ascii(writer),
+            CloudFormat::PlyBinary => self.write_ply_binary(writer),
+            CloudFormat::Xyz => self.write_xyz(writer),
+            CloudFormat::Las => self.write_las(writer),
+        }
+    }
+
+    /// Save the cloud to `path` in `format`, or in the [CloudFormat] of its extension.
+    pub fn save<P: AsRef<Path>>(&self, path: P, format: Option<CloudFormat>) -> Result<()> {
+        let path = path.as_ref();
+        let format = format.or_else(|| CloudFormat::from_path(path)).ok_or_else(|| {
+            let path = path.display();
+            Error::new(format!("Unknown point cloud format for `{path}`, use .ply, .xyz or .las"))
+        })?;
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer, format)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// `text` padded with zeros to the 32 bytes of a LAS header string.
+fn padded(text: &[u8]) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    let len = text.len().min(bytes.len());
+    bytes[..len].copy_from_slice(&text[..len]);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::shot_bytes;
+    use crate::types::{Direction, HeadPosition};
+    use binrw::BinRead;
+    use std::io::Cursor;
+
+    use log::info;
+    use test_log::test;
+
+    fn cloud(intensity: bool) -> PointCloud {
+        let points = vec![
+            CloudPoint { x: 0.0, y: 0.5, z: -0.25, intensity: 1.0 },
+            CloudPoint { x: 1.5, y: -0.125, z: 0.75, intensity: 0.5 },
+        ];
+        PointCloud { points, intensity, level: false }
+    }
+
+    // keeps the raw zero profile range of the fixture, which marks no detection, for `None`
+    fn shot(distance: f32, angle: f32, profile_range: Option<f32>) -> Shot {
+        let mut bytes = shot_bytes();
+        bytes[107..111].copy_from_slice(&distance.to_be_bytes());
+
+        let mut shot = Shot::read(&mut Cursor::new(bytes)).expect("It should not return an error");
+        let header = shot.sonar_return.header_mut();
+        header.head_position = HeadPosition::new(angle, Direction::Clockwise);
+        if let Some(profile_range) = profile_range {
+            header.profile_range = profile_range;
+        }
+        shot
+    }
+
+    #[test]
+    fn from_shots() {
+        // the fast steps place the points 0.45° counterclockwise of the head positions
+        let shots =
+            vec![shot(0.0, 0.45, Some(0.5)), shot(0.25, 90.45, Some(0.5)), shot(0.5, 0.0, None)];
+        let cloud = PointCloud::from_shots(&shots, true);
+        info!("Got {:?}", cloud.points());
+
+        assert_eq!(2, cloud.len());
+        let cases = vec![(0, (0.0, 0.0, 0.5)), (1, (0.25, -0.5, 0.0))];
+        for (i, (x, y, z)) in cases {
+            let point = cloud.points()[i];
+            assert!((x - point.x).abs() < 1e-5);
+            assert!((y - point.y).abs() < 1e-5);
+            assert!((z - point.z).abs() < 1e-5);
+        }
+
+        // the fixture echo rises by one step per sample
+        let want = shot(0.0, 0.0, None)
+            .echo_profile()
+            .iter()
+            .find(|&(range, _)| range >= 0.5)
+            .map(|(_, intensity)| intensity);
+        assert_eq!(want, Some(cloud.points()[0].intensity));
+    }
+
+    #[test]
+    fn level() {
+        let mut bytes = shot_bytes();
+        bytes[34] = 1; // sensor available
+        bytes[99..103].copy_from_slice(&30f32.to_be_bytes()); // pitch
+        bytes[107..111].copy_from_slice(&1f32.to_be_bytes()); // distance
+
+        let mut shot = Shot::read(&mut Cursor::new(bytes)).expect("It should not return an error");
+        let header = shot.sonar_return.header_mut();
+        header.head_position = HeadPosition::new(0.45, Direction::Clockwise);
+        header.profile_range = 0.5;
+
+        let cases = vec![(false, (1.0, 0.0, 0.5)), (true, (1.0 - 0.25, 0.0, 0.4330127))];
+        for (level, (x, y, z)) in cases {
+            info!("Pushing a shot pitched 30° with leveling {level}, want ({x}, {y}, {z})");
+            let mut cloud = PointCloud::new(false).with_level(level);
+            assert!(cloud.push(&shot));
+
+            let point = cloud.points()[0];
+            assert!((x - point.x).abs() < 1e-5);
+            assert!((y - point.y).abs() < 1e-5);
+            assert!((z - point.z).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn distance_invalid() {
+        let mut bytes = shot_bytes();
+        bytes[98] = 0b0000_0011; // sensor information: distance invalid
+        let mut shot = Shot::read(&mut Cursor::new(bytes)).expect("It should not return an error");
+        shot.sonar_return.header_mut().profile_range = 0.5;
+
+        let mut cloud = PointCloud::new(false);
+        assert!(!cloud.push(&shot));
+        assert!(cloud.is_empty());
+    }
+
+    #[test]
+    fn ply_ascii() {
+        let cases = vec![
+            (
+                false,
+                "ply\nformat ascii 1.0\nelement vertex 2\nproperty float x\nproperty float y\n\
+                 property float z\nend_header\n0 0.5 -0.25\n1.5 -0.125 0.75\n",
+            ),
+            (
+                true,
+                "ply\nformat ascii 1.0\nelement vertex 2\nproperty float x\nproperty float y\n\
+                 property float z\nproperty float intensity\nend_header\n0 0.5 -0.25 1\n\
+                 1.5 -0.125 0.75 0.5\n",
+            ),
+        ];
+
+        for (intensity, want) in cases {
+            info!("Writing ASCII PLY with intensity {intensity}");
+            let mut got = Vec::new();
+            cloud(intensity).write_ply_ascii(&mut got).expect("It should not return an error");
+            assert_eq!(want, String::from_utf8(got).unwrap());
+        }
+    }
+
+    #[test]
+    fn ply_binary() {
+        let mut got = Vec::new();
+        cloud(true).write_ply_binary(&mut got).expect("It should not return an error");
+
+        let header = cloud(true).ply_header("binary_little_endian");
+        assert!(header.starts_with("ply\nformat binary_little_endian 1.0\n"));
+        assert_eq!(header.as_bytes(), &got[..header.len()]);
+
+        let body = &got[header.len()..];
+        assert_eq!(2 * 4 * 4, body.len());
+        assert_eq!(&1.5f32.to_le_bytes(), &body[16..20]);
+        assert_eq!(&0.5f32.to_le_bytes(), &body[28..32]);
+    }
+
+    #[test]
+    fn xyz() {
+        let mut got = Vec::new();
+        cloud(false).write_xyz(&mut got).expect("It should not return an error");
+        assert_eq!("0 0.5 -0.25\n1.5 -0.125 0.75\n", String::from_utf8(got).unwrap());
+    }
+
+    #[test]
+    fn las() {
+        let mut got = Vec::new();
+        cloud(true).write_las(&mut got).expect("It should not return an error");
+
+        let u32_at = |at: usize| u32::from_le_bytes(got[at..at + 4].try_into().unwrap());
+        let i32_at = |at: usize| i32::from_le_bytes(got[at..at + 4].try_into().unwrap());
+        let f64_at = |at: usize| f64::from_le_bytes(got[at..at + 8].try_into().unwrap());
+
+        assert_eq!(b"LASF", &got[..4]);
+        assert_eq!(&[1, 2], &got[24..26]);
+        assert_eq!(227 + 2 * 20, got.len());
+        assert_eq!(227, u32_at(96));
+        assert_eq!(2, u32_at(107));
+        assert_eq!((1.5, 0.0), (f64_at(179), f64_at(187)));
+        assert_eq!((0.75, -0.25), (f64_at(211), f64_at(219)));
+
+        let record = 227 + 20;
+        assert_eq!((15000, -1250, 7500), (i32_at(record), i32_at(record + 4), i32_at(record + 8)));
+        assert_eq!(32768, u16::from_le_bytes([got[record + 12], got[record + 13]]));
+    }
+
+    #[test]
+    fn format_from_path() {
+        let cases = vec![
+            ("survey.ply", Some(CloudFormat::PlyBinary)),
+            ("survey.XYZ", Some(CloudFormat::Xyz)),
+            ("survey.las", Some(CloudFormat::Las)),
+            ("survey.laz", None),
+        ];
+
+        for (path, want) in cases {
+            info!("Getting the format of {path}, want {want:?}");
+            assert_eq!(want, CloudFormat::from_path(path));
+        }
+    }
+}
@@ -0,0 +1,359 @@
+//! Decoding of the echo samples packed in the sonar return data.
+use crate::types::{primitive::u14, DataBits, DataPoints, RangeCode};
+
+#[cfg(feature = "pyo3")]
+use pyo3::prelude::*;
+
+const MASK_NIBBLE: u8 = 0b0000_1111;
+const SHIFT_NIBBLE: usize = 4;
+
+/// The echo intensities of a sonar return, unpacked according to their [DataBits].
+///
+/// Samples are packed in the data as follows:
+/// - [DataBits::X4Bits]: two samples per byte, the first in the high nibble.
+/// - [DataBits::X8Bits]: one sample per byte.
+/// - [DataBits::X14Bits]: one sample per two bytes, in the format of the
+///   [u14](crate::types::primitive::u14) primitive.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "pyo3", pyclass(eq))]
+pub struct Echo {
+    data_bits: DataBits,
+    samples: Vec<u16>,
+}
+
+impl Echo {
+    /// Unpack up to `points` samples of `data_bits` each from `data`.
+    ///
+    /// Data too short for all points yields only the samples it holds in full.
+    pub fn decode(data: &[u8], data_bits: DataBits, points: DataPoints) -> Self {
+        let samples: Vec<u16> = match data_bits {
+            DataBits::X4Bits => data
+                .iter()
+                .flat_map(|&byte| [byte >> SHIFT_NIBBLE, byte & MASK_NIBBLE])
+                .map(u16::from)
+                .take(points.points())
+                .collect(),
+            DataBits::X8Bits => {
+                data.iter().map(|&byte| byte as u16).take(points.points()).collect()
+            },
+            DataBits::X14Bits => data
+                .chunks_exact(2)
+                .map(|pair| u14::from_bytes([pair[0], pair[1]]))
+                .take(points.points())
+                .collect(),
+        };
+
+        Self { data_bits, samples }
+    }
+
+    /// The resolution of the samples.
+    #[inline]
+    pub fn data_bits(&self) -> DataBits {
+        self.data_bits
+    }
+
+    /// The largest possible sample for the [Echo::data_bits].
+    #[inline]
+    pub fn max(&self) -> u16 {
+        ((1u32 << self.data_bits.bits()) - 1) as u16
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// The raw samples, from the nearest to the farthest.
+    #[inline]
+    pub fn raw(&self) -> &[u16] {
+        &self.samples
+    }
+
+    #[inline]
+    pub fn into_raw(self) -> Vec<u16> {
+        self.samples
+    }
+
+    /// The samples scaled to `0.0..=1.0` by the [Echo::max].
+    pub fn normalized(&self) -> Vec<f32> {
+        let max = self.max() as f32;
+        self.samples.iter().map(|&sample| sample as f32 / max).collect()
+    }
+}
+
+/// An [Echo] annotated with the range of each sample in meters.
+///
+/// Ranges come from the [RangeCode::ranges] table, corrected for the filter delay and the sound
+/// velocity recorded with the shot.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "pyo3", pyclass(eq))]
+pub struct EchoProfile {
+    echo: Echo,
+    range_code: RangeCode,
+    sound_velocity: f32,
+    ranges: Vec<f32>,
+}
+
+impl EchoProfile {
+    /// Annotate `echo` with the ranges of `range_code` at `sound_velocity` in m/s.
+    pub fn new(echo: Echo, range_code: RangeCode, sound_velocity: f32) -> Self {
+        let ranges = range_code.ranges(sound_velocity).into_iter().take(echo.len()).collect();
+        Self { echo, range_code, sound_velocity, ranges }
+    }
+
+    #[inline]
+    pub fn echo(&self) -> &Echo {
+        &self.echo
+    }
+
+    #[inline]
+    pub fn range_code(&self) -> RangeCode {
+        self.range_code
+    }
+
+    /// The sound velocity the ranges were scaled to, in m/s.
+    #[inline]
+    pub fn sound_velocity(&self) -> f32 {
+        self.sound_velocity
+    }
+
+    /// The range of each sample in meters.
+    #[inline]
+    pub fn ranges(&self) -> &[f32] {
+        &self.ranges
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Iterate over the (range in meters, normalized intensity) pairs of the samples.
+    pub fn iter(&self) -> impl Iterator<Item = (f32, f32)> + '_ {
+        let max = self.echo.max() as f32;
+        self.ranges
+            .iter()
+            .zip(self.echo.raw())
+            .map(move |(&range, &sample)| (range, sample as f32 / max))
+    }
+}
+
+#[cfg(feature = "pyo3")]
+#[pymethods]
+impl EchoProfile {
+    #[getter(echo)]
+    pub(crate) fn py_echo(&self) -> Echo {
+        self.echo.clone()
+    }
+
+    #[getter(range_code)]
+    pub(crate) fn py_range_code(&self) -> RangeCode {
+        self.range_code
+    }
+
+    #[getter(sound_velocity)]
+    pub(crate) fn py_sound_velocity(&self) -> f32 {
+        self.sound_velocity
+    }
+
+    #[pyo3(name = "ranges")]
+    pub(crate) fn py_ranges(&self) -> Vec<f32> {
+        self.ranges.clone()
+    }
+
+    #[pyo3(name = "pairs")]
+    pub(crate) fn py_pairs(&self) -> Vec<(f32, f32)> {
+        self.iter().collect()
+    }
+
+    pub(crate) fn __len__(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(feature = "pyo3")]
+#[pymethods]
+impl Echo {
+    #[getter(data_bits)]
+    pub(crate) fn py_data_bits(&self) -> DataBits {
+        self.data_bits()
+    }
+
+    #[pyo3(name = "max")]
+    pub(crate) fn py_max(&self) -> u16 {
+        self.max()
+    }
+
+    #[pyo3(name = "raw")]
+    pub(crate) fn py_raw(&self) -> Vec<u16> {
+        self.samples.clone()
+    }
+
+    #[pyo3(name = "normalized")]
+    pub(crate) fn py_normalized(&self) -> Vec<f32> {
+        self.normalized()
+    }
+
+    pub(crate) fn __len__(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{shot_bytes, sonar_return_bytes};
+    use crate::{DecodeMode, Shot, SonarReturn, ENDIAN};
+    use binrw::BinRead;
+    use std::io::Cursor;
+
+    use log::info;
+    use test_log::test;
+
+    const POINTS: DataPoints = DataPoints::X250Points;
+
+    #[test]
+    fn decode() {
+        let cases = vec![
+            (DataBits::X4Bits, vec![0x12, 0xF0, 0x0F], vec![0x1, 0x2, 0xF, 0x0, 0x0, 0xF]),
+            (DataBits::X8Bits, vec![0x00, 0x7F, 0xFF], vec![0x00, 0x7F, 0xFF]),
+            (
+                DataBits::X14Bits,
+                vec![0b0111_1111, 0b0111_1111, 0b0010_1010, 0b0101_0101, 0x01, 0x00],
+                vec![0b0011_1111_1111_1111, 0b0010_1010_1010_1010, 0x0001],
+            ),
+        ];
+
+        for (data_bits, data, want) in cases {
+            info!("Decoding {data:?} as {data_bits}, want {want:?}");
+            let echo = Echo::decode(&data, data_bits, POINTS);
+            assert_eq!(data_bits, echo.data_bits());
+            assert_eq!(want, echo.raw());
+        }
+    }
+
+    #[test]
+    fn decode_points() {
+        let data = vec![0x11; 300];
+        let cases =
+            vec![(DataBits::X4Bits, 250), (DataBits::X8Bits, 250), (DataBits::X14Bits, 150)];
+
+        for (data_bits, want) in cases {
+            info!("Decoding {} bytes as {data_bits}, want {want} samples", data.len());
+            let echo = Echo::decode(&data, data_bits, POINTS);
+            assert_eq!(want, echo.len());
+        }
+
+        let echo = Echo::decode(&[0x11; 5], DataBits::X14Bits, POINTS);
+        assert_eq!(2, echo.len());
+    }
+
+    #[test]
+    fn normalized() {
+        let cases = vec![
+            (DataBits::X4Bits, vec![0x0F, 0x80], vec![0.0, 1.0, 8.0 / 15.0, 0.0]),
+            (DataBits::X8Bits, vec![0x00, 0xFF], vec![0.0, 1.0]),
+            (DataBits::X14Bits, vec![0x7F, 0x7F, 0x00, 0x00], vec![1.0, 0.0]),
+        ];
+
+        for (data_bits, data, want) in cases {
+            info!("Normalizing {data:?} as {data_bits}, want {want:?}");
+            let echo = Echo::decode(&data, data_bits, POINTS);
+            assert_eq!(want, echo.normalized());
+        }
+    }
+
+    #[test]
+    fn max() {
+        let cases =
+            vec![(DataBits::X4Bits, 15), (DataBits::X8Bits, 255), (DataBits::X14Bits, 16383)];
+
+        for (data_bits, want) in cases {
+            info!("Getting the maximum sample of {data_bits}, want {want}");
+            let echo = Echo::decode(&[], data_bits, POINTS);
+            assert_eq!(want, echo.max());
+            assert!(echo.is_empty());
+        }
+    }
+
+    #[test]
+    fn shot_echo() {
+        let shot =
+            Shot::read(&mut Cursor::new(shot_bytes())).expect("It should not return an error");
+        let echo = shot.echo();
+
+        let want: Vec<u16> = (0..250).collect();
+        assert_eq!(DataBits::X8Bits, echo.data_bits());
+        assert_eq!(want, echo.raw());
+    }
+
+    #[test]
+    fn profile() {
+        let echo = Echo::decode(&[0x00, 0x80, 0xFF], DataBits::X8Bits, POINTS);
+        let profile = EchoProfile::new(echo, RangeCode::X2m, 1560.0);
+
+        let want = vec![(0.03328, 0.0), (0.0416, 128.0 / 255.0), (0.04992, 1.0)];
+        let got: Vec<_> = profile.iter().collect();
+        assert_eq!(want.len(), got.len());
+        for ((want_range, want), (range, got)) in want.into_iter().zip(got) {
+            assert!((want_range - range).abs() < 1e-5, "{want_range} != {range}");
+            assert_eq!(want, got);
+        }
+
+        let echo = Echo::decode(&[0x40; 250], DataBits::X8Bits, POINTS);
+        let profile = EchoProfile::new(echo, RangeCode::X2m, 1560.0);
+        assert_eq!(250, profile.len());
+
+        let (range, _) = profile.iter().last().unwrap();
+        info!("Got final range {range} m");
+        assert!((range - (2.0 + 0.024) * 1.04).abs() < 1e-5);
+    }
+
+    #[test]
+    fn shot_profile() {
+        let shot =
+            Shot::read(&mut Cursor::new(shot_bytes())).expect("It should not return an error");
+        let profile = shot.echo_profile();
+
+        assert_eq!(RangeCode::X1m, profile.range_code());
+        assert_eq!(1500.0, profile.sound_velocity());
+        assert_eq!(&RangeCode::X1m.ranges(1500.0)[..], profile.ranges());
+        assert_eq!(&shot.echo(), profile.echo());
+    }
+
+    #[test]
+    fn profile_range_alignment() {
+        let cases = vec![(RangeCode::X1m, 100), (RangeCode::X6m, 10), (RangeCode::X0_125m, 249)];
+
+        for (range_code, peak) in cases {
+            info!("Echoing a target at sample {peak} of {range_code:?}");
+            // the head reports the profile range in samples of the range table
+            let mut bytes = sonar_return_bytes();
+            bytes[7] = range_code as u8;
+            let value = (range_code.table()[peak] / 0.0005).round() as u16;
+            bytes[8..10].copy_from_slice(&u14::to_bytes(value));
+            bytes[32..282].fill(0);
+            bytes[32 + peak] = 0xFF;
+
+            let sonar_return =
+                SonarReturn::read_options(&mut Cursor::new(bytes), ENDIAN, (DecodeMode::Strict,))
+                    .expect("It should not return an error");
+            let echo = Echo::decode(sonar_return.data(), DataBits::X8Bits, POINTS);
+            let profile = EchoProfile::new(echo, range_code, 1500.0);
+
+            let (range, _) = profile.iter().max_by(|a, b| a.1.total_cmp(&b.1)).unwrap();
+            let profile_range = sonar_return.header().profile_range;
+            assert!((profile_range - range).abs() < 1e-3, "{profile_range} != {range}");
+        }
+    }
+}
@@ -1,8 +1,58 @@
 use std::fmt;
 
+/// The kind of an [Error], for callers that need to react to specific failures.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Reading from or writing to the underlying source or sink failed.
+    Io,
+
+    /// The data ended before a complete item could be read.
+    Truncated,
+
+    /// The data does not begin with the expected magic bytes.
+    BadMagic,
+
+    /// A field value is outside of its valid range.
+    OutOfRange { value: f32, min: f32, max: f32 },
+
+    /// A field value is not a valid discriminant of its enumeration, if known.
+    InvalidDiscriminant { value: Option<u64> },
+
+    /// The date or time could not be parsed.
+    Timestamp,
+
+    /// A string is not valid UTF-8.
+    Utf8,
+
+    /// Any other structural check failed, such as a length or termination byte.
+    InvalidData,
+
+    /// The sonar head did not respond in time.
+    Timeout,
+
+    /// The logger could not be initialized.
+    Logging,
+
+    /// Any other failure.
+    Other,
+}
+
 #[derive(thiserror::Error, Debug)]
 pub struct Error {
+    pub kind: ErrorKind,
     pub message: String,
+    /// The byte offset in the source at which decoding failed, if known.
+    pub offset: Option<u64>,
+    /// The index of the shot that failed, counted as the shots decoded before it.
+    ///
+    /// Skipping data with `resynchronize` does not advance the count, so in a damaged source it
+    /// can trail the physical position of the shot. After `seek_shot` or seeking, counting
+    /// restarts from the shot index estimated from the byte offset, or read from the
+    /// [ShotIndex](crate::ShotIndex) of a [Reader](crate::Reader) that has built one.
+    pub shot: Option<usize>,
+    /// The path of the binary field that failed to decode (e.g. `header.start_gain`), if known.
+    pub field: Option<String>,
     source: Option<Box<dyn std::error::Error + Send + Sync + 'static>>,
     #[cfg(feature = "nightly")]
     backtrace: std::backtrace::Backtrace,
@@ -10,13 +60,205 @@ pub struct Error {
 
 impl Error {
     pub fn new(message: String) -> Self {
+        Self::with_kind(ErrorKind::Other, message, None)
+    }
+
+    /// Create an [ErrorKind::InvalidData] error for a failed structural check.
+    pub(crate) fn invalid_data(message: String) -> Self {
+        Self::with_kind(ErrorKind::InvalidData, message, None)
+    }
+
+    /// Create an [ErrorKind::Timeout] error for a sonar head that did not respond.
+    pub(crate) fn timeout(message: String) -> Self {
+        Self::with_kind(ErrorKind::Timeout, message, None)
+    }
+
+    fn with_kind(
+        kind: ErrorKind,
+        message: String,
+        source: Option<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    ) -> Self {
         Self {
+            kind,
             message,
-            source: None,
+            offset: None,
+            shot: None,
+            field: None,
+            source,
             #[cfg(feature = "nightly")]
             backtrace: std::backtrace::Backtrace::capture(),
         }
     }
+
+    /// Create an error for a [binrw::Error] raised while decoding the item starting at `offset`.
+    ///
+    /// The failing field path is recovered from the binrw backtrace frames, if present.
+    pub fn decode(error: binrw::Error, offset: u64) -> Self {
+        let kind = classify(&error);
+        let field = decode_field_path(&error);
+        let message = match (&field, &kind) {
+            (_, ErrorKind::Truncated) => format!("Truncated data at byte {offset}"),
+            (Some(field), _) => format!(
+                "Failed to decode field `{field}` at byte {offset}: {}",
+                root_cause_message(&error)
+            ),
+            (None, _) => {
+                format!("Failed to decode data at byte {offset}: {}", root_cause_message(&error))
+            },
+        };
+
+        let mut result = Self::with_kind(kind, message, Some(Box::new(error)));
+        result.offset = Some(offset);
+        result.field = field;
+        result
+    }
+
+    /// Create an error for a [binrw::Error] raised while encoding the item starting at `offset`.
+    pub fn encode(error: binrw::Error, offset: u64) -> Self {
+        let kind = classify(&error);
+        let message =
+            format!("Failed to encode data at byte {offset}: {}", root_cause_message(&error));
+
+        let mut result = Self::with_kind(kind, message, Some(Box::new(error)));
+        result.offset = Some(offset);
+        result
+    }
+
+    /// Attach the index of the shot that failed.
+    pub fn with_shot(mut self, shot: usize) -> Self {
+        self.shot = Some(shot);
+        self
+    }
+
+    #[inline]
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// Whether this error was caused by the data ending before a complete item could be read.
+    #[inline]
+    pub fn is_truncated(&self) -> bool {
+        self.kind == ErrorKind::Truncated
+    }
+}
+
+/// A value outside of its valid range, raised by the primitive parsers and writers.
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+#[error("{name} of {value}{unit} exceeds range of {min} to {max}{unit}")]
+pub(crate) struct OutOfRangeError {
+    pub name: &'static str,
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+    pub unit: &'static str,
+}
+
+impl OutOfRangeError {
+    pub(crate) fn new<T: Into<f64>>(
+        name: &'static str,
+        value: T,
+        min: T,
+        max: T,
+        unit: &'static str,
+    ) -> Self {
+        let (value, min, max) = (value.into() as f32, min.into() as f32, max.into() as f32);
+        Self { name, value, min, max, unit }
+    }
+
+    /// Wrap into a [binrw::Error] at `pos`.
+    pub(crate) fn at(self, pos: u64) -> binrw::Error {
+        binrw::Error::Custom { pos, err: Box::new(self) }
+    }
+}
+
+/// A raw value that is not a valid discriminant of an enumeration.
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+#[error("invalid value {value} for {type_name}")]
+pub(crate) struct InvalidDiscriminantError {
+    pub type_name: &'static str,
+    pub value: u64,
+}
+
+impl InvalidDiscriminantError {
+    /// Wrap into a [binrw::Error] at `pos`.
+    pub(crate) fn at(self, pos: u64) -> binrw::Error {
+        binrw::Error::Custom { pos, err: Box::new(self) }
+    }
+}
+
+/// Determine the [ErrorKind] from the root cause of a [binrw::Error].
+fn classify(error: &binrw::Error) -> ErrorKind {
+    match error.root_cause() {
+        binrw::Error::Io(e) => classify_io(e),
+        binrw::Error::BadMagic { .. } => ErrorKind::BadMagic,
+        binrw::Error::NoVariantMatch { .. } | binrw::Error::EnumErrors { .. } => {
+            ErrorKind::InvalidDiscriminant { value: None }
+        },
+        binrw::Error::Custom { err, .. } => {
+            if let Some(e) = err.downcast_ref::<OutOfRangeError>() {
+                ErrorKind::OutOfRange { value: e.value, min: e.min, max: e.max }
+            } else if let Some(e) = err.downcast_ref::<InvalidDiscriminantError>() {
+                ErrorKind::InvalidDiscriminant { value: Some(e.value) }
+            } else if err.is::<chrono::ParseError>() || err.is::<std::num::ParseIntError>() {
+                ErrorKind::Timestamp
+            } else if err.is::<std::str::Utf8Error>() {
+                ErrorKind::Utf8
+            } else if let Some(e) = err.downcast_ref::<std::io::Error>() {
+                classify_io(e)
+            } else {
+                ErrorKind::Other
+            }
+        },
+        binrw::Error::AssertFail { .. } => ErrorKind::InvalidData,
+        _ => ErrorKind::Other,
+    }
+}
+
+fn classify_io(error: &std::io::Error) -> ErrorKind {
+    match error.kind() {
+        std::io::ErrorKind::UnexpectedEof => ErrorKind::Truncated,
+        std::io::ErrorKind::TimedOut => ErrorKind::Timeout,
+        _ => ErrorKind::Io,
+    }
+}
+
+/// Recover the dotted field path (e.g. `header.start_gain`) from the binrw backtrace frames.
+fn decode_field_path(error: &binrw::Error) -> Option<String> {
+    const PREFIX: &str = "While parsing field '";
+
+    let binrw::Error::Backtrace(backtrace) = error else {
+        return None;
+    };
+
+    // frames are ordered from the innermost field outwards
+    let fields: Vec<&str> = backtrace
+        .frames
+        .iter()
+        .rev()
+        .filter_map(|frame| match frame {
+            binrw::error::BacktraceFrame::Full { message, .. }
+            | binrw::error::BacktraceFrame::Message(message) => message
+                .strip_prefix(PREFIX)
+                .and_then(|rest| rest.split_once('\''))
+                .map(|(field, _)| field),
+            _ => None,
+        })
+        .collect();
+
+    if fields.is_empty() {
+        None
+    } else {
+        Some(fields.join("."))
+    }
+}
+
+/// Describe the root cause of a [binrw::Error] without the backtrace decoration.
+fn root_cause_message(error: &binrw::Error) -> String {
+    match error.root_cause() {
+        binrw::Error::AssertFail { message, .. } => message.clone(),
+        binrw::Error::Custom { err, .. } => err.to_string(),
+        root => root.to_string(),
+    }
 }
 
 impl fmt::Display for Error {
@@ -27,107 +269,192 @@ impl fmt::Display for Error {
 
 impl Default for Error {
     fn default() -> Self {
-        Error {
-            message: "".to_string(),
-            source: None,
-            #[cfg(feature = "nightly")]
-            backtrace: std::backtrace::Backtrace::capture(),
-        }
+        Self::new("".to_string())
     }
 }
 
 impl From<std::io::Error> for Error {
     fn from(error: std::io::Error) -> Self {
-        Error {
-            message: String::from("IO Error"),
-            source: Some(Box::new(error)),
-            #[cfg(feature = "nightly")]
-            backtrace: std::backtrace::Backtrace::capture(),
-        }
+        Self::with_kind(classify_io(&error), String::from("IO Error"), Some(Box::new(error)))
     }
 }
 
 impl From<log::SetLoggerError> for Error {
     fn from(error: log::SetLoggerError) -> Self {
-        Error {
-            message: String::from("Logging Error"),
-            source: Some(Box::new(error)),
-            #[cfg(feature = "nightly")]
-            backtrace: std::backtrace::Backtrace::capture(),
-        }
+        Self::with_kind(ErrorKind::Logging, String::from("Logging Error"), Some(Box::new(error)))
     }
 }
 
 impl From<std::str::Utf8Error> for Error {
     fn from(error: std::str::Utf8Error) -> Self {
-        Error {
-            message: String::from("UTF-8 Encoding Error"),
-            source: Some(Box::new(error)),
-            #[cfg(feature = "nightly")]
-            backtrace: std::backtrace::Backtrace::capture(),
-        }
+        let message = String::from("UTF-8 Encoding Error");
+        Self::with_kind(ErrorKind::Utf8, message, Some(Box::new(error)))
     }
 }
 
 impl From<chrono::ParseError> for Error {
     fn from(error: chrono::ParseError) -> Self {
-        Error {
-            message: String::from("Date/Time Parse Error"),
-            source: Some(Box::new(error)),
-            #[cfg(feature = "nightly")]
-            backtrace: std::backtrace::Backtrace::capture(),
-        }
+        let message = String::from("Date/Time Parse Error");
+        Self::with_kind(ErrorKind::Timestamp, message, Some(Box::new(error)))
     }
 }
 
 impl From<binrw::Error> for Error {
     fn from(error: binrw::Error) -> Self {
-        Error {
-            message: String::from("Binary Encoding Error"),
-            source: Some(Box::new(error)),
-            #[cfg(feature = "nightly")]
-            backtrace: std::backtrace::Backtrace::capture(),
-        }
+        let message = String::from("Binary Encoding Error");
+        Self::with_kind(classify(&error), message, Some(Box::new(error)))
+    }
+}
+
+impl From<OutOfRangeError> for Error {
+    fn from(error: OutOfRangeError) -> Self {
+        let kind = ErrorKind::OutOfRange { value: error.value, min: error.min, max: error.max };
+        Self::with_kind(kind, error.to_string(), Some(Box::new(error)))
+    }
+}
+
+impl From<png::EncodingError> for Error {
+    fn from(error: png::EncodingError) -> Self {
+        let kind = match &error {
+            png::EncodingError::IoError(error) => classify_io(error),
+            _ => ErrorKind::Other,
+        };
+        Self::with_kind(kind, String::from("PNG Encoding Error"), Some(Box::new(error)))
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl From<serialport::Error> for Error {
+    fn from(error: serialport::Error) -> Self {
+        let message = format!("Serial port error: {}", error.description);
+        Self::with_kind(ErrorKind::Io, message, Some(Box::new(error)))
     }
 }
 
 #[cfg(feature = "pyo3")]
 impl From<pyo3::PyErr> for Error {
     fn from(error: pyo3::PyErr) -> Self {
-        Error {
-            message: error.to_string(),
-            source: Some(Box::new(error)),
-            #[cfg(feature = "nightly")]
-            backtrace: std::backtrace::Backtrace::capture(),
-        }
+        Self::with_kind(ErrorKind::Other, error.to_string(), Some(Box::new(error)))
     }
 }
 
+/// The Python exception classes raised for each [ErrorKind].
 #[cfg(feature = "pyo3")]
-impl Into<pyo3::PyErr> for Error {
-    fn into(self) -> pyo3::PyErr {
-        use pyo3::exceptions::*;
-
-        // Match specific error sources to Python exceptions
-        if let Some(source) = self.source {
-            if let Some(io_error) = source.downcast_ref::<std::io::Error>() {
-                return PyIOError::new_err(format!("IO Error: {}", io_error));
-            }
-            if let Some(utf8_error) = source.downcast_ref::<std::str::Utf8Error>() {
-                return PyValueError::new_err(format!("UTF-8 Encoding Error: {}", utf8_error));
-            }
-            if let Some(parse_error) = source.downcast_ref::<chrono::ParseError>() {
-                return PyValueError::new_err(format!("Date/Time Parse Error: {}", parse_error));
-            }
-            if let Some(binrw_error) = source.downcast_ref::<binrw::Error>() {
-                return PyRuntimeError::new_err(format!("Binary Encoding Error: {}", binrw_error));
-            }
-            if let Some(logger_error) = source.downcast_ref::<log::SetLoggerError>() {
-                return PyRuntimeError::new_err(format!("Logging Error: {}", logger_error));
-            }
+pub(crate) mod exceptions {
+    use pyo3::create_exception;
+    use pyo3::exceptions::PyException;
+    use pyo3::prelude::*;
+
+    create_exception!(imagenex831l, Imagenex831lError, PyException, "Base class of all errors.");
+    create_exception!(imagenex831l, TruncatedError, Imagenex831lError, "The data ended early.");
+    create_exception!(imagenex831l, BadMagicError, Imagenex831lError, "The magic bytes are wrong.");
+    create_exception!(
+        imagenex831l,
+        OutOfRangeError,
+        Imagenex831lError,
+        "A field value is outside of its valid range."
+    );
+    create_exception!(
+        imagenex831l,
+        InvalidDiscriminantError,
+        Imagenex831lError,
+        "A field value is not a valid member of its enumeration."
+    );
+    create_exception!(
+        imagenex831l,
+        TimestampError,
+        Imagenex831lError,
+        "The date or time could not be parsed."
+    );
+    create_exception!(imagenex831l, EncodingError, Imagenex831lError, "A string is not UTF-8.");
+    create_exception!(
+        imagenex831l,
+        InvalidDataError,
+        Imagenex831lError,
+        "A structural check on the data failed."
+    );
+
+    /// Register the exception classes on the module.
+    pub(crate) fn register(module: &Bound<'_, PyModule>) -> PyResult<()> {
+        let py = module.py();
+        module.add("Imagenex831lError", py.get_type::<Imagenex831lError>())?;
+        module.add("TruncatedError", py.get_type::<TruncatedError>())?;
+        module.add("BadMagicError", py.get_type::<BadMagicError>())?;
+        module.add("OutOfRangeError", py.get_type::<OutOfRangeError>())?;
+        module.add("InvalidDiscriminantError", py.get_type::<InvalidDiscriminantError>())?;
+        module.add("TimestampError", py.get_type::<TimestampError>())?;
+        module.add("EncodingError", py.get_type::<EncodingError>())?;
+        module.add("InvalidDataError", py.get_type::<InvalidDataError>())?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "pyo3")]
+impl From<Error> for pyo3::PyErr {
+    fn from(error: Error) -> Self {
+        use exceptions::*;
+        use pyo3::exceptions::{PyIOError, PyTimeoutError};
+
+        let message = error.to_string();
+        match error.kind {
+            ErrorKind::Io => PyIOError::new_err(message),
+            ErrorKind::Truncated => TruncatedError::new_err(message),
+            ErrorKind::BadMagic => BadMagicError::new_err(message),
+            ErrorKind::OutOfRange { .. } => OutOfRangeError::new_err(message),
+            ErrorKind::InvalidDiscriminant { .. } => InvalidDiscriminantError::new_err(message),
+            ErrorKind::Timestamp => TimestampError::new_err(message),
+            ErrorKind::Utf8 => EncodingError::new_err(message),
+            ErrorKind::InvalidData => InvalidDataError::new_err(message),
+            ErrorKind::Timeout => PyTimeoutError::new_err(message),
+            ErrorKind::Logging | ErrorKind::Other => Imagenex831lError::new_err(message),
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::ErrorKind::{PermissionDenied, TimedOut, UnexpectedEof};
+
+    use log::info;
+    use test_log::test;
+
+    #[test]
+    fn decode_kind() {
+        let cases = vec![
+            (binrw::Error::Io(UnexpectedEof.into()), ErrorKind::Truncated),
+            (binrw::Error::Io(PermissionDenied.into()), ErrorKind::Io),
+            (binrw::Error::Io(TimedOut.into()), ErrorKind::Timeout),
+            (binrw::Error::BadMagic { pos: 0, found: Box::new(0) }, ErrorKind::BadMagic),
+            (
+                binrw::Error::NoVariantMatch { pos: 0 },
+                ErrorKind::InvalidDiscriminant { value: None },
+            ),
+            (
+                OutOfRangeError::new("Start gain", 41u8, 0, 40, " dB").at(0),
+                ErrorKind::OutOfRange { value: 41.0, min: 0.0, max: 40.0 },
+            ),
+            (
+                InvalidDiscriminantError { type_name: "Mode", value: 3 }.at(0),
+                ErrorKind::InvalidDiscriminant { value: Some(3) },
+            ),
+            (
+                binrw::Error::AssertFail { pos: 0, message: "assertion".to_string() },
+                ErrorKind::InvalidData,
+            ),
+        ];
+
+        for (error, want) in cases {
+            info!("Classifying {error:?}, want {want:?}");
+            let got = Error::decode(error, 42);
+            assert_eq!(want, got.kind);
+            assert_eq!(Some(42), got.offset);
+        }
+    }
 
-        // Fallback to a generic Python exception
-        PyRuntimeError::new_err(self.message)
+    #[test]
+    fn out_of_range_message() {
+        let error = OutOfRangeError::new("Absorption", 2.56f32, 0.0, 2.55, " dB/m");
+        assert_eq!("Absorption of 2.56 dB/m exceeds range of 0 to 2.55 dB/m", error.to_string());
     }
 }
@@ -0,0 +1,740 @@
+//! Robust circle and ellipse fits of the pipe cross-section.
+use crate::{ProfilePoint, Sweep};
+use std::f64::consts::PI;
+
+#[cfg(feature = "pyo3")]
+use pyo3::prelude::*;
+
+/// The factor from the median absolute residual to the standard deviation of normal residuals.
+const MAD_SCALE: f64 = 1.4826;
+
+/// The smallest outlier threshold, in meters, so exact fits keep all of their points.
+const MIN_THRESHOLD: f64 = 1e-6;
+
+/// The number of Gauss-Newton iterations refining the algebraic circle fit.
+const REFINE_ITERATIONS: usize = 10;
+
+/// The number of angular bins the [CircleFit::coverage] and [EllipseFit::coverage] count.
+const COVERAGE_BINS: usize = 36;
+
+/// How outliers are rejected by [CircleFit::fit_with] and [EllipseFit::fit_with].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FitOptions {
+    /// Points with residuals beyond this many robust standard deviations are outliers.
+    pub threshold: f32,
+
+    /// The most rounds of fitting and rejecting outliers.
+    pub iterations: usize,
+}
+
+impl Default for FitOptions {
+    fn default() -> Self {
+        Self { threshold: 3.0, iterations: 10 }
+    }
+}
+
+/// A circle fitted to the profile points of a pipe cross-section.
+///
+/// The circle is fitted algebraically, refined geometrically and refitted without the points
+/// whose residuals exceed the [FitOptions::threshold], until the inliers no longer change.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "pyo3", pyclass(eq))]
+pub struct CircleFit {
+    center: (f32, f32),
+    radius: f32,
+    residuals: Vec<f32>,
+    inliers: Vec<bool>,
+    rms: f32,
+    coverage: f32,
+}
+
+impl CircleFit {
+    /// Fit a circle to `points` with the default [FitOptions], or `None` for fewer than three
+    /// points or points on a line.
+    pub fn fit(points: &[ProfilePoint]) -> Option<Self> {
+        Self::fit_with(points, &FitOptions::default())
+    }
+
+    /// Fit a circle to `points`, rejecting outliers per `options`.
+    pub fn fit_with(points: &[ProfilePoint], options: &FitOptions) -> Option<Self> {
+        let points = to_f64(points);
+        let fit = robust(&points, 3, options, fit_circle, circle_residual)?;
+        let ((cx, cy, r), residuals, inliers) = fit;
+
+        Some(Self {
+            center: (cx as f32, cy as f32),
+            radius: r as f32,
+            rms: rms(&residuals, &inliers),
+            coverage: coverage(&points, &inliers, (cx, cy)),
+            residuals: residuals.iter().map(|&r| r as f32).collect(),
+            inliers,
+        })
+    }
+
+    /// The center of the pipe in the sonar frame, in meters.
+    #[inline]
+    pub fn center(&self) -> (f32, f32) {
+        self.center
+    }
+
+    #[inline]
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    #[inline]
+    pub fn diameter(&self) -> f32 {
+        2.0 * self.radius
+    }
+
+    /// The distance of the sonar head from the center of the pipe, in meters.
+    #[inline]
+    pub fn offset(&self) -> f32 {
+        self.center.0.hypot(self.center.1)
+    }
+
+    /// The distance of each point outside of the circle in meters, negative inside.
+    #[inline]
+    pub fn residuals(&self) -> &[f32] {
+        &self.residuals
+    }
+
+    /// Whether each point was kept in the final fit.
+    #[inline]
+    pub fn inliers(&self) -> &[bool] {
+        &self.inliers
+    }
+
+    /// The root mean square of the residuals of the inliers, in meters.
+    #[inline]
+    pub fn rms(&self) -> f32 {
+        self.rms
+    }
+
+    /// The fraction of the circumference the inliers cover, in 10° bins about the center.
+    #[inline]
+    pub fn coverage(&self) -> f32 {
+        self.coverage
+    }
+
+    /// The quality of the fit in `0.0..=1.0`: the fraction of inliers times the [coverage].
+    ///
+    /// [coverage]: CircleFit::coverage
+    pub fn quality(&self) -> f32 {
+        inlier_fraction(&self.inliers) * self.coverage
+    }
+}
+
+/// An ellipse fitted to the profile points of a pipe cross-section.
+///
+/// The ellipse is fitted as a conic with its quadratic coefficients summing to one, which holds
+/// for every ellipse, and refitted without outliers as for [CircleFit]. Residuals are the Sampson
+/// approximation of the distance to the ellipse.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "pyo3", pyclass(eq))]
+pub struct EllipseFit {
+    center: (f32, f32),
+    semi_major: f32,
+    semi_minor: f32,
+    angle: f32,
+    residuals: Vec<f32>,
+    inliers: Vec<bool>,
+    rms: f32,
+    coverage: f32,
+}
+
+impl EllipseFit {
+    /// Fit an ellipse to `points` with the default [FitOptions], or `None` for fewer than five
+    /// points or points fitting no ellipse.
+    pub fn fit(points: &[ProfilePoint]) -> Option<Self> {
+        Self::fit_with(points, &FitOptions::default())
+    }
+
+    /// Fit an ellipse to `points`, rejecting outliers per `options`.
+    pub fn fit_with(points: &[ProfilePoint], options: &FitOptions) -> Option<Self> {
+        let points = to_f64(points);
+        let (conic, residuals, inliers) = robust(&points, 5, options, fit_conic, conic_residual)?;
+        let ellipse = Ellipse::from_conic(&conic)?;
+
+        Some(Self {
+            center: (ellipse.cx as f32, ellipse.cy as f32),
+            semi_major: ellipse.a as f32,
+            semi_minor: ellipse.b as f32,
+            angle: ellipse.angle as f32,
+            rms: rms(&residuals, &inliers),
+            coverage: coverage(&points, &inliers, (ellipse.cx, ellipse.cy)),
+            residuals: residuals.iter().map(|&r| r as f32).collect(),
+            inliers,
+        })
+    }
+
+    /// The center of the pipe in the sonar frame, in meters.
+    #[inline]
+    pub fn center(&self) -> (f32, f32) {
+        self.center
+    }
+
+    #[inline]
+    pub fn semi_major(&self) -> f32 {
+        self.semi_major
+    }
+
+    #[inline]
+    pub fn semi_minor(&self) -> f32 {
+        self.semi_minor
+    }
+
+    #[inline]
+    pub fn max_diameter(&self) -> f32 {
+        2.0 * self.semi_major
+    }
+
+    #[inline]
+    pub fn min_diameter(&self) -> f32 {
+        2.0 * self.semi_minor
+    }
+
+    /// The ratio of the [max_diameter] to the [min_diameter], one for a round pipe.
+    ///
+    /// [max_diameter]: EllipseFit::max_diameter
+    /// [min_diameter]: EllipseFit::min_diameter
+    #[inline]
+    pub fn ovality(&self) -> f32 {
+        self.semi_major / self.semi_minor
+    }
+
+    /// The angle of the major axis clockwise from up, in degrees within `-90.0..90.0`.
+    #[inline]
+    pub fn angle(&self) -> f32 {
+        self.angle
+    }
+
+    /// The distance of the sonar head from the center of the pipe, in meters.
+    #[inline]
+    pub fn offset(&self) -> f32 {
+        self.center.0.hypot(self.center.1)
+    }
+
+    /// The approximate distance of each point outside of the ellipse in meters, negative inside.
+    #[inline]
+    pub fn residuals(&self) -> &[f32] {
+        &self.residuals
+    }
+
+    /// Whether each point was kept in the final fit.
+    #[inline]
+    pub fn inliers(&self) -> &[bool] {
+        &self.inliers
+    }
+
+    /// The root mean square of the residuals of the inliers, in meters.
+    #[inline]
+    pub fn rms(&self) -> f32 {
+        self.rms
+    }
+
+    /// The fraction of the circumference the inliers cover, in 10° bins about the center.
+    #[inline]
+    pub fn coverage(&self) -> f32 {
+        self.coverage
+    }
+
+    /// The quality of the fit in `0.0..=1.0`: the fraction of inliers times the [coverage].
+    ///
+    /// [coverage]: EllipseFit::coverage
+    pub fn quality(&self) -> f32 {
+        inlier_fraction(&self.inliers) * self.coverage
+    }
+}
+
+impl Sweep {
+    /// The profile points detected by the shots of the sweep.
+    pub fn profile_points(&self) -> Vec<ProfilePoint> {
+        self.shots().iter().filter_map(|shot| shot.profile_point()).collect()
+    }
+
+    /// Fit a circle to the [Sweep::profile_points].
+    pub fn fit_circle(&self) -> Option<CircleFit> {
+        CircleFit::fit(&self.profile_points())
+    }
+
+    /// Fit an ellipse to the [Sweep::profile_points].
+    pub fn fit_ellipse(&self) -> Option<EllipseFit> {
+        EllipseFit::fit(&self.profile_points())
+    }
+}
+
+type Point = (f64, f64);
+
+fn to_f64(points: &[ProfilePoint]) -> Vec<Point> {
+    points.iter().map(|point| (point.x as f64, point.y as f64)).collect()
+}
+
+/// Fit `model` to the inliers of `points` until the points beyond the threshold no longer
+/// change, returning the model with the residuals and inliers of all points.
+fn robust<M>(
+    points: &[Point],
+    min_points: usize,
+    options: &FitOptions,
+    fit: impl Fn(&[Point]) -> Option<M>,
+    residual: impl Fn(&M, Point) -> f64,
+) -> Option<(M, Vec<f64>, Vec<bool>)> {
+    let mut inliers = vec![true; points.len()];
+    let select = |inliers: &[bool]| -> Vec<Point> {
+        points.iter().zip(inliers).filter(|(_, &inlier)| inlier).map(|(&p, _)| p).collect()
+    };
+
+    if points.len() < min_points {
+        return None;
+    }
+
+    let mut model = fit(points)?;
+    for _ in 0..options.iterations {
+        let residuals: Vec<f64> = points.iter().map(|&p| residual(&model, p)).collect();
+        let mut kept: Vec<f64> = residuals
+            .iter()
+            .zip(&inliers)
+            .filter(|(_, &inlier)| inlier)
+            .map(|(r, _)| r.abs())
+            .collect();
+        let sigma = MAD_SCALE * median(&mut kept);
+        let threshold = (options.threshold as f64 * sigma).max(MIN_THRESHOLD);
+
+        let next: Vec<bool> = residuals.iter().map(|r| r.abs() <= threshold).collect();
+        if next == inliers || next.iter().filter(|&&inlier| inlier).count() < min_points {
+            break;
+        }
+
+        inliers = next;
+        model = fit(&select(&inliers))?;
+    }
+
+    let residuals = points.iter().map(|&p| residual(&model, p)).collect();
+    Some((model, residuals, inliers))
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(f64::total_cmp);
+    let middle = values.len() / 2;
+    match values.len() % 2 {
+        0 => (values[middle - 1] + values[middle]) / 2.0,
+        _ => values[middle],
+    }
+}
+
+fn rms(residuals: &[f64], inliers: &[bool]) -> f32 {
+    let kept: Vec<f64> =
+        residuals.iter().zip(inliers).filter(|(_, &inlier)| inlier).map(|(&r, _)| r).collect();
+    match kept.is_empty() {
+        true => 0.0,
+        false => (kept.iter().map(|r| r * r).sum::<f64>() / kept.len() as f64).sqrt() as f32,
+    }
+}
+
+fn inlier_fraction(inliers: &[bool]) -> f32 {
+    match inliers.is_empty() {
+        true => 0.0,
+        false => inliers.iter().filter(|&&inlier| inlier).count() as f32 / inliers.len() as f32,
+    }
+}
+
+fn coverage(points: &[Point], inliers: &[bool], (cx, cy): Point) -> f32 {
+    let mut bins = [false; COVERAGE_BINS];
+    for (&(x, y), _) in points.iter().zip(inliers).filter(|(_, &inlier)| inlier) {
+        let turn = ((x - cx).atan2(y - cy) / (2.0 * PI)).rem_euclid(1.0);
+        bins[((turn * COVERAGE_BINS as f64) as usize).min(COVERAGE_BINS - 1)] = true;
+    }
+    bins.iter().filter(|&&bin| bin).count() as f32 / COVERAGE_BINS as f32
+}
+
+/// Solve the normal equations of the least squares problem with `rows` of coefficients and
+/// right-hand sides.
+fn least_squares<const N: usize>(rows: impl Iterator<Item = ([f64; N], f64)>) -> Option<[f64; N]> {
+    let mut a = [[0.0; N]; N];
+    let mut b = [0.0; N];
+    for (row, rhs) in rows {
+        for i in 0..N {
+            for j in 0..N {
+                a[i][j] += row[i] * row[j];
+            }
+            b[i] += row[i] * rhs;
+        }
+    }
+    solve(a, b)
+}
+
+/// Solve `a · x = b` by Gaussian elimination with partial pivoting.
+fn solve<const N: usize>(mut a: [[f64; N]; N], mut b: [f64; N]) -> Option<[f64; N]> {
+    let scale = a.iter().flatten().fold(0.0f64, |max, v| max.max(v.abs()));
+    for col in 0..N {
+        let pivot = (col..N).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() <= scale * 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        let pivot_row = a[col];
+        for row in col + 1..N {
+            let factor = a[row][col] / pivot_row[col];
+            for (cell, pivot) in a[row].iter_mut().zip(pivot_row).skip(col) {
+                *cell -= factor * pivot;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = [0.0; N];
+    for row in (0..N).rev() {
+        let sum: f64 = (row + 1..N).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
+
+type Circle = (f64, f64, f64);
+
+/// Fit `x² + y² + d·x + e·y + f = 0` to `points`, then refine the distances geometrically.
+fn fit_circle(points: &[Point]) -> Option<Circle> {
+    let [d, e, f] = least_squares(points.iter().map(|&(x, y)| ([x, y, 1.0], -(x * x + y * y))))?;
+    let (mut cx, mut cy) = (-d / 2.0, -e / 2.0);
+    let mut r = (cx * cx + cy * cy - f).max(0.0).sqrt();
+
+    for _ in 0..REFINE_ITERATIONS {
+        let rows = points.iter().filter_map(|&(x, y)| {
+            let distance = (x - cx).hypot(y - cy);
+            match distance > 0.0 {
+                true => Some(([(x - cx) / distance, (y - cy) / distance, 1.0], distance - r)),
+                false => None,
+            }
+        });
+        let Some([dx, dy, dr]) = least_squares(rows) else {
+            break;
+        };
+        cx += dx;
+        cy += dy;
+        r += dr;
+    }
+
+    match r.is_finite() && r > 0.0 {
+        true => Some((cx, cy, r)),
+        false => None,
+    }
+}
+
+fn circle_residual(&(cx, cy, r): &Circle, (x, y): Point) -> f64 {
+    (x - cx).hypot(y - cy) - r
+}
+
+/// The coefficients of `a·x² + b·x·y + c·y² + d·x + e·y + f = 0`.
+type Conic = [f64; 6];
+
+/// Fit a conic with `a + c = 1` to `points`, centered on their mean for conditioning.
+fn fit_conic(points: &[Point]) -> Option<Conic> {
+    let n = points.len() as f64;
+    let mx = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let my = points.iter().map(|p| p.1).sum::<f64>() / n;
+
+    let rows = points.iter().map(|&(x, y)| {
+        let (x, y) = (x - mx, y - my);
+        ([x * x - y * y, x * y, x, y, 1.0], -y * y)
+    });
+    let [a, b, d, e, f] = least_squares(rows)?;
+    let c = 1.0 - a;
+
+    // translate back from the mean
+    Some([
+        a,
+        b,
+        c,
+        d - 2.0 * a * mx - b * my,
+        e - 2.0 * c * my - b * mx,
+        a * mx * mx + b * mx * my + c * my * my - d * mx - e * my + f,
+    ])
+}
+
+fn conic_residual(&[a, b, c, d, e, f]: &Conic, (x, y): Point) -> f64 {
+    let value = a * x * x + b * x * y + c * y * y + d * x + e * y + f;
+    let gradient = (2.0 * a * x + b * y + d).hypot(b * x + 2.0 * c * y + e);
+    match gradient > 0.0 {
+        true => value / gradient,
+        false => 0.0,
+    }
+}
+
+struct Ellipse {
+    cx: f64,
+    cy: f64,
+    a: f64,
+    b: f64,
+    angle: f64,
+}
+
+impl Ellipse {
+    /// The ellipse of `conic`, or `None` if it is not an ellipse.
+    fn from_conic(&[a, b, c, d, e, f]: &Conic) -> Option<Self> {
+        let [cx, cy] = solve([[2.0 * a, b], [b, 2.0 * c]], [-d, -e])?;
+        let f0 = a * cx * cx + b * cx * cy + c * cy * cy + d * cx + e * cy + f;
+
+        // principal axes of the quadratic form
+        let theta = 0.5 * b.atan2(a - c);
+        let (sin, cos) = theta.sin_cos();
+        let lambda_1 = a * cos * cos + b * sin * cos + c * sin * sin;
+        let lambda_2 = a + c - lambda_1;
+
+        let axis_1 = (-f0 / lambda_1).sqrt();
+        let axis_2 = (-f0 / lambda_2).sqrt();
+        if !(axis_1.is_finite() && axis_2.is_finite() && axis_1 > 0.0 && axis_2 > 0.0) {
+            return None;
+        }
+
+        // the major axis as an angle clockwise from up
+        let (major, minor, direction) = match axis_1 >= axis_2 {
+            true => (axis_1, axis_2, theta),
+            false => (axis_2, axis_1, theta + PI / 2.0),
+        };
+        let angle = (direction.cos().atan2(direction.sin()).to_degrees() + 90.0).rem_euclid(180.0);
+
+        Some(Self { cx, cy, a: major, b: minor, angle: angle - 90.0 })
+    }
+}
+
+#[cfg(feature = "pyo3")]
+#[pymethods]
+impl CircleFit {
+    #[getter(center)]
+    pub(crate) fn py_center(&self) -> (f32, f32) {
+        self.center
+    }
+
+    #[getter(radius)]
+    pub(crate) fn py_radius(&self) -> f32 {
+        self.radius
+    }
+
+    #[getter(diameter)]
+    pub(crate) fn py_diameter(&self) -> f32 {
+        self.diameter()
+    }
+
+    #[getter(offset)]
+    pub(crate) fn py_offset(&self) -> f32 {
+        self.offset()
+    }
+
+    #[getter(residuals)]
+    pub(crate) fn py_residuals(&self) -> Vec<f32> {
+        self.residuals.clone()
+    }
+
+    #[getter(inliers)]
+    pub(crate) fn py_inliers(&self) -> Vec<bool> {
+        self.inliers.clone()
+    }
+
+    #[getter(rms)]
+    pub(crate) fn py_rms(&self) -> f32 {
+        self.rms
+    }
+
+    #[getter(quality)]
+    pub(crate) fn py_quality(&self) -> f32 {
+        self.quality()
+    }
+}
+
+#[cfg(feature = "pyo3")]
+#[pymethods]
+impl EllipseFit {
+    #[getter(center)]
+    pub(crate) fn py_center(&self) -> (f32, f32) {
+        self.center
+    }
+
+    #[getter(max_diameter)]
+    pub(crate) fn py_max_diameter(&self) -> f32 {
+        self.max_diameter()
+    }
+
+    #[getter(min_diameter)]
+    pub(crate) fn py_min_diameter(&self) -> f32 {
+        self.min_diameter()
+    }
+
+    #[getter(ovality)]
+    pub(crate) fn py_ovality(&self) -> f32 {
+        self.ovality()
+    }
+
+    #[getter(angle)]
+    pub(crate) fn py_angle(&self) -> f32 {
+        self.angle
+    }
+
+    #[getter(offset)]
+    pub(crate) fn py_offset(&self) -> f32 {
+        self.offset()
+    }
+
+    #[getter(residuals)]
+    pub(crate) fn py_residuals(&self) -> Vec<f32> {
+        self.residuals.clone()
+    }
+
+    #[getter(inliers)]
+    pub(crate) fn py_inliers(&self) -> Vec<bool> {
+        self.inliers.clone()
+    }
+
+    #[getter(rms)]
+    pub(crate) fn py_rms(&self) -> f32 {
+        self.rms
+    }
+
+    #[getter(quality)]
+    pub(crate) fn py_quality(&self) -> f32 {
+        self.quality()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use log::info;
+    use test_log::test;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn point(x: f64, y: f64) -> ProfilePoint {
+        let (x, y) = (x as f32, y as f32);
+        ProfilePoint { angle: x.atan2(y).to_degrees(), range: x.hypot(y), x, y }
+    }
+
+    /// `count` points around the ellipse centered at `center` with semi-axes `a` along x and `b`
+    /// along y, rotated counterclockwise by `rotation` degrees, from `start` to `end` degrees.
+    fn ellipse(
+        center: Point,
+        (a, b): (f64, f64),
+        rotation: f64,
+        (start, end): (f64, f64),
+        count: usize,
+    ) -> Vec<ProfilePoint> {
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        (0..count)
+            .map(|i| (start + (end - start) * i as f64 / count as f64).to_radians())
+            .map(|t| (a * t.cos(), b * t.sin()))
+            .map(|(x, y)| point(center.0 + x * cos - y * sin, center.1 + x * sin + y * cos))
+            .collect()
+    }
+
+    fn close(want: f32, got: f32) -> bool {
+        (want - got).abs() < EPSILON
+    }
+
+    #[test]
+    fn circle() {
+        let cases = vec![
+            ((0.0, 0.0), 0.5, (0.0, 360.0), 1.0),
+            ((0.1, -0.05), 0.3, (0.0, 360.0), 1.0),
+            ((0.02, 0.2), 0.45, (-55.0, 65.0), 13.0 / 36.0),
+        ];
+
+        for (center, radius, arc, coverage) in cases {
+            info!("Fitting a circle of {radius} m at {center:?} over {arc:?}");
+            let points = ellipse(center, (radius, radius), 0.0, arc, 360);
+            let fit = CircleFit::fit(&points).expect("It should fit a circle");
+
+            assert!(close(radius as f32, fit.radius()), "got radius {}", fit.radius());
+            assert!(
+                close(center.0 as f32, fit.center().0) && close(center.1 as f32, fit.center().1)
+            );
+            assert!(close(2.0 * radius as f32, fit.diameter()));
+            assert!(close((center.0 as f32).hypot(center.1 as f32), fit.offset()));
+            assert!(fit.rms() < EPSILON);
+            assert!(fit.inliers().iter().all(|&inlier| inlier));
+            assert!(close(coverage, fit.coverage()), "got coverage {}", fit.coverage());
+        }
+    }
+
+    #[test]
+    fn circle_outliers() {
+        let mut points = ellipse((0.05, 0.0), (0.4, 0.4), 0.0, (0.0, 360.0), 200);
+        let outliers = [10, 50, 51, 120, 180];
+        for &i in &outliers {
+            let p = points[i];
+            points[i] = point(p.x as f64 * 0.5, p.y as f64 * 0.5);
+        }
+
+        let fit = CircleFit::fit(&points).expect("It should fit a circle");
+        info!("Fitted radius {} at {:?}, quality {}", fit.radius(), fit.center(), fit.quality());
+        assert!(close(0.4, fit.radius()));
+        assert!(close(0.05, fit.center().0));
+        for (i, &inlier) in fit.inliers().iter().enumerate() {
+            assert_eq!(!outliers.contains(&i), inlier, "point {i}");
+        }
+        assert!(fit.residuals()[10] < -0.2);
+        assert!(close(195.0 / 200.0, fit.quality()));
+    }
+
+    #[test]
+    fn circle_degenerate() {
+        assert_eq!(None, CircleFit::fit(&[point(0.0, 1.0), point(1.0, 0.0)]));
+
+        let line: Vec<_> = (0..10).map(|i| point(i as f64, 0.0)).collect();
+        assert_eq!(None, CircleFit::fit(&line));
+    }
+
+    #[test]
+    fn ellipse_fit() {
+        let cases = vec![
+            ((0.0, 0.0), (0.5, 0.4), 0.0, -90.0),
+            ((0.05, -0.1), (0.5, 0.45), 30.0, 60.0),
+            ((-0.02, 0.03), (0.3, 0.36), 0.0, 0.0),
+        ];
+
+        for (center, (a, b), rotation, angle) in cases {
+            info!("Fitting an ellipse of {a} by {b} m rotated {rotation}° at {center:?}");
+            let points = ellipse(center, (a, b), rotation, (0.0, 360.0), 360);
+            let fit = EllipseFit::fit(&points).expect("It should fit an ellipse");
+            info!("Got {fit:?}");
+
+            let (major, minor) = (a.max(b) as f32, a.min(b) as f32);
+            assert!(close(2.0 * major, fit.max_diameter()));
+            assert!(close(2.0 * minor, fit.min_diameter()));
+            assert!(close(major / minor, fit.ovality()));
+            assert!(
+                close(center.0 as f32, fit.center().0) && close(center.1 as f32, fit.center().1)
+            );
+            assert!((angle - fit.angle()).abs() < 0.01, "got angle {}", fit.angle());
+            assert!(fit.rms() < EPSILON);
+            assert!(close(1.0, fit.quality()));
+        }
+    }
+
+    #[test]
+    fn ellipse_outliers() {
+        let mut points = ellipse((0.0, 0.0), (0.5, 0.4), 0.0, (0.0, 360.0), 200);
+        for i in [5, 70, 140] {
+            let p = points[i];
+            points[i] = point(p.x as f64 * 1.5, p.y as f64 * 1.5);
+        }
+
+        let fit = EllipseFit::fit(&points).expect("It should fit an ellipse");
+        assert!(close(1.0, fit.max_diameter()) && close(0.8, fit.min_diameter()));
+        assert_eq!(3, fit.inliers().iter().filter(|&&inlier| !inlier).count());
+        assert!(fit.residuals()[5] > 0.1);
+    }
+
+    #[test]
+    fn ellipse_degenerate() {
+        let few = ellipse((0.0, 0.0), (0.5, 0.4), 0.0, (0.0, 360.0), 4);
+        assert_eq!(None, EllipseFit::fit(&few));
+
+        let line: Vec<_> = (0..10).map(|i| point(i as f64, 0.0)).collect();
+        assert_eq!(None, EllipseFit::fit(&line));
+    }
+}
@@ -0,0 +1,72 @@
+//! Synthetic `.31l` data shared by the unit tests.
+
+/// The length of a single encoded shot.
+pub(crate) const SHOT_LENGTH: usize = 512;
+
+/// Build the bytes of a single valid shot.
+pub(crate) fn shot_bytes() -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(SHOT_LENGTH);
+
+    // file header
+    bytes.extend_from_slice(b"31L");
+    bytes.push(2); // data size index: 250 points
+    bytes.extend_from_slice(&512u16.to_be_bytes()); // total length
+    bytes.extend_from_slice(&283u16.to_be_bytes()); // data length
+    bytes.extend_from_slice(b"27-JUL-2023\0");
+    bytes.extend_from_slice(b"10:19:19\0");
+    bytes.extend_from_slice(b".69\0");
+    bytes.push(0); // reserved
+    bytes.push(0); // sensor available
+    bytes.extend_from_slice(&[0; 2]); // reserved
+    bytes.push(0b1100_1010); // motion: clockwise, up, polar, fast
+    bytes.push(6); // start gain
+    bytes.push(120); // sector size: 360°
+    bytes.push(0); // train angle
+    bytes.push(10); // range code: 1 meter
+    bytes.push(170); // absorption: 1.7 dB/m
+    bytes.push(0b0000_1001); // config: 8 bits, 20 dB
+    bytes.push(10); // pulse length: 100 μs
+    bytes.push(0); // reserved
+    bytes.extend_from_slice(&0x8000u16.to_be_bytes()); // sound velocity: 1500 m/s
+    bytes.extend_from_slice(&[0; 31]); // reserved
+    bytes.extend_from_slice(&8u16.to_be_bytes()); // operating frequency
+    bytes.extend_from_slice(&18947u16.to_be_bytes()); // real-time PRF: 189.47 Hz
+    bytes.extend_from_slice(&[0; 15]); // reserved
+    bytes.push(0b0000_0111); // sensor information: all valid
+    bytes.extend_from_slice(&0f32.to_be_bytes()); // pitch
+    bytes.extend_from_slice(&0f32.to_be_bytes()); // roll
+    bytes.extend_from_slice(&0f32.to_be_bytes()); // distance
+    bytes.extend_from_slice(&[0; 117]); // reserved
+
+    // sonar return
+    bytes.extend_from_slice(b"IMX");
+    bytes.push(0); // sonar type: scanning
+    bytes.push(0); // status
+    bytes.extend_from_slice(&[0x58, 0x04]); // head position: 0°, counterclockwise
+    bytes.push(10); // range code: 1 meter
+    bytes.extend_from_slice(&[0x00, 0x00]); // profile range
+    bytes.extend_from_slice(&[0x7A, 0x01]); // data length: 250
+    bytes.extend_from_slice(&[0; 4]); // reserved
+    bytes.extend_from_slice(&[0; 8]); // roll angle, pitch angle, roll & pitch acceleration
+    bytes.extend_from_slice(&[0; 8]); // reserved
+    bytes.extend((0..250).map(|i| i as u8)); // echo data
+    bytes.push(0xFC); // termination byte
+    bytes.push(0); // reserved
+
+    debug_assert_eq!(SHOT_LENGTH, bytes.len());
+    bytes
+}
+
+/// Build the bytes of `count` consecutive valid shots.
+pub(crate) fn shots_bytes(count: usize) -> Vec<u8> {
+    shot_bytes().repeat(count)
+}
+
+/// The bytes of the sonar return of [shot_bytes], as sent by a sonar head.
+pub(crate) fn sonar_return_bytes() -> Vec<u8> {
+    let bytes = shot_bytes();
+    bytes[SHOT_LENGTH - SONAR_RETURN_LENGTH - 1..SHOT_LENGTH - 1].to_vec()
+}
+
+/// The length of the sonar return of a single encoded shot.
+const SONAR_RETURN_LENGTH: usize = 283;
@@ -0,0 +1,564 @@
+//! Placement of profile points in the cross-section of the pipe.
+use crate::types::{Angle, Direction, FileHeader, HeadPosition, SensorAvailable, Transducer, Zero};
+use crate::Shot;
+use std::fmt::{Display, Formatter};
+
+#[cfg(feature = "pyo3")]
+use pyo3::prelude::*;
+
+/// Converts head positions and ranges into points in the sonar frame.
+///
+/// The sonar frame looks forward along the pipe from behind the sonar head, with `x` to the
+/// right, `y` up and angles increasing clockwise from up. Head angles are measured clockwise from
+/// the [Zero] of the head, which is relative to the head itself and so is turned over along with
+/// a [Transducer::Down] mounting.
+///
+/// The profile point of a shot is acquired while the head steps, so it is placed in the middle of
+/// the step: half a step behind the reported position in the [Direction] of the step.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "pyo3", pyclass(eq))]
+pub struct Geometry {
+    transducer: Transducer,
+    zero: Zero,
+    step: f32,
+}
+
+impl Geometry {
+    /// Create a [Geometry] for a head mounted as `transducer` with its `zero`, stepping `step`
+    /// degrees at a time. A `step` of zero places points at the reported head positions.
+    pub fn new(transducer: Transducer, zero: Zero, step: f32) -> Self {
+        Self { transducer, zero, step }
+    }
+
+    /// The [Geometry] recorded in the [MotionConfig](crate::types::MotionConfig) and
+    /// [Config](crate::types::Config) of `header`.
+    pub fn from_header(header: &FileHeader) -> Self {
+        let motion = header.motion();
+        Self::new(motion.transducer, header.config().zero, motion.step_size.degrees())
+    }
+
+    /// The angle of `head_position` in the sonar frame, in degrees within `-180.0..180.0`.
+    pub fn angle(&self, head_position: &HeadPosition) -> f32 {
+        let half_step = match head_position.direction {
+            Direction::Clockwise => -self.step / 2.0,
+            Direction::Counterclockwise => self.step / 2.0,
+        };
+
+        let mut angle = head_position.angle + half_step;
+        if self.zero == Zero::Down {
+            angle += 180.0;
+        }
+        if self.transducer == Transducer::Down {
+            angle += 180.0;
+        }
+
+        (angle + 180.0).rem_euclid(360.0) - 180.0
+    }
+
+    /// Place the point at `range` meters from the head at `head_position`.
+    pub fn point(&self, head_position: &HeadPosition, range: f32) -> ProfilePoint {
+        let angle = self.angle(head_position);
+        let (sin, cos) = angle.to_radians().sin_cos();
+        ProfilePoint { angle, range, x: range * sin, y: range * cos }
+    }
+
+    /// The profile point of `shot`, or `None` if no profile point was detected.
+    pub fn profile_point(&self, shot: &Shot) -> Option<ProfilePoint> {
+        let header = shot.sonar_return.header();
+        match header.profile_detected() {
+            true => Some(self.point(&header.head_position, header.profile_range)),
+            false => None,
+        }
+    }
+
+    /// The profile points of `shots` that detected one, in order.
+    pub fn profile_points<'a, I>(&self, shots: I) -> Vec<ProfilePoint>
+    where
+        I: IntoIterator<Item = &'a Shot>,
+    {
+        shots.into_iter().filter_map(|shot| self.profile_point(shot)).collect()
+    }
+
+    /// The profile point of `shot` rotated into the level pipe frame by its [Attitude], or `None`
+    /// if no profile point was detected.
+    pub fn level_profile_point(&self, shot: &Shot) -> Option<ProfilePoint> {
+        let point = self.profile_point(shot)?;
+        Some(Attitude::from_shot(shot).level(&point))
+    }
+}
+
+/// Where the pitch or roll of an [Attitude] was taken from.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(
+    target_family = "wasm",
+    derive(tsify::Tsify, serde::Serialize, serde::Deserialize),
+    tsify(into_wasm_abi, from_wasm_abi)
+)]
+#[cfg_attr(
+    all(feature = "serde", not(target_family = "wasm")),
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "pyo3", pyclass(eq, eq_int))]
+pub enum AttitudeSource {
+    /// The external sensor recorded in the [FileHeader].
+    FileHeader,
+
+    /// The sensor of the sonar head, reported in the
+    /// [SonarReturnHeader](crate::types::SonarReturnHeader).
+    SonarReturn,
+
+    /// Neither source was valid, so the head is taken as level.
+    Unavailable,
+}
+
+impl Display for AttitudeSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                Self::FileHeader => "file header",
+                Self::SonarReturn => "sonar return",
+                Self::Unavailable => "unavailable",
+            }
+        )
+    }
+}
+
+/// The pitch and roll of the sonar head, for rotating profile points into a level pipe frame.
+///
+/// Positive pitch raises the front of the head and positive roll turns it clockwise, looking
+/// forward along the pipe, both in degrees.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "pyo3", pyclass(eq))]
+pub struct Attitude {
+    pitch: f32,
+    roll: f32,
+    pitch_source: AttitudeSource,
+    roll_source: AttitudeSource,
+}
+
+impl Attitude {
+    /// A level head.
+    pub const LEVEL: Self = Self {
+        pitch: 0.0,
+        roll: 0.0,
+        pitch_source: AttitudeSource::Unavailable,
+        roll_source: AttitudeSource::Unavailable,
+    };
+
+    /// The attitude of a head at `pitch` and `roll` degrees from `source`.
+    pub fn new(pitch: f32, roll: f32, source: AttitudeSource) -> Self {
+        Self { pitch, roll, pitch_source: source, roll_source: source }
+    }
+
+    /// The attitude recorded with `shot`, taking each of the pitch and roll from the first valid
+    /// source of:
+    /// 1. the [FileHeader], if its [SensorAvailable] and its [SensorInformation] flag is set;
+    /// 2. the [SonarReturnHeader], if the [Angle] is within range and not in alarm;
+    ///
+    /// and taking it as level otherwise.
+    ///
+    /// [SensorInformation]: crate::types::SensorInformation
+    /// [SonarReturnHeader]: crate::types::SonarReturnHeader
+    pub fn from_shot(shot: &Shot) -> Self {
+        let header = &shot.header;
+        let external = header.sensor_available() == SensorAvailable::Available;
+        let information = header.sensor_information();
+        let sonar_return = shot.sonar_return.header();
+
+        let (pitch, pitch_source) = select(
+            (external && information.pitch_valid).then_some(header.pitch()),
+            &sonar_return.pitch_angle,
+        );
+        let (roll, roll_source) = select(
+            (external && information.roll_valid).then_some(header.roll()),
+            &sonar_return.roll_angle,
+        );
+
+        Self { pitch, roll, pitch_source, roll_source }
+    }
+
+    #[inline]
+    pub fn pitch(&self) -> f32 {
+        self.pitch
+    }
+
+    #[inline]
+    pub fn roll(&self) -> f32 {
+        self.roll
+    }
+
+    #[inline]
+    pub fn pitch_source(&self) -> AttitudeSource {
+        self.pitch_source
+    }
+
+    #[inline]
+    pub fn roll_source(&self) -> AttitudeSource {
+        self.roll_source
+    }
+
+    /// `point` in the level frame as (`x` right, `y` up, `z` forward along the pipe), in meters.
+    ///
+    /// The head rolls about the pipe axis, then pitches about its right-hand axis.
+    pub fn rotate(&self, point: &ProfilePoint) -> [f32; 3] {
+        let (sin_roll, cos_roll) = self.roll.to_radians().sin_cos();
+        let x = point.x * cos_roll + point.y * sin_roll;
+        let y = point.y * cos_roll - point.x * sin_roll;
+
+        let (sin_pitch, cos_pitch) = self.pitch.to_radians().sin_cos();
+        [x, y * cos_pitch, -y * sin_pitch]
+    }
+
+    /// `point` rotated into the level frame and projected onto the cross-section of the pipe.
+    ///
+    /// Pitch tilts the plane of the scan, so projecting it restores the true cross-section of a
+    /// straight pipe.
+    pub fn level(&self, point: &ProfilePoint) -> ProfilePoint {
+        let [x, y, _] = self.rotate(point);
+        ProfilePoint { angle: x.atan2(y).to_degrees(), range: x.hypot(y), x, y }
+    }
+}
+
+impl Default for Attitude {
+    fn default() -> Self {
+        Self::LEVEL
+    }
+}
+
+/// The `external` angle if valid, else the `sonar_return` angle if valid, else level.
+fn select(external: Option<f32>, sonar_return: &Angle) -> (f32, AttitudeSource) {
+    match external.filter(|angle| angle.is_finite()) {
+        Some(angle) => (angle, AttitudeSource::FileHeader),
+        None if sonar_return.valid() && !sonar_return.error_alarm() => {
+            (sonar_return.angle, AttitudeSource::SonarReturn)
+        },
+        None => (0.0, AttitudeSource::Unavailable),
+    }
+}
+
+/// A point of the pipe cross-section in the sonar frame of a [Geometry].
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "pyo3", pyclass(eq))]
+pub struct ProfilePoint {
+    /// The angle clockwise from up, in degrees.
+    #[cfg(not(feature = "pyo3"))]
+    pub angle: f32,
+
+    /// The angle clockwise from up, in degrees.
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    pub angle: f32,
+
+    /// The distance from the head, in meters.
+    #[cfg(not(feature = "pyo3"))]
+    pub range: f32,
+
+    /// The distance from the head, in meters.
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    pub range: f32,
+
+    /// The distance to the right of the head, in meters.
+    #[cfg(not(feature = "pyo3"))]
+    pub x: f32,
+
+    /// The distance to the right of the head, in meters.
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    pub x: f32,
+
+    /// The distance above the head, in meters.
+    #[cfg(not(feature = "pyo3"))]
+    pub y: f32,
+
+    /// The distance above the head, in meters.
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    pub y: f32,
+}
+
+#[cfg(feature = "pyo3")]
+#[pymethods]
+impl Geometry {
+    #[new]
+    pub(crate) fn py_new(transducer: Transducer, zero: Zero, step: f32) -> Self {
+        Self::new(transducer, zero, step)
+    }
+
+    #[pyo3(name = "from_header")]
+    #[staticmethod]
+    pub(crate) fn py_from_header(header: &FileHeader) -> Self {
+        Self::from_header(header)
+    }
+
+    #[pyo3(name = "point")]
+    pub(crate) fn py_point(&self, head_position: &HeadPosition, range: f32) -> ProfilePoint {
+        self.point(head_position, range)
+    }
+
+    #[pyo3(name = "profile_point")]
+    pub(crate) fn py_profile_point(&self, shot: &Shot) -> Option<ProfilePoint> {
+        self.profile_point(shot)
+    }
+
+    #[pyo3(name = "level_profile_point")]
+    pub(crate) fn py_level_profile_point(&self, shot: &Shot) -> Option<ProfilePoint> {
+        self.level_profile_point(shot)
+    }
+}
+
+#[cfg(feature = "pyo3")]
+#[pymethods]
+impl AttitudeSource {
+    pub(crate) fn __str__(&self) -> String {
+        self.to_string()
+    }
+}
+
+#[cfg(feature = "pyo3")]
+#[pymethods]
+impl Attitude {
+    #[new]
+    pub(crate) fn py_new(pitch: f32, roll: f32, source: AttitudeSource) -> Self {
+        Self::new(pitch, roll, source)
+    }
+
+    #[pyo3(name = "from_shot")]
+    #[staticmethod]
+    pub(crate) fn py_from_shot(shot: &Shot) -> Self {
+        Self::from_shot(shot)
+    }
+
+    #[getter(pitch)]
+    pub(crate) fn py_pitch(&self) -> f32 {
+        self.pitch
+    }
+
+    #[getter(roll)]
+    pub(crate) fn py_roll(&self) -> f32 {
+        self.roll
+    }
+
+    #[getter(pitch_source)]
+    pub(crate) fn py_pitch_source(&self) -> AttitudeSource {
+        self.pitch_source
+    }
+
+    #[getter(roll_source)]
+    pub(crate) fn py_roll_source(&self) -> AttitudeSource {
+        self.roll_source
+    }
+
+    #[pyo3(name = "level")]
+    pub(crate) fn py_level(&self, point: &ProfilePoint) -> ProfilePoint {
+        self.level(point)
+    }
+}
+
+#[cfg(feature = "pyo3")]
+#[pymethods]
+impl ProfilePoint {
+    pub(crate) fn __repr__(&self) -> String {
+        format!(
+            "ProfilePoint(angle={}, range={}, x={}, y={})",
+            self.angle, self.range, self.x, self.y
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::shot_bytes;
+    use binrw::BinRead;
+    use std::io::Cursor;
+
+    use log::info;
+    use test_log::test;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn head_position(angle: f32, direction: Direction) -> HeadPosition {
+        HeadPosition::new(angle, direction)
+    }
+
+    #[test]
+    fn angle() {
+        use Direction::{Clockwise, Counterclockwise};
+
+        let cases = vec![
+            (Transducer::Up, Zero::Up, 0.0, 90.0, Clockwise, 90.0),
+            (Transducer::Up, Zero::Up, 0.0, -90.0, Counterclockwise, -90.0),
+            (Transducer::Up, Zero::Down, 0.0, 0.0, Clockwise, -180.0),
+            (Transducer::Down, Zero::Up, 0.0, 45.0, Clockwise, -135.0),
+            (Transducer::Down, Zero::Down, 0.0, 45.0, Clockwise, 45.0),
+            (Transducer::Up, Zero::Up, 0.9, 90.0, Clockwise, 89.55),
+            (Transducer::Up, Zero::Up, 0.9, 90.0, Counterclockwise, 90.45),
+            (Transducer::Up, Zero::Up, 2.4, 179.4, Counterclockwise, -179.4),
+        ];
+
+        for (transducer, zero, step, angle, direction, want) in cases {
+            info!("Getting angle of {angle}° {direction} for {transducer}, {zero}, want {want}");
+            let geometry = Geometry::new(transducer, zero, step);
+            let got = geometry.angle(&head_position(angle, direction));
+            assert!((want - got).abs() < EPSILON, "want {want}, got {got}");
+        }
+    }
+
+    #[test]
+    fn point() {
+        let cases = vec![
+            (Zero::Up, 0.0, (0.0, 1.0)),
+            (Zero::Up, 90.0, (1.0, 0.0)),
+            (Zero::Up, -90.0, (-1.0, 0.0)),
+            (Zero::Down, 0.0, (0.0, -1.0)),
+            (Zero::Down, 90.0, (-1.0, 0.0)),
+        ];
+
+        for (zero, angle, (x, y)) in cases {
+            info!("Placing 1 m at {angle}° for {zero}, want ({x}, {y})");
+            let geometry = Geometry::new(Transducer::Up, zero, 0.0);
+            let got = geometry.point(&head_position(angle, Direction::Clockwise), 1.0);
+            assert_eq!(1.0, got.range);
+            assert!((x - got.x).abs() < EPSILON && (y - got.y).abs() < EPSILON, "got {got:?}");
+        }
+    }
+
+    #[test]
+    fn profile_point() {
+        let mut shot =
+            Shot::read(&mut Cursor::new(shot_bytes())).expect("It should not return an error");
+        let geometry = Geometry::from_header(&shot.header);
+
+        let header = shot.sonar_return.header_mut();
+        header.profile_range = 0.5;
+        header.head_position = head_position(90.0, Direction::Counterclockwise);
+        let point = geometry.profile_point(&shot).expect("It should detect a point");
+        info!("Got {point:?}");
+        assert!((0.5 - point.range).abs() < EPSILON);
+
+        // the fixture records the raw profile range 0 of a return without a detection
+        let shot =
+            Shot::read(&mut Cursor::new(shot_bytes())).expect("It should not return an error");
+        assert_eq!([0x00, 0x00], shot_bytes()[236..238]);
+        assert_eq!(None, geometry.profile_point(&shot));
+        assert!(geometry.profile_points([&shot]).is_empty());
+    }
+
+    #[test]
+    fn level() {
+        let point = Geometry::new(Transducer::Up, Zero::Up, 0.0)
+            .point(&head_position(0.0, Direction::Clockwise), 0.5);
+
+        let cases = vec![
+            (0.0, 0.0, (0.0, 0.5), 0.0),
+            (0.0, 90.0, (0.5, 0.0), 0.0),
+            (0.0, -30.0, (-0.25, 0.4330127), 0.0),
+            (60.0, 0.0, (0.0, 0.25), -0.4330127),
+            (60.0, 90.0, (0.5, 0.0), 0.0),
+        ];
+
+        for (pitch, roll, (x, y), z) in cases {
+            info!("Leveling {point:?} pitched {pitch}° and rolled {roll}°, want ({x}, {y}, {z})");
+            let attitude = Attitude::new(pitch, roll, AttitudeSource::FileHeader);
+            let [got_x, got_y, got_z] = attitude.rotate(&point);
+            assert!((x - got_x).abs() < EPSILON && (y - got_y).abs() < EPSILON);
+            assert!((z - got_z).abs() < EPSILON);
+
+            let level = attitude.level(&point);
+            assert_eq!((got_x, got_y), (level.x, level.y));
+            assert!((x.hypot(y) - level.range).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn attitude_from_shot() {
+        use AttitudeSource::{FileHeader, SonarReturn, Unavailable};
+
+        // sensor available, sensor information, file header pitch and roll, sonar return pitch
+        // and roll with error alarms
+        let cases = vec![
+            (
+                true,
+                0b111,
+                (2.0, -3.0),
+                ((4.0, false), (5.0, false)),
+                (2.0, FileHeader, -3.0, FileHeader),
+            ),
+            (
+                false,
+                0b111,
+                (2.0, -3.0),
+                ((4.0, false), (5.0, false)),
+                (4.0, SonarReturn, 5.0, SonarReturn),
+            ),
+            (
+                true,
+                0b110,
+                (2.0, -3.0),
+                ((4.0, false), (5.0, false)),
+                (4.0, SonarReturn, -3.0, FileHeader),
+            ),
+            (
+                true,
+                0b101,
+                (2.0, -3.0),
+                ((4.0, true), (5.0, true)),
+                (2.0, FileHeader, 0.0, Unavailable),
+            ),
+            (
+                false,
+                0b000,
+                (2.0, -3.0),
+                ((4.0, true), (5.0, false)),
+                (0.0, Unavailable, 5.0, SonarReturn),
+            ),
+        ];
+
+        for (available, information, (pitch, roll), sonar_return, want) in cases {
+            let ((sonar_pitch, pitch_alarm), (sonar_roll, roll_alarm)) = sonar_return;
+            let mut bytes = shot_bytes();
+            bytes[34] = available as u8;
+            bytes[98] = information;
+            bytes[99..103].copy_from_slice(&f32::to_be_bytes(pitch));
+            bytes[103..107].copy_from_slice(&f32::to_be_bytes(roll));
+
+            let mut shot =
+                Shot::read(&mut Cursor::new(bytes)).expect("It should not return an error");
+            let header = shot.sonar_return.header_mut();
+            header.pitch_angle = Angle::new(sonar_pitch, true, pitch_alarm);
+            header.roll_angle = Angle::new(sonar_roll, true, roll_alarm);
+
+            let attitude = Attitude::from_shot(&shot);
+            info!("Got {attitude:?}, want {want:?}");
+            let got = (
+                attitude.pitch(),
+                attitude.pitch_source(),
+                attitude.roll(),
+                attitude.roll_source(),
+            );
+            assert_eq!(want, got);
+        }
+    }
+
+    #[test]
+    fn level_profile_point() {
+        let mut bytes = shot_bytes();
+        bytes[34] = 1; // sensor available
+        bytes[103..107].copy_from_slice(&f32::to_be_bytes(90.0)); // roll
+
+        let mut shot = Shot::read(&mut Cursor::new(bytes)).expect("It should not return an error");
+        let geometry = Geometry::new(Transducer::Up, Zero::Up, 0.0);
+        let header = shot.sonar_return.header_mut();
+        header.profile_range = 0.5;
+        header.head_position = head_position(0.0, Direction::Clockwise);
+
+        let point = geometry.level_profile_point(&shot).expect("It should detect a point");
+        info!("Got {point:?}");
+        assert!((90.0 - point.angle).abs() < EPSILON);
+        assert!((0.5 - point.x).abs() < EPSILON && point.y.abs() < EPSILON);
+    }
+}
@@ -0,0 +1,298 @@
+//! Raster images of rendered sonar data and their encodings.
+use crate::{Error, Result};
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+/// The mapping of intensities in `0.0..=1.0` to colors.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(
+    target_family = "wasm",
+    derive(tsify::Tsify, serde::Serialize, serde::Deserialize),
+    tsify(into_wasm_abi, from_wasm_abi)
+)]
+#[cfg_attr(
+    all(feature = "serde", not(target_family = "wasm")),
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Colormap {
+    /// Black to white.
+    #[default]
+    Grayscale,
+
+    /// Black to amber, as on the IMAGENEX display.
+    Amber,
+
+    /// Blue through green and yellow to red.
+    Jet,
+}
+
+impl Colormap {
+    /// The color of `intensity`, clamped to `0.0..=1.0`.
+    pub fn color(&self, intensity: f32) -> [u8; 3] {
+        let v = intensity.clamp(0.0, 1.0);
+        match *self {
+            Self::Grayscale => [channel(v); 3],
+            Self::Amber => [channel(v), channel(v * 0.75), channel(v * 0.25)],
+            Self::Jet => [
+                channel(1.5 - (4.0 * v - 3.0).abs()),
+                channel(1.5 - (4.0 * v - 2.0).abs()),
+                channel(1.5 - (4.0 * v - 1.0).abs()),
+            ],
+        }
+    }
+}
+
+fn channel(v: f32) -> u8 {
+    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+impl Display for Colormap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                Self::Grayscale => "grayscale",
+                Self::Amber => "amber",
+                Self::Jet => "jet",
+            }
+        )
+    }
+}
+
+impl FromStr for Colormap {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "grayscale" | "gray" | "grey" => Ok(Self::Grayscale),
+            "amber" => Ok(Self::Amber),
+            "jet" => Ok(Self::Jet),
+            _ => Err(Error::new(format!("Unknown colormap `{s}`"))),
+        }
+    }
+}
+
+/// The encodings an [Image] can be written in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ImageFormat {
+    /// Portable Network Graphics, in color.
+    Png,
+
+    /// Binary Portable Graymap, in the luma of the colors.
+    Pgm,
+}
+
+impl ImageFormat {
+    /// The format named by the extension of `path`, if known.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "png" => Some(Self::Png),
+            "pgm" => Some(Self::Pgm),
+            _ => None,
+        }
+    }
+}
+
+/// An RGB raster, stored row by row from the top left.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Create a `width` by `height` image filled with `color`.
+    pub fn new(width: u32, height: u32, color: [u8; 3]) -> Self {
+        let pixels = color.repeat(width as usize * height as usize);
+        Self { width, height, pixels }
+    }
+
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn offset(&self, x: u32, y: u32) -> Option<usize> {
+        match x < self.width && y < self.height {
+            true => Some((y as usize * self.width as usize + x as usize) * 3),
+            false => None,
+        }
+    }
+
+    /// The color at column `x` and row `y`, or `None` outside of the image.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 3]> {
+        let i = self.offset(x, y)?;
+        Some([self.pixels[i], self.pixels[i + 1], self.pixels[i + 2]])
+    }
+
+    /// Paint column `x` and row `y` with `color`, ignoring pixels outside of the image.
+    pub fn set_pixel(&mut self, x: i64, y: i64, color: [u8; 3]) {
+        if x < 0 || y < 0 || x > u32::MAX as i64 || y > u32::MAX as i64 {
+            return;
+        }
+        if let Some(i) = self.offset(x as u32, y as u32) {
+            self.pixels[i..i + 3].copy_from_slice(&color);
+        }
+    }
+
+    /// The RGB samples of the pixels, row by row.
+    #[inline]
+    pub fn rgb(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// The luma of the pixels per ITU-R BT.601, row by row.
+    pub fn luma(&self) -> Vec<u8> {
+        self.pixels
+            .chunks_exact(3)
+            .map(|p| (0.299 * p[0] as f32 + 0.587 * p[1] as f32 + 0.114 * p[2] as f32).round())
+            .map(|luma| luma as u8)
+            .collect()
+    }
+
+    /// Encode the image as a color PNG to `writer`.
+    pub fn write_png<W: Write>(&self, writer: W) -> Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Encode the [Image::luma] of the image as a binary PGM to `writer`.
+    pub fn write_pgm<W: Write>(&self, mut writer: W) -> Result<()> {
+        write!(writer, "P5\n{} {}\n255\n", self.width, self.height)?;
+        writer.write_all(&self.luma())?;
+        Ok(())
+    }
+
+    /// Encode the image in `format` to `writer`.
+    pub fn write<W: Write>(&self, writer: W, format: ImageFormat) -> Result<()> {
+        match format {
+            ImageFormat::Png => self.write_png(writer),
+            ImageFormat::Pgm => self.write_pgm(writer),
+        }
+    }
+
+    /// Save the image to `path`, in the [ImageFormat] of its extension.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let format = ImageFormat::from_path(path).ok_or_else(|| {
+            Error::new(format!("Unknown image format for `{}`, use .png or .pgm", path.display()))
+        })?;
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer, format)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    use log::info;
+    use test_log::test;
+
+    #[test]
+    fn colormap() {
+        let cases = vec![
+            (Colormap::Grayscale, 0.0, [0, 0, 0]),
+            (Colormap::Grayscale, 1.0, [255, 255, 255]),
+            (Colormap::Grayscale, 2.0, [255, 255, 255]),
+            (Colormap::Amber, 1.0, [255, 191, 64]),
+            (Colormap::Jet, 0.0, [0, 0, 128]),
+            (Colormap::Jet, 0.5, [128, 255, 128]),
+            (Colormap::Jet, 1.0, [128, 0, 0]),
+        ];
+
+        for (colormap, intensity, want) in cases {
+            info!("Coloring {intensity} with {colormap}, want {want:?}");
+            assert_eq!(want, colormap.color(intensity));
+        }
+    }
+
+    #[test]
+    fn colormap_from_str() {
+        let cases = vec![
+            ("grayscale", Colormap::Grayscale),
+            ("Grey", Colormap::Grayscale),
+            ("amber", Colormap::Amber),
+            ("JET", Colormap::Jet),
+        ];
+
+        for (s, want) in cases {
+            info!("Parsing {s}, want {want}");
+            let got: Colormap = s.parse().expect("It should not return an error");
+            assert_eq!(want, got);
+        }
+
+        assert!("viridis".parse::<Colormap>().is_err());
+    }
+
+    #[test]
+    fn format_from_path() {
+        let cases = vec![
+            ("sweep.png", Some(ImageFormat::Png)),
+            ("sweep.PGM", Some(ImageFormat::Pgm)),
+            ("sweep.jpg", None),
+            ("sweep", None),
+        ];
+
+        for (path, want) in cases {
+            info!("Getting the format of {path}, want {want:?}");
+            assert_eq!(want, ImageFormat::from_path(path));
+        }
+    }
+
+    #[test]
+    fn pixels() {
+        let mut image = Image::new(3, 2, [1, 2, 3]);
+        image.set_pixel(2, 1, [255, 255, 255]);
+        image.set_pixel(-1, 0, [9, 9, 9]);
+        image.set_pixel(3, 0, [9, 9, 9]);
+
+        assert_eq!(Some([1, 2, 3]), image.pixel(0, 0));
+        assert_eq!(Some([255, 255, 255]), image.pixel(2, 1));
+        assert_eq!(None, image.pixel(3, 0));
+        assert_eq!(vec![2, 2, 2, 2, 2, 255], image.luma());
+    }
+
+    #[test]
+    fn write() {
+        let image = Image::new(4, 2, [255, 0, 0]);
+
+        let mut pgm = Vec::new();
+        image.write_pgm(&mut pgm).expect("It should not return an error");
+        assert_eq!(b"P5\n4 2\n255\n", &pgm[..11]);
+        assert_eq!(&[76; 8], &pgm[11..]);
+
+        let mut png = Vec::new();
+        image.write_png(&mut png).expect("It should not return an error");
+        assert_eq!(b"\x89PNG\r\n\x1a\n", &png[..8]);
+
+        let decoder = png::Decoder::new(Cursor::new(png));
+        let mut reader = decoder.read_info().expect("It should not return an error");
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).expect("It should not return an error");
+        assert_eq!((4, 2), (info.width, info.height));
+        assert_eq!(image.rgb(), &buffer[..info.buffer_size()]);
+    }
+}
@@ -0,0 +1,346 @@
+use crate::io::{reader::find_plausible_start, ShotView};
+use crate::{DecodeMode, Error, Result, Shot};
+use binrw::{binrw, BinRead, BinWrite};
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::io::{BufReader, BufWriter};
+use std::ops::{Index, RangeBounds};
+use std::path::{Path, PathBuf};
+
+/// The extension appended to a `.31l` file name to form its index sidecar file name.
+pub const SIDECAR_EXTENSION: &str = "idx";
+
+/// The location and search keys of one shot in a [ShotIndex].
+#[derive(Debug, Copy, Clone, PartialEq, BinRead, BinWrite)]
+#[brw(big)]
+pub struct IndexEntry {
+    /// The byte offset of the shot in its source.
+    pub offset: u64,
+
+    /// The date and time the shot was recorded.
+    #[br(try_map = |micros: i64| DateTime::from_timestamp_micros(micros).ok_or("invalid timestamp"))]
+    #[bw(map = |datetime: &DateTime<Utc>| datetime.timestamp_micros())]
+    pub datetime: DateTime<Utc>,
+
+    /// The distance travelled along the pipe, see [crate::types::FileHeader::distance].
+    pub distance: f32,
+}
+
+/// A random-access index of the shots in a recording.
+///
+/// The index is built by a quick scan of the file headers, skipping any region that does not
+/// decode, and can be cached in a sidecar file next to the recording (see [ShotIndex::save]).
+#[binrw]
+#[derive(Debug, Default, Clone, PartialEq)]
+#[brw(big, magic = b"31LIDX\x03")]
+pub struct ShotIndex {
+    /// The length of the indexed data, used to detect a stale sidecar file.
+    source_length: u64,
+
+    /// A hash of the first and last shots of the indexed data, used with the length to detect a
+    /// sidecar file of another recording.
+    fingerprint: u64,
+
+    /// The [DecodeMode] the index was built in, which decides whether out-of-range shots are
+    /// indexed.
+    mode: DecodeMode,
+
+    #[br(temp)]
+    #[bw(calc = entries.len() as u64)]
+    count: u64,
+
+    #[br(count = count)]
+    entries: Vec<IndexEntry>,
+}
+
+impl ShotIndex {
+    /// Build an index of all shots in `data` by a quick scan of their file headers.
+    ///
+    /// Regions that do not decode are skipped by scanning for the next plausible shot.
+    #[inline]
+    pub fn build(data: &[u8]) -> Self {
+        Self::build_with_mode(data, DecodeMode::Strict)
+    }
+
+    /// Build an index as [ShotIndex::build], decoding the file headers in `mode`.
+    ///
+    /// In [DecodeMode::Lenient], shots with out-of-range values are indexed rather than skipped.
+    pub fn build_with_mode(data: &[u8], mode: DecodeMode) -> Self {
+        let mut entries = Vec::with_capacity(data.len() / Shot::LENGTH);
+        let mut position = 0;
+
+        while position < data.len() {
+            let entry = ShotView::new(&data[position..], position as u64)
+                .and_then(|view| view.with_decode_mode(mode).header())
+                .map(|header| IndexEntry {
+                    offset: position as u64,
+                    datetime: header.datetime(),
+                    distance: header.distance(),
+                });
+
+            match entry {
+                Ok(entry) => {
+                    entries.push(entry);
+                    position += Shot::LENGTH;
+                },
+                Err(e) => {
+                    log::warn!("Skipping unindexable data: {e}");
+                    position = find_plausible_start(data, position + 1).unwrap_or(data.len());
+                },
+            }
+        }
+
+        Self { source_length: data.len() as u64, fingerprint: fingerprint(data), mode, entries }
+    }
+
+    /// The length of the indexed data.
+    #[inline]
+    pub fn source_length(&self) -> u64 {
+        self.source_length
+    }
+
+    /// The hash of the first and last shots of the indexed data.
+    #[inline]
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    /// The [DecodeMode] the index was built in.
+    #[inline]
+    pub fn mode(&self) -> DecodeMode {
+        self.mode
+    }
+
+    /// Whether the index was built for `data` in `mode`, judging the data by its length and the
+    /// fingerprint of its first and last shots.
+    pub fn matches(&self, data: &[u8], mode: DecodeMode) -> bool {
+        self.source_length == data.len() as u64
+            && self.fingerprint == fingerprint(data)
+            && self.mode == mode
+    }
+
+    /// The number of indexed shots.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[inline]
+    pub fn entries(&self) -> &[IndexEntry] {
+        &self.entries
+    }
+
+    /// The entry of the shot at `index`.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&IndexEntry> {
+        self.entries.get(index)
+    }
+
+    /// The entries of the shots in `range`, clamped to the indexed shots.
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> &[IndexEntry] {
+        let start = match range.start_bound() {
+            std::ops::Bound::Included(&i) => i,
+            std::ops::Bound::Excluded(&i) => i.saturating_add(1),
+            std::ops::Bound::Unbounded => 0,
+        };
+
+        let end = match range.end_bound() {
+            std::ops::Bound::Included(&i) => i.saturating_add(1),
+            std::ops::Bound::Excluded(&i) => i,
+            std::ops::Bound::Unbounded => self.len(),
+        };
+
+        let end = end.min(self.len());
+        &self.entries[start.min(end)..end]
+    }
+
+    /// The index of the first shot recorded at or after `datetime`.
+    ///
+    /// Shots are assumed to be recorded in chronological order.
+    pub fn search_datetime(&self, datetime: DateTime<Utc>) -> usize {
+        self.entries.partition_point(|entry| entry.datetime < datetime)
+    }
+
+    /// The index of the first shot at or beyond `distance` along the pipe.
+    ///
+    /// The distance is assumed not to decrease over the recording.
+    pub fn search_distance(&self, distance: f32) -> usize {
+        self.entries.partition_point(|entry| entry.distance < distance)
+    }
+
+    /// The path of the sidecar file caching the index of the recording at `path`.
+    pub fn sidecar_path<P: AsRef<Path>>(path: P) -> PathBuf {
+        let mut sidecar = path.as_ref().as_os_str().to_owned();
+        sidecar.push(".");
+        sidecar.push(SIDECAR_EXTENSION);
+        sidecar.into()
+    }
+
+    /// Load an index from a sidecar file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut reader = BufReader::new(fs::File::open(path)?);
+        Self::read(&mut reader).map_err(|e| Error::decode(e, 0))
+    }
+
+    /// Save the index to a sidecar file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+        self.write(&mut writer)?;
+        Ok(())
+    }
+}
+
+/// The FNV-1a hash of the first and last [Shot::LENGTH] bytes of `data`, cheap to compute for
+/// recordings of any size.
+fn fingerprint(data: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01B3;
+
+    let head = &data[..data.len().min(Shot::LENGTH)];
+    let tail = &data[data.len().saturating_sub(Shot::LENGTH)..];
+    head.iter()
+        .chain(tail)
+        .fold(OFFSET_BASIS, |hash, &byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+}
+
+impl Index<usize> for ShotIndex {
+    type Output = IndexEntry;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.entries[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{shot_bytes, shots_bytes, SHOT_LENGTH};
+    use std::io::Cursor;
+
+    use log::info;
+    use test_log::test;
+
+    const DISTANCE_OFFSET: usize = 3 + 104;
+    const TIME_OFFSET: usize = 3 + 5 + 12;
+
+    /// Build `count` shots one second and one meter apart.
+    fn survey(count: usize) -> Vec<u8> {
+        let mut data = Vec::new();
+
+        for i in 0..count {
+            let mut shot = shot_bytes();
+            let time = format!("10:19:{:02}\0", 10 + i);
+            shot[TIME_OFFSET..TIME_OFFSET + 9].copy_from_slice(time.as_bytes());
+            shot[DISTANCE_OFFSET..DISTANCE_OFFSET + 4].copy_from_slice(&(i as f32).to_be_bytes());
+            data.extend(shot);
+        }
+
+        data
+    }
+
+    fn datetime(second: u32) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(&format!("2023-07-27T10:19:{second:02}.69Z")).unwrap().to_utc()
+    }
+
+    #[test]
+    fn build() {
+        let index = ShotIndex::build(&survey(5));
+
+        assert_eq!(5, index.len());
+        assert_eq!(5 * SHOT_LENGTH as u64, index.source_length());
+
+        for (i, entry) in index.entries().iter().enumerate() {
+            info!("Checking entry {i}: {entry:?}");
+            assert_eq!((i * SHOT_LENGTH) as u64, entry.offset);
+            assert_eq!(datetime(10 + i as u32), entry.datetime);
+            assert_eq!(i as f32, entry.distance);
+        }
+    }
+
+    #[test]
+    fn build_skips_corrupt_data() {
+        let mut data = shots_bytes(1);
+        data.extend_from_slice(b"garbage");
+        data.extend(shots_bytes(2));
+        data.extend_from_slice(&shot_bytes()[..100]);
+
+        let index = ShotIndex::build(&data);
+        let offsets: Vec<u64> = index.entries().iter().map(|entry| entry.offset).collect();
+        assert_eq!(vec![0, 519, 1031], offsets);
+    }
+
+    #[test]
+    fn slice() {
+        let index = ShotIndex::build(&survey(5));
+
+        assert_eq!(&index.entries()[1..3], index.slice(1..3));
+        assert_eq!(&index.entries()[3..], index.slice(3..));
+        assert_eq!(&index.entries()[..=1], index.slice(..=1));
+        assert!(index.slice(4..10).len() == 1);
+        assert!(index.slice(7..).is_empty());
+    }
+
+    #[test]
+    fn search() {
+        let index = ShotIndex::build(&survey(5));
+
+        let cases = vec![(datetime(5), 0), (datetime(12), 2), (datetime(30), 5)];
+        for (datetime, want) in cases {
+            info!("Searching for {datetime:?}, want {want:?}");
+            assert_eq!(want, index.search_datetime(datetime));
+        }
+
+        let cases = vec![(-1.0, 0), (2.0, 2), (2.5, 3), (10.0, 5)];
+        for (distance, want) in cases {
+            info!("Searching for {distance:?}, want {want:?}");
+            assert_eq!(want, index.search_distance(distance));
+        }
+    }
+
+    #[test]
+    fn sidecar_path() {
+        let got = ShotIndex::sidecar_path("survey/27JUL2023-101914.31l");
+        assert_eq!(PathBuf::from("survey/27JUL2023-101914.31l.idx"), got);
+    }
+
+    #[test]
+    fn matches() {
+        let data = survey(3);
+        let index = ShotIndex::build(&data);
+
+        let mut shifted = data.clone();
+        shifted[TIME_OFFSET + 7] = b'9';
+        let mut last = data.clone();
+        last[2 * SHOT_LENGTH + DISTANCE_OFFSET] ^= 0x01;
+
+        let cases = vec![
+            (data.clone(), DecodeMode::Strict, true),
+            (data, DecodeMode::Lenient, false),
+            (survey(4), DecodeMode::Strict, false),
+            (shifted, DecodeMode::Strict, false),
+            (last, DecodeMode::Strict, false),
+        ];
+        for (data, mode, want) in cases {
+            info!("Matching data of {} bytes in {mode:?}, want {want:?}", data.len());
+            assert_eq!(want, index.matches(&data, mode));
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let index = ShotIndex::build(&survey(3));
+
+        let mut cursor = Cursor::new(Vec::new());
+        index.write(&mut cursor).expect("It should not return an error");
+        cursor.set_position(0);
+
+        let got = ShotIndex::read(&mut cursor).expect("It should not return an error");
+        assert_eq!(index, got);
+    }
+}
@@ -0,0 +1,66 @@
+//! Reading and writing IMAGENEX 831L `.31l` shot files.
+mod index;
+mod reader;
+mod stream;
+mod view;
+mod writer;
+
+pub use index::{IndexEntry, ShotIndex, SIDECAR_EXTENSION};
+pub use reader::{Reader, Recover, Recovered};
+pub use stream::StreamReader;
+pub use view::{ShotView, ShotViews};
+pub use writer::Writer;
+
+use crate::{Result, Shot, Warning};
+
+/// The index of the shot at `offset` in whole shots from the start of the source, an estimate for
+/// positions reached by seeking.
+#[inline]
+pub(crate) fn shot_index(offset: u64) -> usize {
+    (offset / Shot::LENGTH as u64) as usize
+}
+
+/// A source of sequentially decoded shots.
+pub trait ReadShot {
+    /// Read the next [Shot], returning `Ok(None)` at a clean end of data.
+    fn read_shot(&mut self) -> Result<Option<Shot>>;
+
+    /// Read the next [Shot] along with the [Warning]s from [validating](Shot::validate) it.
+    fn read_validated(&mut self) -> Result<Option<(Shot, Vec<Warning>)>> {
+        Ok(self.read_shot()?.map(|shot| {
+            let warnings = shot.validate();
+            (shot, warnings)
+        }))
+    }
+}
+
+/// A fallible iterator over the shots of a [ReadShot] source, created by [Reader::shots] or
+/// [StreamReader::shots].
+///
+/// Yields decode errors instead of stopping silently, and ends after the first error.
+pub struct Shots<'a, S: ?Sized> {
+    source: &'a mut S,
+    failed: bool,
+}
+
+impl<'a, S: ReadShot + ?Sized> Shots<'a, S> {
+    pub(crate) fn new(source: &'a mut S) -> Self {
+        Self { source, failed: false }
+    }
+}
+
+impl<S: ReadShot + ?Sized> Iterator for Shots<'_, S> {
+    type Item = Result<Shot>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let result = self.source.read_shot().transpose();
+        self.failed = matches!(result, Some(Err(_)));
+        result
+    }
+}
+
+impl<S: ReadShot + ?Sized> std::iter::FusedIterator for Shots<'_, S> {}
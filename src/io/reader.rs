@@ -0,0 +1,750 @@
+use crate::io::{shot_index, ReadShot, ShotIndex, ShotView, ShotViews, Shots};
+use crate::{DecodeMode, Error, Result, Shot};
+use binrw::BinRead;
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+#[cfg(feature = "pyo3")]
+use pyo3::{
+    exceptions::{PyIOError, PyIndexError},
+    intern,
+    prelude::*,
+    types::{PySlice, PyString},
+    IntoPyObjectExt,
+};
+#[cfg(feature = "pyo3")]
+use pyo3_file::PyFileLikeObject;
+use std::fs;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::ops::{Range, RangeBounds};
+#[cfg(all(unix, not(target_family = "wasm"), feature = "pyo3"))]
+use std::os::fd::{AsRawFd, FromRawFd};
+use std::path::Path;
+
+/// The bytes backing a [Reader], either owned or memory-mapped.
+enum Source {
+    Owned(Vec<u8>),
+    Mapped(memmap2::Mmap),
+}
+
+impl AsRef<[u8]> for Source {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::Owned(data) => data,
+            Self::Mapped(map) => map,
+        }
+    }
+}
+
+#[cfg_attr(feature = "pyo3", pyclass)]
+pub struct Reader {
+    cursor: Cursor<Source>,
+    index: OnceCell<ShotIndex>,
+    mode: DecodeMode,
+    /// The number of the next shot, counted as shots are decoded.
+    shot: usize,
+}
+
+impl Reader {
+    pub fn new(inner: Vec<u8>) -> Self {
+        Self::from_source(Source::Owned(inner))
+    }
+
+    #[inline]
+    fn from_source(source: Source) -> Self {
+        Self {
+            cursor: Cursor::new(source),
+            index: OnceCell::new(),
+            mode: DecodeMode::default(),
+            shot: 0,
+        }
+    }
+
+    /// Decode shots in `mode` instead of [DecodeMode::Strict].
+    #[inline]
+    pub fn with_decode_mode(mut self, mode: DecodeMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    #[inline]
+    pub fn decode_mode(&self) -> DecodeMode {
+        self.mode
+    }
+
+    #[inline]
+    pub fn set_decode_mode(&mut self, mode: DecodeMode) {
+        self.mode = mode;
+    }
+
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.cursor.position()
+    }
+
+    /// The number of bytes left to read.
+    #[inline]
+    pub fn remaining(&self) -> u64 {
+        (self.cursor.get_ref().as_ref().len() as u64).saturating_sub(self.position())
+    }
+
+    #[inline]
+    pub fn is_mapped(&self) -> bool {
+        matches!(self.cursor.get_ref(), Source::Mapped(_))
+    }
+
+    #[inline]
+    pub fn map_ref(&self) -> Option<&memmap2::Mmap> {
+        match self.cursor.get_ref() {
+            Source::Mapped(map) => Some(map),
+            Source::Owned(_) => None,
+        }
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[inline]
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_file(fs::OpenOptions::new().read(true).open(path)?)
+    }
+
+    /// Create a [Reader] over a file, memory-mapping it when possible.
+    ///
+    /// The mapped file is read in place without being copied into memory.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn from_file(file: fs::File) -> Result<Self> {
+        // Attempt to use memory mapping
+        match unsafe { memmap2::Mmap::map(&file) } {
+            Ok(map) => {
+                #[cfg(unix)]
+                map.advise(memmap2::Advice::Sequential).unwrap_or(());
+
+                Ok(Self::from_source(Source::Mapped(map)))
+            },
+            Err(_) => {
+                // Fallback
+                let mut data = Vec::new();
+                file.take(u64::MAX).read_to_end(&mut data)?;
+                Ok(Self::new(data))
+            },
+        }
+    }
+}
+
+impl Reader {
+    /// Read the next [Shot].
+    ///
+    /// Returns `Ok(None)` at a clean end of file, where no bytes remain. A shot that fails to
+    /// decode, including a truncated final shot, is reported as an [Error] carrying the byte
+    /// offset of the shot and the failing field; the position is then left at the start of
+    /// that shot.
+    pub fn read_shot(&mut self) -> Result<Option<Shot>> {
+        let offset = self.cursor.position();
+        if self.remaining() == 0 {
+            return Ok(None);
+        }
+
+        match Shot::read_args(&mut self.cursor, (self.mode,)) {
+            Ok(shot) => {
+                self.shot += 1;
+                Ok(Some(shot))
+            },
+            Err(e) => {
+                self.cursor.set_position(offset);
+                Err(Error::decode(e, offset).with_shot(self.shot))
+            },
+        }
+    }
+
+    /// Scan forward from the byte after the current position for the next plausible shot start
+    /// (see [Shot::is_plausible_start]) and move there.
+    ///
+    /// Returns the offset of the shot found, or `None` after moving to the end of the data if
+    /// there is none.
+    pub fn resynchronize(&mut self) -> Option<u64> {
+        let data = self.cursor.get_ref().as_ref();
+        let start = (self.cursor.position() as usize).saturating_add(1);
+        let found = find_plausible_start(data, start);
+
+        let end = data.len();
+        self.cursor.set_position(found.unwrap_or(end) as u64);
+        found.map(|i| i as u64)
+    }
+
+    /// Iterate over the remaining shots, recovering from corrupt or truncated regions.
+    ///
+    /// Whenever a shot fails to decode, the reader [resynchronizes](Reader::resynchronize) on the
+    /// next plausible shot and reports the skipped byte range along with the error that caused it.
+    #[inline]
+    pub fn recover(&mut self) -> Recover<'_> {
+        Recover { reader: self }
+    }
+
+    /// Iterate over zero-copy [views](crate::ShotView) of the remaining shots, borrowed from the
+    /// underlying data without moving the reader.
+    #[inline]
+    pub fn views(&self) -> ShotViews<'_> {
+        ShotViews::new(self.cursor.get_ref().as_ref(), self.cursor.position() as usize)
+    }
+
+    /// Iterate over the remaining shots, yielding decode errors instead of stopping silently.
+    ///
+    /// The iterator ends after the first error.
+    #[inline]
+    pub fn shots(&mut self) -> Shots<'_, Self> {
+        Shots::new(self)
+    }
+}
+
+/// Random access through the [ShotIndex].
+impl Reader {
+    /// The index of all shots in the data, built by a quick scan on first use.
+    ///
+    /// The index is built in the [DecodeMode] of the reader at the time of first use.
+    pub fn index(&self) -> &ShotIndex {
+        let data = self.cursor.get_ref().as_ref();
+        self.index.get_or_init(|| ShotIndex::build_with_mode(data, self.mode))
+    }
+
+    /// The index of all shots in the data, cached in the sidecar file at `path`.
+    ///
+    /// A sidecar file that is missing, unreadable or was built for other data or in another
+    /// [DecodeMode] (see [ShotIndex::matches]) is replaced with a fresh index. If [Reader::index]
+    /// was already called, the index it built is the one returned and written to the sidecar.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn index_with_sidecar<P: AsRef<Path>>(&self, path: P) -> Result<&ShotIndex> {
+        let path = path.as_ref();
+        let data = self.cursor.get_ref().as_ref();
+
+        let cached = match ShotIndex::load(path) {
+            Ok(index) if index.matches(data, self.mode) => Some(index),
+            Ok(_) => {
+                log::warn!("Shot index sidecar {} is of other data or mode", path.display());
+                None
+            },
+            Err(_) => None,
+        };
+
+        let stale = cached.is_none();
+        let index = self
+            .index
+            .get_or_init(|| cached.unwrap_or_else(|| ShotIndex::build_with_mode(data, self.mode)));
+        if stale {
+            log::debug!("Writing shot index sidecar {}", path.display());
+            index.save(path)?;
+        }
+        Ok(index)
+    }
+
+    /// The number of shots in the data.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.index().len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.index().is_empty()
+    }
+
+    /// Decode the shot at `offset` without moving the reader.
+    pub fn read_shot_at(&self, offset: u64) -> Result<Shot> {
+        let data = self.cursor.get_ref().as_ref();
+        let start = (offset as usize).min(data.len());
+        ShotView::new(&data[start..], offset)?.with_decode_mode(self.mode).to_shot()
+    }
+
+    /// Decode the shot at `index`, or `None` if there are not that many shots.
+    pub fn get(&self, index: usize) -> Result<Option<Shot>> {
+        match self.index().get(index) {
+            Some(entry) => {
+                self.read_shot_at(entry.offset).map(Some).map_err(|e| e.with_shot(index))
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Decode the shots in `range`, clamped to the available shots.
+    pub fn get_range<R: RangeBounds<usize>>(
+        &self,
+        range: R,
+    ) -> impl Iterator<Item = Result<Shot>> + '_ {
+        self.index().slice(range).iter().map(|entry| self.read_shot_at(entry.offset))
+    }
+
+    /// The index of the first shot recorded at or after `datetime`.
+    #[inline]
+    pub fn search_datetime(&self, datetime: DateTime<Utc>) -> usize {
+        self.index().search_datetime(datetime)
+    }
+
+    /// The index of the first shot at or beyond `distance` along the pipe.
+    #[inline]
+    pub fn search_distance(&self, distance: f32) -> usize {
+        self.index().search_distance(distance)
+    }
+}
+
+/// Find the offset of the first plausible shot start at or after `start`.
+pub(crate) fn find_plausible_start(data: &[u8], start: usize) -> Option<usize> {
+    let start = start.min(data.len());
+
+    data[start..]
+        .windows(Shot::MAGIC.len())
+        .enumerate()
+        .filter(|(_, window)| *window == Shot::MAGIC)
+        .map(|(i, _)| start + i)
+        .find(|&i| Shot::is_plausible_start(&data[i..]))
+}
+
+impl ReadShot for Reader {
+    #[inline]
+    fn read_shot(&mut self) -> Result<Option<Shot>> {
+        Reader::read_shot(self)
+    }
+}
+
+/// An item produced by [Reader::recover].
+// shots are the common case, so they are not boxed
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum Recovered {
+    /// A successfully decoded shot.
+    Shot(Shot),
+
+    /// A region of bytes that was skipped to reach the next plausible shot.
+    Skipped {
+        /// The skipped byte range.
+        range: Range<u64>,
+
+        /// The error that caused the region to be skipped.
+        error: Error,
+    },
+}
+
+/// A recovering iterator over the shots of a [Reader], created by [Reader::recover].
+pub struct Recover<'a> {
+    reader: &'a mut Reader,
+}
+
+impl Iterator for Recover<'_> {
+    type Item = Recovered;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.reader.cursor.position();
+
+        match self.reader.read_shot() {
+            Ok(shot) => shot.map(Recovered::Shot),
+            Err(error) => {
+                let end = match self.reader.resynchronize() {
+                    Some(offset) => offset,
+                    None => self.reader.cursor.position(),
+                };
+
+                log::warn!("Skipped bytes {start}..{end}: {error}");
+                Some(Recovered::Skipped { range: start..end, error })
+            },
+        }
+    }
+}
+
+impl std::iter::FusedIterator for Recover<'_> {}
+
+/// Iterates until the end of file or the first shot that fails to decode.
+///
+/// Decode errors are logged and end the iteration; use [Reader::shots] to observe them.
+impl Iterator for Reader {
+    type Item = Shot;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_shot().unwrap_or_else(|e| {
+            log::warn!("Stopped reading shots: {e}");
+            None
+        })
+    }
+}
+
+/// Seeking renumbers the shots from the [ShotIndex] if it was built, and by whole shots from
+/// the start of the data otherwise.
+impl Seek for Reader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = self.cursor.seek(pos)?;
+        self.shot = match self.index.get() {
+            Some(index) => index.entries().partition_point(|entry| entry.offset < position),
+            None => shot_index(position),
+        };
+        Ok(position)
+    }
+}
+
+#[cfg(feature = "pyo3")]
+#[pymethods]
+impl Reader {
+    #[new]
+    pub(crate) fn py_new(vec: Vec<u8>) -> Self {
+        Self::new(vec)
+    }
+
+    pub(crate) fn __repr__(&self) -> String {
+        format!("<imagenex831l.Reader {}>", if self.is_mapped() { "mapped" } else { "unmapped" })
+    }
+
+    pub(crate) fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    pub(crate) fn __next__(mut slf: PyRefMut<Self>) -> PyResult<Option<Shot>> {
+        slf.read_shot().map_err(Into::into)
+    }
+
+    pub(crate) fn __len__(&self) -> usize {
+        self.len()
+    }
+
+    pub(crate) fn __getitem__(&self, py: Python<'_>, key: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        let length = self.len() as isize;
+
+        if let Ok(slice) = key.downcast::<PySlice>() {
+            let indices = slice.indices(length)?;
+            let shots = (0..indices.slicelength as isize)
+                .map(|i| indices.start + i * indices.step)
+                .map(|i| self.get(i as usize).map(Option::unwrap).map_err(Into::into))
+                .collect::<PyResult<Vec<Shot>>>()?;
+            return shots.into_py_any(py);
+        }
+
+        let index: isize = key.extract()?;
+        let index = if index < 0 { index + length } else { index };
+        if !(0..length).contains(&index) {
+            return Err(PyIndexError::new_err("shot index out of range"));
+        }
+
+        match self.get(index as usize) {
+            Ok(shot) => shot.into_py_any(py),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    #[getter(decode_mode)]
+    pub(crate) fn py_decode_mode(&self) -> DecodeMode {
+        self.decode_mode()
+    }
+
+    #[setter(decode_mode)]
+    pub(crate) fn py_set_decode_mode(&mut self, mode: DecodeMode) {
+        self.set_decode_mode(mode)
+    }
+
+    /// Read the remaining shots, grouped into sweeps.
+    #[pyo3(name = "sweeps")]
+    pub(crate) fn py_sweeps(&mut self) -> Vec<crate::Sweep> {
+        crate::Sweeps::new(self).collect()
+    }
+
+    #[pyo3(name = "search_datetime")]
+    pub(crate) fn py_search_datetime(&self, datetime: DateTime<Utc>) -> usize {
+        self.search_datetime(datetime)
+    }
+
+    #[pyo3(name = "search_distance")]
+    pub(crate) fn py_search_distance(&self, distance: f32) -> usize {
+        self.search_distance(distance)
+    }
+
+    #[pyo3(name = "from_path")]
+    #[staticmethod]
+    pub(crate) fn py_from_path(path: String) -> PyResult<Self> {
+        Self::from_path(path).map_err(|e| PyIOError::new_err(e.to_string()))
+    }
+
+    #[pyo3(name = "from_file")]
+    #[staticmethod]
+    pub(crate) fn py_from_file(file_like: PyObject) -> PyResult<Self> {
+        Python::with_gil(|py| {
+            // detect Path-like objects (e.g., strings)
+            if let Ok(path) = file_like.downcast_bound::<PyString>(py) {
+                return Reader::from_path(path.to_string_lossy().to_string())
+                    .map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()));
+            }
+
+            #[cfg(unix)]
+            let has_fileno = file_like.call_method0(py, intern!(py, "fileno")).is_ok();
+            match PyFileLikeObject::with_requirements(file_like, true, false, false, false) {
+                Ok(mut f) => {
+                    #[cfg(unix)]
+                    if has_fileno {
+                        let fd = f.as_raw_fd();
+                        let file = unsafe { fs::File::from_raw_fd(fd) };
+                        return Reader::from_file(file).map_err(|e| {
+                            pyo3::exceptions::PyIOError::new_err(format!(
+                                "Failed to create Reader: {e}"
+                            ))
+                        });
+                    }
+
+                    // If no valid file descriptor, fall back to reading data into memory
+                    let mut data = Vec::new();
+                    f.read_to_end(&mut data)?;
+                    Ok(Reader::new(data))
+                },
+                Err(e) => Err(pyo3::exceptions::PyTypeError::new_err(format!(
+                    "Invalid file-like object: {e}"
+                ))),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{shot_bytes, shots_bytes, SHOT_LENGTH};
+    use crate::{ErrorKind, Warning, Writer};
+    use project_root::get_project_root;
+
+    use log::info;
+    use test_log::test;
+
+    #[test]
+    fn test_shot_file_reading_and_writing() {
+        let mut path = get_project_root().unwrap();
+        path.push("sample");
+        path.push("27JUL2023-101914.31l");
+
+        let mut shot_file = Reader::from_path(&path).expect("Failed to open shot file for reading");
+        if let Some(shot) = shot_file.next() {
+            println!("{shot:#?}");
+        }
+    }
+
+    #[test]
+    fn read_shot() {
+        let mut reader = Reader::new(shots_bytes(3));
+
+        for i in 0..3 {
+            info!("Reading shot {i}");
+            let shot = reader.read_shot().expect("It should not return an error");
+            assert!(shot.is_some());
+            assert_eq!((i + 1) * SHOT_LENGTH as u64, reader.position());
+        }
+
+        let shot = reader.read_shot().expect("It should not return an error at the end of file");
+        assert!(shot.is_none());
+    }
+
+    #[test]
+    fn read_shot_truncated() {
+        let mut data = shots_bytes(2);
+        data.truncate(SHOT_LENGTH + 100);
+        let mut reader = Reader::new(data);
+
+        assert!(reader.read_shot().unwrap().is_some());
+
+        let error = reader.read_shot().unwrap_err();
+        info!("Got error: {error}");
+        assert!(error.is_truncated());
+        assert_eq!(Some(SHOT_LENGTH as u64), error.offset);
+        assert_eq!(SHOT_LENGTH as u64, reader.position());
+    }
+
+    #[test]
+    fn read_shot_invalid_field() {
+        let mut data = shots_bytes(2);
+        data[SHOT_LENGTH + 38] = 41; // start gain exceeds 40 dB
+        let mut reader = Reader::new(data);
+
+        assert!(reader.read_shot().unwrap().is_some());
+
+        let error = reader.read_shot().unwrap_err();
+        info!("Got error: {error}");
+        assert!(!error.is_truncated());
+        assert_eq!(Some(SHOT_LENGTH as u64), error.offset);
+        assert_eq!(Some("header.start_gain"), error.field.as_deref());
+        assert_eq!(Some(1), error.shot);
+        assert_eq!(&ErrorKind::OutOfRange { value: 41.0, min: 0.0, max: 40.0 }, error.kind());
+    }
+
+    #[test]
+    fn read_shot_lenient() {
+        let mut data = shots_bytes(2);
+        data[SHOT_LENGTH + 38] = 41; // start gain exceeds 40 dB
+        data[SHOT_LENGTH + 44] = 101; // pulse length exceeds 1000 μs
+        data[SHOT_LENGTH + 510] = 0; // termination byte
+        let mut reader = Reader::new(data).with_decode_mode(DecodeMode::Lenient);
+
+        let (shot, warnings) = reader.read_validated().unwrap().unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(shot, reader.get(0).unwrap().unwrap());
+
+        let (shot, warnings) =
+            reader.read_validated().expect("It should not return an error").unwrap();
+        for warning in &warnings {
+            info!("Got warning: {warning}");
+        }
+
+        let fields: Vec<_> = warnings.iter().map(Warning::field).collect();
+        assert_eq!(
+            vec!["header.start_gain", "header.pulse_length", "sonar_return.termination_byte"],
+            fields
+        );
+        assert_eq!(&ErrorKind::OutOfRange { value: 41.0, min: 0.0, max: 40.0 }, warnings[0].kind());
+        assert_eq!(&ErrorKind::InvalidData, warnings[2].kind());
+
+        assert_eq!(2, reader.len());
+        assert_eq!(shot, reader.get(1).unwrap().unwrap());
+
+        reader.set_decode_mode(DecodeMode::Strict);
+        assert!(reader.get(1).is_err());
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        assert!(writer.write_shot(&shot).is_err());
+    }
+
+    #[test]
+    fn shots() {
+        let mut data = shots_bytes(3);
+        data.extend_from_slice(&shot_bytes()[..10]);
+        let mut reader = Reader::new(data);
+
+        let results: Vec<_> = reader.shots().collect();
+        assert_eq!(4, results.len());
+        assert!(results[..3].iter().all(Result::is_ok));
+        assert!(results[3].as_ref().is_err_and(Error::is_truncated));
+    }
+
+    #[test]
+    fn resynchronize() {
+        let mut data = vec![0u8; 7];
+        data.extend_from_slice(b"31L31L");
+        data.extend(shots_bytes(2));
+        let mut reader = Reader::new(data);
+
+        assert_eq!(Some(13), reader.resynchronize());
+        assert_eq!(13, reader.position());
+        assert_eq!(Some(13 + SHOT_LENGTH as u64), reader.resynchronize());
+        assert_eq!(None, reader.resynchronize());
+        assert_eq!(0, reader.remaining());
+    }
+
+    #[test]
+    fn recover() {
+        let shot = shot_bytes();
+        let mut data = Vec::new();
+        data.extend_from_slice(&shot); // 0..512: valid
+        data.extend_from_slice(&shot[..100]); // 512..612: partial write
+        data.extend_from_slice(b"garbage"); // 612..619: noise
+        data.extend_from_slice(&shot); // 619..1131: valid
+        let mut corrupt = shot.clone();
+        corrupt[38] = 41; // start gain exceeds 40 dB
+        data.extend_from_slice(&corrupt); // 1131..1643: corrupt
+        data.extend_from_slice(&shot); // 1643..2155: valid
+        data.extend_from_slice(&shot[..300]); // 2155..2455: truncated
+
+        let mut reader = Reader::new(data);
+        let recovered: Vec<_> = reader.recover().collect();
+
+        let summary: Vec<_> = recovered
+            .iter()
+            .map(|item| match item {
+                Recovered::Shot(_) => None,
+                Recovered::Skipped { range, .. } => Some(range.clone()),
+            })
+            .collect();
+
+        info!("Recovered {summary:?}");
+        assert_eq!(
+            vec![None, Some(512..619), None, Some(1131..1643), None, Some(2155..2455)],
+            summary
+        );
+
+        let Recovered::Skipped { error, .. } = &recovered[5] else { unreachable!() };
+        assert!(error.is_truncated());
+
+        // shots are numbered as decoded, not by their offset past the skipped regions
+        let shots: Vec<_> = recovered
+            .iter()
+            .filter_map(|item| match item {
+                Recovered::Shot(_) => None,
+                Recovered::Skipped { error, .. } => Some(error.shot),
+            })
+            .collect();
+        assert_eq!(vec![Some(1), Some(2), Some(3)], shots);
+    }
+
+    #[test]
+    fn random_access() {
+        let mut data = shots_bytes(4);
+        data[2 * SHOT_LENGTH + 38] = 41; // start gain exceeds 40 dB
+        let mut reader = Reader::new(data);
+
+        assert_eq!(3, reader.len());
+        assert!(!reader.is_empty());
+
+        let want: Vec<u64> = vec![0, SHOT_LENGTH as u64, 3 * SHOT_LENGTH as u64];
+        let got: Vec<u64> = reader.index().entries().iter().map(|entry| entry.offset).collect();
+        assert_eq!(want, got);
+
+        assert!(reader.get(2).unwrap().is_some());
+        assert!(reader.get(3).unwrap().is_none());
+        assert_eq!(2, reader.get_range(1..).filter(Result::is_ok).count());
+
+        // random access leaves the position alone
+        assert_eq!(0, reader.position());
+        assert!(reader.read_shot().unwrap().is_some());
+    }
+
+    #[test]
+    fn index_with_sidecar() {
+        let path =
+            std::env::temp_dir().join(format!("imagenex831l-{}.31l.idx", std::process::id()));
+
+        let reader = Reader::new(shots_bytes(3));
+        let index = reader.index_with_sidecar(&path).expect("It should not return an error");
+        assert_eq!(3, index.len());
+
+        let saved = ShotIndex::load(&path).expect("It should not return an error");
+        assert_eq!(index, &saved);
+
+        // a stale sidecar is rebuilt
+        let reader = Reader::new(shots_bytes(5));
+        let index = reader.index_with_sidecar(&path).expect("It should not return an error");
+        assert_eq!(5, index.len());
+
+        // as is the sidecar of another recording of the same length
+        let mut other = shots_bytes(5);
+        other[4 * SHOT_LENGTH..].fill(0);
+        let reader = Reader::new(other);
+        let index = reader.index_with_sidecar(&path).expect("It should not return an error");
+        assert_eq!(4, index.len());
+        assert_eq!(index, &ShotIndex::load(&path).expect("It should not return an error"));
+
+        // as is the sidecar of another decode mode
+        let mut lenient = shots_bytes(3);
+        lenient[SHOT_LENGTH + 38] = 41; // start gain exceeds 40 dB
+        let reader = Reader::new(lenient.clone()).with_decode_mode(DecodeMode::Lenient);
+        let index = reader.index_with_sidecar(&path).expect("It should not return an error");
+        assert_eq!(3, index.len());
+        let reader = Reader::new(lenient);
+        let index = reader.index_with_sidecar(&path).expect("It should not return an error");
+        assert_eq!(2, index.len());
+        assert_eq!(DecodeMode::Strict, ShotIndex::load(&path).unwrap().mode());
+
+        // and an index built before is still written
+        fs::remove_file(&path).unwrap();
+        let reader = Reader::new(shots_bytes(2));
+        assert_eq!(2, reader.index().len());
+        reader.index_with_sidecar(&path).expect("It should not return an error");
+        let saved = ShotIndex::load(&path).expect("It should not return an error");
+        assert_eq!(reader.index(), &saved);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn iterator_stops_on_error() {
+        let mut data = shots_bytes(2);
+        data[SHOT_LENGTH] = b'X';
+        let reader = Reader::new(data);
+
+        assert_eq!(1, reader.count());
+    }
+}
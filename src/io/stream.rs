@@ -0,0 +1,278 @@
+use crate::io::{shot_index, ReadShot, Shots};
+use crate::{DecodeMode, Error, Result, Shot};
+use binrw::BinRead;
+use std::io::{Cursor, ErrorKind, Read, Seek, SeekFrom};
+
+/// A streaming shot reader over any [Read] source, such as standard input, a pipe or a socket.
+///
+/// Shots are decoded one at a time from a single reused buffer, so memory use is bounded by the
+/// size of one shot regardless of the length of the recording. When the source also implements
+/// [Seek], shots can be skipped without being read (see [StreamReader::seek_shot]).
+///
+/// Unlike [crate::Reader], a shot that fails to decode is consumed from a plain [Read] source.
+pub struct StreamReader<R> {
+    inner: R,
+    position: u64,
+    buffer: Vec<u8>,
+    mode: DecodeMode,
+    /// The number of the next shot, counted as shots are decoded.
+    shot: usize,
+}
+
+impl<R: Read> StreamReader<R> {
+    /// Create a [StreamReader] treating the current position of `inner` as byte zero.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            position: 0,
+            buffer: Vec::with_capacity(Shot::LENGTH),
+            mode: DecodeMode::default(),
+            shot: 0,
+        }
+    }
+
+    /// Decode shots in `mode` instead of [DecodeMode::Strict].
+    #[inline]
+    pub fn with_decode_mode(mut self, mode: DecodeMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    #[inline]
+    pub fn decode_mode(&self) -> DecodeMode {
+        self.mode
+    }
+
+    #[inline]
+    pub fn set_decode_mode(&mut self, mode: DecodeMode) {
+        self.mode = mode;
+    }
+
+    /// The number of bytes consumed from the source.
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Read the next [Shot].
+    ///
+    /// Returns `Ok(None)` when the source ends cleanly between shots. A shot that fails to
+    /// decode, including a truncated final shot, is reported as an [Error] carrying the byte
+    /// offset of the shot and the failing field.
+    pub fn read_shot(&mut self) -> Result<Option<Shot>> {
+        let offset = self.position;
+
+        self.buffer.resize(Shot::LENGTH, 0);
+        let length = self.fill_buffer()?;
+        self.buffer.truncate(length);
+        self.position += length as u64;
+
+        if length == 0 {
+            return Ok(None);
+        }
+
+        let shot = Shot::read_args(&mut Cursor::new(&self.buffer), (self.mode,))
+            .map_err(|e| Error::decode(e, offset).with_shot(self.shot))?;
+        self.shot += 1;
+        Ok(Some(shot))
+    }
+
+    /// Iterate over the remaining shots, yielding decode errors instead of stopping silently.
+    ///
+    /// The iterator ends after the first error.
+    #[inline]
+    pub fn shots(&mut self) -> Shots<'_, Self> {
+        Shots::new(self)
+    }
+
+    /// Fill the buffer from the source, stopping early only at the end of the source.
+    fn fill_buffer(&mut self) -> Result<usize> {
+        let mut length = 0;
+
+        while length < self.buffer.len() {
+            match self.inner.read(&mut self.buffer[length..]) {
+                Ok(0) => break,
+                Ok(n) => length += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(length)
+    }
+}
+
+impl<R: Read + Seek> StreamReader<R> {
+    /// Create a [StreamReader] over a seekable source, keeping its absolute positions.
+    pub fn from_seekable(mut inner: R) -> Result<Self> {
+        let position = inner.stream_position()?;
+        Ok(Self {
+            inner,
+            position,
+            buffer: Vec::with_capacity(Shot::LENGTH),
+            mode: DecodeMode::default(),
+            shot: shot_index(position),
+        })
+    }
+
+    /// Move to the start of the shot at `index` without decoding the shots before it.
+    ///
+    /// The index is counted from byte zero of the source. Returns the new position.
+    pub fn seek_shot(&mut self, index: u64) -> Result<u64> {
+        let position = self.seek(SeekFrom::Start(index * Shot::LENGTH as u64))?;
+        self.shot = index as usize;
+        Ok(position)
+    }
+}
+
+impl<R: Read> ReadShot for StreamReader<R> {
+    #[inline]
+    fn read_shot(&mut self) -> Result<Option<Shot>> {
+        StreamReader::read_shot(self)
+    }
+}
+
+/// Iterates until the end of the source or the first shot that fails to decode.
+///
+/// Decode errors are logged and end the iteration; use [StreamReader::shots] to observe them.
+impl<R: Read> Iterator for StreamReader<R> {
+    type Item = Shot;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        StreamReader::read_shot(self).unwrap_or_else(|e| {
+            log::warn!("Stopped reading shots: {e}");
+            None
+        })
+    }
+}
+
+/// Seeking renumbers the shots by whole shots from byte zero of the source.
+impl<R: Seek> Seek for StreamReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.position = self.inner.seek(pos)?;
+        self.shot = shot_index(self.position);
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{shots_bytes, SHOT_LENGTH};
+
+    use log::info;
+    use test_log::test;
+
+    /// A [Read] source returning at most `chunk` bytes per call, like a pipe.
+    struct Trickle<'a> {
+        data: &'a [u8],
+        chunk: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let length = self.chunk.min(buf.len()).min(self.data.len());
+            buf[..length].copy_from_slice(&self.data[..length]);
+            self.data = &self.data[length..];
+            Ok(length)
+        }
+    }
+
+    #[test]
+    fn read_shot() {
+        let data = shots_bytes(3);
+        let mut reader = StreamReader::new(Trickle { data: &data, chunk: 7 });
+
+        for i in 0..3 {
+            info!("Reading shot {i}");
+            let shot = reader.read_shot().expect("It should not return an error");
+            assert!(shot.is_some());
+            assert_eq!((i + 1) * SHOT_LENGTH as u64, reader.position());
+        }
+
+        let shot = reader.read_shot().expect("It should not return an error at the end of file");
+        assert!(shot.is_none());
+    }
+
+    #[test]
+    fn read_shot_numbers() {
+        let mut data = shots_bytes(4);
+        for i in [1, 3] {
+            data[i * SHOT_LENGTH + 38] = 41; // start gain exceeds 40 dB
+        }
+        let mut reader = StreamReader::new(data.as_slice());
+
+        let cases = vec![None, Some(1), None, Some(2)];
+        for want in cases {
+            let got = reader.read_shot().err().map(|error| error.shot);
+            info!("Read a shot numbered {got:?}, want {want:?}");
+            assert_eq!(want.map(Some), got);
+        }
+    }
+
+    #[test]
+    fn read_shot_matches_reader() {
+        let data = shots_bytes(2);
+        let want: Vec<Shot> = crate::Reader::new(data.clone()).collect();
+        let got: Vec<Shot> = StreamReader::new(data.as_slice()).collect();
+        assert_eq!(want, got);
+    }
+
+    #[test]
+    fn read_shot_truncated() {
+        let mut data = shots_bytes(2);
+        data.truncate(SHOT_LENGTH + 100);
+        let mut reader = StreamReader::new(data.as_slice());
+
+        let results: Vec<_> = reader.shots().collect();
+        assert_eq!(2, results.len());
+
+        let error = results[1].as_ref().unwrap_err();
+        info!("Got error: {error}");
+        assert!(error.is_truncated());
+        assert_eq!(Some(SHOT_LENGTH as u64), error.offset);
+    }
+
+    #[test]
+    fn seek_shot() {
+        let mut data = shots_bytes(3);
+        data[SHOT_LENGTH] = b'X';
+        let mut reader =
+            StreamReader::from_seekable(Cursor::new(data)).expect("It should not return an error");
+
+        assert!(reader.read_shot().is_ok());
+        assert!(reader.read_shot().is_err());
+
+        let position = reader.seek_shot(2).expect("It should not return an error");
+        assert_eq!(2 * SHOT_LENGTH as u64, position);
+        assert!(reader.read_shot().unwrap().is_some());
+        assert!(reader.read_shot().unwrap().is_none());
+    }
+
+    #[test]
+    fn from_seekable() {
+        let mut cursor = Cursor::new(shots_bytes(2));
+        cursor.set_position(SHOT_LENGTH as u64);
+
+        let mut reader =
+            StreamReader::from_seekable(cursor).expect("It should not return an error");
+        assert_eq!(SHOT_LENGTH as u64, reader.position());
+        assert!(reader.next().is_some());
+        assert!(reader.next().is_none());
+    }
+}
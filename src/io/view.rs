@@ -0,0 +1,236 @@
+use crate::types::{primitive::u14, FileHeader};
+use crate::types::{HeadPosition, RangeCode, SonarReturnHeader, SonarReturnStatus};
+use crate::{DecodeMode, Echo, Error, Result, Shot, ENDIAN};
+use binrw::BinRead;
+use std::io::{Cursor, ErrorKind};
+
+/// The offset of the [FileHeader] within a shot, after the magic.
+const FILE_HEADER_OFFSET: usize = Shot::MAGIC.len();
+
+/// The offset of the sonar return within a shot.
+const SONAR_RETURN_OFFSET: usize = 228;
+
+const STATUS_OFFSET: usize = SONAR_RETURN_OFFSET + 4;
+const HEAD_POSITION_OFFSET: usize = SONAR_RETURN_OFFSET + 5;
+const RANGE_CODE_OFFSET: usize = SONAR_RETURN_OFFSET + 7;
+const DATA_LENGTH_OFFSET: usize = SONAR_RETURN_OFFSET + 10;
+const DATA_OFFSET: usize = SONAR_RETURN_OFFSET + 32;
+
+/// A zero-copy view of an encoded [Shot], borrowed from the bytes of a [crate::Reader].
+///
+/// Fields are decoded lazily on access, and the echo samples are handed out as a slice of the
+/// underlying (possibly memory-mapped) data, so scanning a recording does not allocate per shot.
+#[derive(Debug, Copy, Clone)]
+pub struct ShotView<'a> {
+    bytes: &'a [u8],
+    offset: u64,
+    mode: DecodeMode,
+}
+
+impl<'a> ShotView<'a> {
+    /// Create a view over the shot at the start of `bytes`, located at byte `offset` of its source.
+    ///
+    /// Only the magic and the lengths declared by the [FileHeader] are checked; all other fields
+    /// are validated as they are decoded.
+    pub fn new(bytes: &'a [u8], offset: u64) -> Result<Self> {
+        if bytes.len() < Shot::LENGTH {
+            let error = binrw::Error::Io(ErrorKind::UnexpectedEof.into());
+            return Err(Error::decode(error, offset));
+        }
+
+        if !Shot::is_plausible_start(bytes) {
+            let found = Box::new(bytes[..FILE_HEADER_OFFSET].to_vec());
+            return Err(Error::decode(binrw::Error::BadMagic { pos: 0, found }, offset));
+        }
+
+        Ok(Self { bytes: &bytes[..Shot::LENGTH], offset, mode: DecodeMode::default() })
+    }
+
+    /// Decode the [FileHeader] and [Shot] in `mode` instead of [DecodeMode::Strict].
+    #[inline]
+    pub fn with_decode_mode(self, mode: DecodeMode) -> Self {
+        Self { mode, ..self }
+    }
+
+    #[inline]
+    pub fn decode_mode(&self) -> DecodeMode {
+        self.mode
+    }
+
+    /// The byte offset of the shot in its source.
+    #[inline]
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// The encoded bytes of the shot.
+    #[inline]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Decode the [FileHeader].
+    pub fn header(&self) -> Result<FileHeader> {
+        self.decode_with(FILE_HEADER_OFFSET, (self.mode,))
+    }
+
+    /// Decode the [SonarReturnHeader].
+    pub fn sonar_return_header(&self) -> Result<SonarReturnHeader> {
+        self.decode(SONAR_RETURN_OFFSET)
+    }
+
+    /// Decode the [SonarReturnStatus] alone.
+    pub fn status(&self) -> Result<SonarReturnStatus> {
+        self.decode(STATUS_OFFSET)
+    }
+
+    /// Decode the [HeadPosition] alone.
+    pub fn head_position(&self) -> Result<HeadPosition> {
+        self.decode(HEAD_POSITION_OFFSET)
+    }
+
+    /// Decode the [RangeCode] of the sonar return alone.
+    pub fn range_code(&self) -> Result<RangeCode> {
+        self.decode(RANGE_CODE_OFFSET)
+    }
+
+    /// The echo samples, borrowed from the underlying data.
+    pub fn data(&self) -> Result<&'a [u8]> {
+        let mut cursor = Cursor::new(&self.bytes[DATA_LENGTH_OFFSET..]);
+        let data_length = u14::parse(&mut cursor, ENDIAN, ())
+            .map_err(|e| Error::decode(e, self.offset + DATA_LENGTH_OFFSET as u64))?;
+
+        let end = DATA_OFFSET + data_length as usize;
+        self.bytes.get(DATA_OFFSET..end).ok_or_else(|| {
+            let error = binrw::Error::Io(ErrorKind::UnexpectedEof.into());
+            Error::decode(error, self.offset + DATA_OFFSET as u64)
+        })
+    }
+
+    /// Unpack the echo samples according to the [FileHeader].
+    pub fn echo(&self) -> Result<Echo> {
+        let header = self.header()?;
+        Ok(Echo::decode(self.data()?, header.config().data_bits, header.data_size_index()))
+    }
+
+    /// Decode the complete, owned [Shot].
+    pub fn to_shot(&self) -> Result<Shot> {
+        self.decode_with(0, (self.mode,))
+    }
+
+    fn decode<T>(&self, at: usize) -> Result<T>
+    where
+        T: BinRead,
+        for<'b> T::Args<'b>: Default,
+    {
+        self.decode_with(at, Default::default())
+    }
+
+    fn decode_with<T: BinRead>(&self, at: usize, args: T::Args<'_>) -> Result<T> {
+        let mut cursor = Cursor::new(&self.bytes[at..]);
+        T::read_options(&mut cursor, ENDIAN, args)
+            .map_err(|e| Error::decode(e, self.offset + at as u64))
+    }
+}
+
+/// An iterator over the [ShotView]s of a [crate::Reader], created by [crate::Reader::views].
+///
+/// Yields an error for data that does not begin a shot, then ends.
+pub struct ShotViews<'a> {
+    data: &'a [u8],
+    position: usize,
+    failed: bool,
+}
+
+impl<'a> ShotViews<'a> {
+    pub(crate) fn new(data: &'a [u8], position: usize) -> Self {
+        Self { data, position, failed: false }
+    }
+}
+
+impl<'a> Iterator for ShotViews<'a> {
+    type Item = Result<ShotView<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.position >= self.data.len() {
+            return None;
+        }
+
+        let view = ShotView::new(&self.data[self.position..], self.position as u64);
+        match view {
+            Ok(_) => self.position += Shot::LENGTH,
+            Err(_) => self.failed = true,
+        }
+
+        Some(view)
+    }
+}
+
+impl std::iter::FusedIterator for ShotViews<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{shot_bytes, shots_bytes, SHOT_LENGTH};
+    use crate::Reader;
+
+    use log::info;
+    use test_log::test;
+
+    #[test]
+    fn fields() {
+        let bytes = shot_bytes();
+        let view = ShotView::new(&bytes, 0).expect("It should not return an error");
+        let shot = view.to_shot().expect("It should not return an error");
+
+        assert_eq!(shot.header, view.header().unwrap());
+        assert_eq!(shot.sonar_return.header(), &view.sonar_return_header().unwrap());
+        assert_eq!(shot.sonar_return.header().status, view.status().unwrap());
+        assert_eq!(shot.sonar_return.header().head_position, view.head_position().unwrap());
+        assert_eq!(shot.sonar_return.header().range_code, view.range_code().unwrap());
+        assert_eq!(shot.sonar_return.data(), view.data().unwrap());
+        assert_eq!(shot.echo(), view.echo().unwrap());
+    }
+
+    #[test]
+    fn data_is_borrowed() {
+        let bytes = shot_bytes();
+        let view = ShotView::new(&bytes, 0).unwrap();
+        let data = view.data().unwrap();
+
+        assert_eq!(250, data.len());
+        assert!(std::ptr::eq(&bytes[DATA_OFFSET], &data[0]));
+    }
+
+    #[test]
+    fn new_invalid() {
+        let mut bytes = shot_bytes();
+
+        let error = ShotView::new(&bytes[..100], 42).unwrap_err();
+        info!("Got error: {error}");
+        assert!(error.is_truncated());
+        assert_eq!(Some(42), error.offset);
+
+        bytes[0] = b'X';
+        let error = ShotView::new(&bytes, 42).unwrap_err();
+        info!("Got error: {error}");
+        assert!(!error.is_truncated());
+    }
+
+    #[test]
+    fn views() {
+        let mut data = shots_bytes(4);
+        data[SHOT_LENGTH + STATUS_OFFSET] = 0b1000_0000; // range error
+        data[3 * SHOT_LENGTH + STATUS_OFFSET] = 0b0001_0000; // calibration error
+        let reader = Reader::new(data);
+
+        let errors: Vec<u64> = reader
+            .views()
+            .map(|view| view.expect("It should not return an error"))
+            .filter(|view| view.status().unwrap().has_error())
+            .map(|view| view.offset())
+            .collect();
+
+        assert_eq!(vec![SHOT_LENGTH as u64, 3 * SHOT_LENGTH as u64], errors);
+    }
+}
@@ -0,0 +1,310 @@
+use crate::types::SubFormat;
+use crate::{Error, Result, Shot};
+use binrw::{BinRead, BinWrite};
+use std::io::{Cursor, Seek, Write};
+
+#[cfg(not(target_family = "wasm"))]
+use std::{fs, io::BufWriter, path::Path};
+
+/// A `.31l` shot writer appending to any [Write] + [Seek] sink.
+///
+/// Every shot is encoded into an internal buffer and decoded again before anything reaches the
+/// sink, so a shot is only written if the [crate::Reader] would accept it, and a rejected shot
+/// leaves the sink untouched.
+pub struct Writer<W: Write + Seek> {
+    inner: W,
+    count: usize,
+    buffer: Cursor<Vec<u8>>,
+    sub_format: Option<SubFormat>,
+}
+
+impl<W: Write + Seek> Writer<W> {
+    /// Create a [Writer] appending at the current position of `inner`.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            count: 0,
+            buffer: Cursor::new(Vec::with_capacity(Shot::LENGTH)),
+            sub_format: None,
+        }
+    }
+
+    /// Convert the timestamp of every shot to `sub_format` instead of keeping the format each
+    /// shot was recorded in, e.g. to bring old recordings to [SubFormat::Milliseconds].
+    pub fn with_sub_format(mut self, sub_format: SubFormat) -> Self {
+        self.sub_format = Some(sub_format);
+        self
+    }
+
+    /// The number of shots written.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Flush and unwrap the sink.
+    pub fn into_inner(mut self) -> Result<W> {
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    /// Encode, validate and write a [Shot].
+    ///
+    /// Errors carry the byte offset in the sink at which the shot would have started.
+    pub fn write_shot(&mut self, shot: &Shot) -> Result<()> {
+        let offset = self.inner.stream_position()?;
+
+        let converted;
+        let shot = match self.sub_format {
+            Some(sub_format) if shot.header.sub_format() != sub_format => {
+                let mut shot = shot.clone();
+                shot.header.set_sub_format(sub_format);
+                converted = shot;
+                &converted
+            },
+            _ => shot,
+        };
+
+        self.buffer.get_mut().clear();
+        self.buffer.set_position(0);
+        let index = self.count;
+        shot.write(&mut self.buffer).map_err(|e| Error::encode(e, offset).with_shot(index))?;
+
+        let bytes = self.buffer.get_ref();
+        if bytes.len() != Shot::LENGTH {
+            return Err(Error::invalid_data(format!(
+                "Encoded shot at byte {offset} is {} bytes, expected {}",
+                bytes.len(),
+                Shot::LENGTH
+            ))
+            .with_shot(index));
+        }
+
+        Shot::read(&mut Cursor::new(bytes))
+            .map_err(|e| Error::decode(e, offset).with_shot(index))?;
+
+        self.inner.write_all(bytes)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Write every [Shot] of `shots`, stopping at the first error.
+    ///
+    /// Returns the number of shots written.
+    pub fn write_shots<'a, I>(&mut self, shots: I) -> Result<usize>
+    where
+        I: IntoIterator<Item = &'a Shot>,
+    {
+        let mut written = 0;
+
+        for shot in shots {
+            self.write_shot(shot)?;
+            written += 1;
+        }
+
+        Ok(written)
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<()> {
+        self.inner.flush().map_err(Into::into)
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl Writer<BufWriter<fs::File>> {
+    /// Create a new `.31l` file at `path`, truncating any existing file.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = fs::File::create(path)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{shot_bytes, shots_bytes, SHOT_LENGTH};
+    use crate::{ErrorKind, Reader, SonarReturn, StreamReader};
+
+    use log::info;
+    use test_log::test;
+
+    /// Decode `data`, write the shots back out and return the written bytes.
+    fn round_trip(data: &[u8]) -> Vec<u8> {
+        let shots: Vec<Shot> = StreamReader::new(data)
+            .shots()
+            .collect::<Result<_>>()
+            .expect("It should not return an error");
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        let written = writer.write_shots(&shots).expect("It should not return an error");
+        assert_eq!(shots.len(), written);
+        assert_eq!(shots.len(), writer.count());
+
+        writer.into_inner().unwrap().into_inner()
+    }
+
+    #[test]
+    fn write_shot() {
+        let data = shots_bytes(3);
+        assert_eq!(data, round_trip(&data));
+    }
+
+    #[test]
+    fn write_shot_round_trips_fields() {
+        // (offset, raw bytes) pairs exercising the scaled and packed fields
+        let cases: Vec<(usize, Vec<u8>)> = vec![
+            (20, b"23:59:59\0".to_vec()),
+            (29, b".07\0".to_vec()),
+            (29, b".99\0".to_vec()),
+            (29, b"001\0".to_vec()),
+            (29, b"999\0".to_vec()),
+            (42, vec![3]),           // absorption: 0.03 dB/m
+            (42, vec![255]),         // absorption: 2.55 dB/m
+            (46, vec![0x3A, 0x9A]),  // sound velocity: 1500.2 m/s
+            (46, vec![0x3A, 0x99]),  // sound velocity: 1500.1 m/s
+            (81, vec![0x00, 0x07]),  // real-time PRF: 0.07 Hz
+            (233, vec![0x00, 0x00]), // head position: -180°
+            (233, vec![0x57, 0x44]), // head position: -0.3°, clockwise
+            (233, vec![0x30, 0x09]), // head position: 180°
+            (236, vec![0x03, 0x00]), // profile range: 3 samples
+            (236, vec![0x7F, 0x7F]), // profile range: maximum
+            (244, vec![0x03, 0x00]), // roll angle: 0.075°
+            (244, vec![0xFD, 0xFF]), // roll angle: -0.075°, flags set
+            (246, vec![0x00, 0x20]), // pitch angle: minimum
+            (248, vec![0x07, 0x00]), // roll acceleration
+            (250, vec![0xF9, 0x3F]), // pitch acceleration: negative
+        ];
+
+        for (offset, raw) in cases {
+            info!("Round-tripping {raw:02X?} at byte {offset}");
+            let mut data = shot_bytes();
+            data[offset..offset + raw.len()].copy_from_slice(&raw);
+            let got = round_trip(&data);
+            assert_eq!(raw, got[offset..offset + raw.len()]);
+            assert_eq!(data, got);
+        }
+    }
+
+    #[test]
+    fn write_shot_preserves_reserved() {
+        let regions = [(33, 1), (35, 2), (45, 1), (48, 31), (83, 15), (111, 117), (240, 4)];
+        let regions = regions.into_iter().chain([(252, 8), (511, 1)]);
+
+        let mut data = shot_bytes();
+        for (offset, length) in regions {
+            for (i, byte) in data[offset..offset + length].iter_mut().enumerate() {
+                *byte = (offset + i) as u8 | 0x80;
+            }
+        }
+
+        let shot = Reader::new(data.clone()).next().expect("It should decode the shot");
+        assert_eq!(&[33 | 0x80], shot.header.reserved_33());
+        assert_eq!(data[48..79], shot.header.reserved_48()[..]);
+        assert_eq!(data[111..228], shot.reserved_111()[..]);
+        assert_eq!(data[240..244], shot.sonar_return.header().reserved_12()[..]);
+        assert_eq!(data[252..260], shot.sonar_return.header().reserved_24()[..]);
+        assert_eq!(data[511..], shot.reserved_511()[..]);
+
+        assert_eq!(data, round_trip(&data));
+    }
+
+    #[test]
+    fn write_shot_converts_sub_format() {
+        const SUB_OFFSET: usize = 29;
+
+        let cases = [
+            (b".69\0", SubFormat::Milliseconds, b"690\0"),
+            (b"690\0", SubFormat::Milliseconds, b"690\0"),
+            (b"695\0", SubFormat::Hundredths, b".69\0"),
+            (b".69\0", SubFormat::Hundredths, b".69\0"),
+        ];
+
+        for (sub, sub_format, want) in cases {
+            info!("Writing {sub:?} as {sub_format:?}, want {want:?}");
+            let mut data = shot_bytes();
+            data[SUB_OFFSET..SUB_OFFSET + 4].copy_from_slice(sub);
+
+            let shot = Reader::new(data).next().expect("It should decode the shot");
+            let mut writer = Writer::new(Cursor::new(Vec::new())).with_sub_format(sub_format);
+            writer.write_shot(&shot).expect("It should not return an error");
+
+            let got = writer.into_inner().unwrap().into_inner();
+            assert_eq!(want, &got[SUB_OFFSET..SUB_OFFSET + 4]);
+        }
+    }
+
+    #[test]
+    fn write_shot_appends() {
+        let mut cursor = Cursor::new(vec![0xAA; 7]);
+        cursor.set_position(7);
+
+        let shot = Reader::new(shot_bytes()).next().unwrap();
+        let mut writer = Writer::new(cursor);
+        writer.write_shot(&shot).expect("It should not return an error");
+
+        let data = writer.into_inner().unwrap().into_inner();
+        assert_eq!(7 + SHOT_LENGTH, data.len());
+        assert_eq!(shot_bytes(), data[7..]);
+    }
+
+    #[test]
+    fn write_shot_invalid() {
+        let shot = Reader::new(shot_bytes()).next().unwrap();
+
+        // unencodable: the roll angle exceeds the i14 range
+        let mut unencodable = shot.clone();
+        unencodable.sonar_return.header_mut().roll_angle = 1000.0.into();
+
+        // encodable, but the data length swallows the termination byte
+        let mut undecodable = shot.clone();
+        undecodable.sonar_return.header_mut().data_length += 1;
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        writer.write_shot(&shot).expect("It should not return an error");
+
+        for invalid in [unencodable, undecodable] {
+            let error = writer.write_shot(&invalid).unwrap_err();
+            info!("Got error: {error}");
+            assert_eq!(Some(SHOT_LENGTH as u64), error.offset);
+            assert_eq!(Some(1), error.shot);
+        }
+
+        // encodable and decodable, but longer than a shot
+        let mut long = shot.clone();
+        long.sonar_return = SonarReturn::new(long.sonar_return.header().clone(), vec![0; 251]);
+        let error = writer.write_shot(&long).unwrap_err();
+        info!("Got error: {error}");
+        assert_eq!(ErrorKind::InvalidData, error.kind);
+        assert_eq!(Some(1), error.shot);
+
+        assert_eq!(1, writer.count());
+        assert_eq!(SHOT_LENGTH, writer.get_ref().get_ref().len(), "It should not write the shots");
+    }
+
+    #[test]
+    #[ignore = "needs the LFS sample, fetched in CI"]
+    fn write_sample_file() {
+        let mut path = project_root::get_project_root().unwrap();
+        path.push("sample");
+        path.push("27JUL2023-101914.31l");
+
+        let data = fs::read(&path).expect("Failed to read the sample file");
+        assert!(
+            Shot::is_plausible_start(&data),
+            "The sample file is a Git LFS pointer, fetch it with `git lfs pull`"
+        );
+
+        assert_eq!(data, round_trip(&data));
+    }
+}
@@ -3,24 +3,53 @@
 //! `imagenex831l` is a library for interacting with IMAGENEX 831L sonar units and their data.
 
 mod build;
+mod cloud;
 mod doc;
+mod echo;
+mod fit;
+mod geometry;
+mod image;
+mod render;
 mod shot;
+mod simulator;
 mod sonar_return;
+mod sweep;
 mod switch_data;
+mod transport;
 
 mod error;
+#[cfg(test)]
+mod fixtures;
 mod io;
 pub(crate) mod logger;
 pub mod types;
+mod validation;
+mod waterfall;
 
 use binrw::Endian;
-pub use io::Reader;
+pub use cloud::{CloudFormat, CloudPoint, PointCloud};
+pub use echo::{Echo, EchoProfile};
+pub use fit::{CircleFit, EllipseFit, FitOptions};
+pub use geometry::{Attitude, AttitudeSource, Geometry, ProfilePoint};
+pub use image::{Colormap, Image, ImageFormat};
+pub use io::{
+    IndexEntry, ReadShot, Reader, Recover, Recovered, ShotIndex, ShotView, ShotViews, Shots,
+    StreamReader, Writer, SIDECAR_EXTENSION,
+};
+pub use render::{RenderOptions, PROFILE_COLOR, RING_COLOR};
 pub use shot::Shot;
+pub use simulator::{Debris, Simulator, SyntheticPipe};
 pub use sonar_return::SonarReturn;
-pub use switch_data::SwitchData;
+pub use sweep::{Sweep, SweepAssembler, SweepStatus, Sweeps};
+pub use switch_data::{SwitchData, SwitchDataBuilder};
+#[cfg(not(target_family = "wasm"))]
+pub use transport::{NetworkOptions, SerialTransport, TcpTransport, UdpTransport};
+pub use transport::{Sonar, Transport};
+pub use validation::{DecodeMode, Warning};
+pub use waterfall::{Waterfall, WaterfallAxis, WaterfallBuilder, WaterfallOptions};
 
 pub use build::{IDENTIFIER, VERSION};
-pub use error::Error;
+pub use error::{Error, ErrorKind};
 
 #[cfg(feature = "pyo3")]
 use pyo3::prelude::*;
@@ -41,6 +70,20 @@ fn py_init(module: &Bound<'_, PyModule>) -> PyResult<()> {
 
     module.add_wrapped(pyo3::wrap_pymodule!(types::types))?;
     module.add_class::<Reader>()?;
+    module.add_class::<DecodeMode>()?;
+    module.add_class::<Echo>()?;
+    module.add_class::<EchoProfile>()?;
+    module.add_class::<Attitude>()?;
+    module.add_class::<AttitudeSource>()?;
+    module.add_class::<CircleFit>()?;
+    module.add_class::<EllipseFit>()?;
+    module.add_class::<Geometry>()?;
+    module.add_class::<ProfilePoint>()?;
+    module.add_class::<Sweep>()?;
+    module.add_class::<SweepStatus>()?;
+    module.add_class::<SwitchData>()?;
+    module.add_class::<Warning>()?;
+    error::exceptions::register(module)?;
     Ok(())
 }
 
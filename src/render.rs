@@ -0,0 +1,304 @@
+//! Rendering of sweeps into the circular image of the IMAGENEX display.
+use crate::image::{Colormap, Image};
+use crate::{Geometry, Sweep};
+
+/// The color of the range rings.
+pub const RING_COLOR: [u8; 3] = [0, 160, 0];
+
+/// The color of the detected profile.
+pub const PROFILE_COLOR: [u8; 3] = [255, 0, 0];
+
+/// The color of the area no beam covers.
+const BACKGROUND_COLOR: [u8; 3] = [0, 0, 0];
+
+/// Beams cover pixels up to this many steps from their angle.
+const BEAM_WIDTH_STEPS: f32 = 0.75;
+
+/// The radius of the dots marking the profile, in pixels.
+const PROFILE_RADIUS: i64 = 1;
+
+/// How a [Sweep] is rendered by [Sweep::render].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderOptions {
+    /// The width and height of the square image, in pixels.
+    pub size: u32,
+
+    /// The range at the edge of the image in meters, or `None` for the
+    /// [RangeCode](crate::types::RangeCode) of the first shot.
+    pub range: Option<f32>,
+
+    /// The colors of the echo intensities.
+    pub colormap: Colormap,
+
+    /// The gain added to the echo intensities, in dB.
+    pub gain: f32,
+
+    /// The factor the displayed intensities are stretched by about mid-scale.
+    pub contrast: f32,
+
+    /// The spacing of the range rings in meters, or `None` for no rings.
+    pub rings: Option<f32>,
+
+    /// Whether to overlay the detected profile points.
+    pub profile: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            size: 512,
+            range: None,
+            colormap: Colormap::default(),
+            gain: 0.0,
+            contrast: 1.0,
+            rings: None,
+            profile: true,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// The displayed intensity of a `normalized` sample spanning `decibels` of dynamic range.
+    ///
+    /// The [RenderOptions::gain] is a fraction of the [Logf](crate::types::Logf) range of the
+    /// samples, so a gain equal to that range lifts the weakest echo to full scale.
+    pub fn intensity(&self, normalized: f32, decibels: f32) -> f32 {
+        let level = normalized + self.gain / decibels;
+        ((level - 0.5) * self.contrast + 0.5).clamp(0.0, 1.0)
+    }
+}
+
+/// The echo of one shot along its beam angle.
+struct Beam {
+    angle: f32,
+    ranges: Vec<f32>,
+    intensities: Vec<f32>,
+}
+
+impl Beam {
+    /// The intensity at `range` meters, or `None` beyond the last sample.
+    fn at(&self, range: f32) -> Option<f32> {
+        let i = self.ranges.partition_point(|&r| r < range);
+        self.intensities.get(i).copied()
+    }
+}
+
+/// The beams of `sweep` sorted by angle.
+fn beams(sweep: &Sweep, options: &RenderOptions) -> Vec<Beam> {
+    let mut beams: Vec<Beam> = sweep
+        .shots()
+        .iter()
+        .map(|shot| {
+            let geometry = Geometry::from_header(&shot.header);
+            let decibels = shot.header.config().logf.decibels() as f32;
+            let profile = shot.echo_profile();
+            Beam {
+                angle: geometry.angle(&shot.sonar_return.header().head_position),
+                ranges: profile.ranges().to_vec(),
+                intensities: profile.iter().map(|(_, n)| options.intensity(n, decibels)).collect(),
+            }
+        })
+        .collect();
+
+    beams.sort_by(|a, b| a.angle.total_cmp(&b.angle));
+    beams
+}
+
+/// The beam nearest to `angle` within `width` degrees, across the ±180° seam.
+fn nearest(beams: &[Beam], angle: f32, width: f32) -> Option<&Beam> {
+    let i = beams.partition_point(|beam| beam.angle < angle);
+    let candidates = [i.checked_sub(1).unwrap_or(beams.len() - 1), i % beams.len()];
+
+    candidates
+        .into_iter()
+        .map(|i| &beams[i])
+        .map(|beam| (beam, ((beam.angle - angle + 180.0).rem_euclid(360.0) - 180.0).abs()))
+        .filter(|&(_, distance)| distance <= width)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(beam, _)| beam)
+}
+
+impl Sweep {
+    /// Render the echoes of the sweep along their beam angles on a square raster centered on the
+    /// head, in the sonar frame of the [Geometry] of each shot.
+    pub fn render(&self, options: &RenderOptions) -> Image {
+        let size = options.size;
+        let mut image = Image::new(size, size, BACKGROUND_COLOR);
+        if self.is_empty() || size == 0 {
+            return image;
+        }
+
+        let range = options
+            .range
+            .filter(|&range| range > 0.0)
+            .unwrap_or_else(|| self.shots()[0].sonar_return.header().range_code.range());
+        let scale = 2.0 * range / size as f32;
+        let center = size as f32 / 2.0;
+
+        let beams = beams(self, options);
+        let width = self.step() * BEAM_WIDTH_STEPS;
+        let rings = options.rings.filter(|&spacing| spacing > 0.0);
+
+        for row in 0..size {
+            for column in 0..size {
+                let x = (column as f32 + 0.5 - center) * scale;
+                let y = (center - row as f32 - 0.5) * scale;
+                let r = x.hypot(y);
+                if r > range {
+                    continue;
+                }
+
+                let on_ring = rings.is_some_and(|spacing| {
+                    let ring = (r / spacing).round() * spacing;
+                    ring > 0.0 && (r - ring).abs() <= scale / 2.0
+                });
+
+                let color = match on_ring {
+                    true => Some(RING_COLOR),
+                    false => nearest(&beams, x.atan2(y).to_degrees(), width)
+                        .and_then(|beam| beam.at(r))
+                        .map(|intensity| options.colormap.color(intensity)),
+                };
+
+                if let Some(color) = color {
+                    image.set_pixel(column as i64, row as i64, color);
+                }
+            }
+        }
+
+        if options.profile {
+            let points = self.shots().iter().filter_map(|shot| shot.profile_point());
+            for point in points.filter(|point| point.range <= range) {
+                let column = (center + point.x / scale).floor() as i64;
+                let row = (center - point.y / scale).floor() as i64;
+                for dy in -PROFILE_RADIUS..=PROFILE_RADIUS {
+                    for dx in -PROFILE_RADIUS..=PROFILE_RADIUS {
+                        image.set_pixel(column + dx, row + dy, PROFILE_COLOR);
+                    }
+                }
+            }
+        }
+
+        image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::shot_bytes;
+    use crate::types::{Direction, HeadPosition};
+    use crate::{Shot, Sweeps};
+    use binrw::BinRead;
+    use std::io::Cursor;
+
+    use log::info;
+    use test_log::test;
+
+    const SIZE: u32 = 101;
+
+    /// A complete polar sweep of the fixture shot, stepping 0.9° clockwise from 0°.
+    fn sweep(profile_range: f32) -> Sweep {
+        let shot =
+            Shot::read(&mut Cursor::new(shot_bytes())).expect("It should not return an error");
+        let shots: Vec<_> = (0..400)
+            .map(|i| {
+                let mut shot = shot.clone();
+                let header = shot.sonar_return.header_mut();
+                let angle = (0.9 * i as f32 + 180.0).rem_euclid(360.0) - 180.0;
+                header.head_position = HeadPosition::new(angle, Direction::Clockwise);
+                header.profile_range = profile_range;
+                shot
+            })
+            .collect();
+
+        let mut sweeps = Sweeps::new(shots);
+        let sweep = sweeps.next().expect("It should assemble a sweep");
+        assert!(sweep.is_complete());
+        sweep
+    }
+
+    fn options() -> RenderOptions {
+        RenderOptions { size: SIZE, profile: false, ..RenderOptions::default() }
+    }
+
+    fn luma(image: &Image, column: u32, row: u32) -> u8 {
+        let [r, g, b] = image.pixel(column, row).expect("It should be within the image");
+        Image::new(1, 1, [r, g, b]).luma()[0]
+    }
+
+    #[test]
+    fn intensity() {
+        let cases = vec![
+            (0.0, 1.0, 0.0, 0.0),
+            (0.5, 1.0, 0.0, 0.5),
+            (0.0, 1.0, 20.0, 1.0),
+            (0.0, 1.0, 10.0, 0.5),
+            (0.25, 2.0, 0.0, 0.0),
+            (0.75, 2.0, 0.0, 1.0),
+        ];
+
+        for (normalized, contrast, gain, want) in cases {
+            info!(
+                "Displaying {normalized} with contrast {contrast} and gain {gain} dB, want {want}"
+            );
+            let options = RenderOptions { gain, contrast, ..RenderOptions::default() };
+            assert_eq!(want, options.intensity(normalized, 20.0));
+        }
+    }
+
+    #[test]
+    fn render() {
+        let image = sweep(0.0).render(&options());
+        assert_eq!((SIZE, SIZE), (image.width(), image.height()));
+
+        let center = SIZE / 2;
+        let cases = vec![(center, 45), (45, center), (center, 55), (55, center)];
+        for (column, row) in cases {
+            let near = luma(&image, column, row);
+            let far = luma(&image, column * 2 - center, row * 2 - center);
+            info!("Comparing ({column}, {row}) at {near} to its double at {far}");
+            assert!(near < far, "echo should strengthen with range");
+        }
+
+        assert_eq!(Some(BACKGROUND_COLOR), image.pixel(0, 0));
+        assert!(luma(&image, center, center) < 16);
+    }
+
+    #[test]
+    fn render_gain() {
+        let options = RenderOptions { gain: 20.0, ..options() };
+        let image = sweep(0.0).render(&options);
+        assert_eq!(Some([255; 3]), image.pixel(SIZE / 2, SIZE / 2));
+    }
+
+    #[test]
+    fn render_range() {
+        let options = RenderOptions { range: Some(2.0), ..options() };
+        let image = sweep(0.0).render(&options);
+        assert_eq!(Some(BACKGROUND_COLOR), image.pixel(SIZE / 2, 5));
+        assert_ne!(Some(BACKGROUND_COLOR), image.pixel(SIZE / 2, 30));
+    }
+
+    #[test]
+    fn render_rings() {
+        let options = RenderOptions { rings: Some(0.5), ..options() };
+        let image = sweep(0.0).render(&options);
+        let center = SIZE / 2;
+        for (column, row) in [(center, 25), (center, 75), (25, center), (75, center)] {
+            info!("Checking for a ring at ({column}, {row})");
+            assert_eq!(Some(RING_COLOR), image.pixel(column, row));
+        }
+    }
+
+    #[test]
+    fn render_profile() {
+        let options = RenderOptions { profile: true, ..options() };
+        let image = sweep(0.5).render(&options);
+        let center = SIZE / 2;
+        for (column, row) in [(center, 25), (center, 75), (25, center), (75, center)] {
+            info!("Checking for the profile at ({column}, {row})");
+            assert_eq!(Some(PROFILE_COLOR), image.pixel(column, row));
+        }
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{types::FileHeader, SonarReturn};
+use crate::{
+    types::{primitive::sound_velocity, FileHeader},
+    validation::Warning,
+    Attitude, DecodeMode, Echo, EchoProfile, Geometry, ProfilePoint, Result, SonarReturn,
+};
 use binrw::{BinRead, BinWrite};
 
 #[cfg(feature = "pyo3")]
@@ -7,22 +11,259 @@ use pyo3::prelude::*;
 #[derive(Debug, BinRead, BinWrite, PartialEq, Clone, derive_new::new)]
 #[cfg_attr(feature = "pyo3", pyclass(eq))]
 #[brw(big, magic = b"31L")]
+#[br(import(mode: DecodeMode))]
 pub struct Shot {
     #[cfg(not(feature = "pyo3"))]
-    #[brw(pad_after = 117)]
+    #[br(args(mode))]
     pub header: FileHeader,
 
     #[cfg(feature = "pyo3")]
     #[pyo3(get, set)]
-    #[brw(pad_after = 117)]
+    #[br(args(mode))]
     pub header: FileHeader,
 
+    /// Reserved bytes 111 to 227 following the [FileHeader].
     #[cfg(not(feature = "pyo3"))]
-    #[brw(pad_after = 1)]
+    #[new(value = "[0; 117]")]
+    reserved_111: [u8; 117],
+
+    /// Reserved bytes 111 to 227 following the [FileHeader].
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    #[new(value = "[0; 117]")]
+    reserved_111: [u8; 117],
+
+    #[cfg(not(feature = "pyo3"))]
+    #[br(args(mode))]
     pub sonar_return: SonarReturn,
 
     #[cfg(feature = "pyo3")]
     #[pyo3(get, set)]
-    #[brw(pad_after = 1)]
+    #[br(args(mode))]
     pub sonar_return: SonarReturn,
+
+    /// The reserved final byte 511 of the shot.
+    #[cfg(not(feature = "pyo3"))]
+    #[new(default)]
+    reserved_511: [u8; 1],
+
+    /// The reserved final byte 511 of the shot.
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    #[new(default)]
+    reserved_511: [u8; 1],
+}
+
+impl Shot {
+    /// The magic bytes marking the start of every shot.
+    pub const MAGIC: &'static [u8; 3] = b"31L";
+
+    /// The encoded length of every shot in bytes.
+    pub const LENGTH: usize = FileHeader::VALID_TOTAL_LENGTH as usize;
+
+    /// Check whether `bytes` plausibly begin a shot: the [Shot::MAGIC] followed by a
+    /// [FileHeader] declaring the valid total and data lengths.
+    pub fn is_plausible_start(bytes: &[u8]) -> bool {
+        let Some(header) = bytes.get(..8) else {
+            return false;
+        };
+
+        let total_length = u16::from_be_bytes([header[4], header[5]]);
+        let data_length = u16::from_be_bytes([header[6], header[7]]);
+
+        header.starts_with(Self::MAGIC)
+            && total_length == FileHeader::VALID_TOTAL_LENGTH
+            && data_length == FileHeader::VALID_DATA_LENGTH
+    }
+
+    /// Unpack the echo samples according to the [Config](crate::types::Config) of the
+    /// [FileHeader].
+    pub fn echo(&self) -> Echo {
+        let data_bits = self.header.config().data_bits;
+        Echo::decode(self.sonar_return.data(), data_bits, self.header.data_size_index())
+    }
+
+    /// Annotate the [Shot::echo] with the range of each sample, using the
+    /// [RangeCode](crate::types::RangeCode) of the sonar return and the sound velocity of the
+    /// [FileHeader].
+    pub fn echo_profile(&self) -> EchoProfile {
+        let range_code = self.sonar_return.header().range_code;
+        EchoProfile::new(self.echo(), range_code, self.header.sound_velocity())
+    }
+
+    /// The profile point of the shot in the sonar frame of the [Geometry] recorded in its
+    /// [FileHeader], or `None` if no profile point was detected.
+    #[inline]
+    pub fn profile_point(&self) -> Option<ProfilePoint> {
+        Geometry::from_header(&self.header).profile_point(self)
+    }
+
+    /// The pitch and roll of the head from the first valid source recorded with the shot.
+    #[inline]
+    pub fn attitude(&self) -> Attitude {
+        Attitude::from_shot(self)
+    }
+
+    /// The [Shot::profile_point] rotated into the level pipe frame by the [Shot::attitude].
+    #[inline]
+    pub fn level_profile_point(&self) -> Option<ProfilePoint> {
+        Geometry::from_header(&self.header).level_profile_point(self)
+    }
+
+    /// Rescale the ranges of the shot from its recorded sound velocity to `sound_velocity` in m/s.
+    ///
+    /// The [profile_range](crate::types::SonarReturnHeader::profile_range) is scaled by the ratio
+    /// of the velocities and the sound velocity of the [FileHeader] replaced, which rescales the
+    /// [Shot::echo_profile]. The velocity is rounded to the 0.1 m/s it is recorded in, and a
+    /// recorded velocity that is not positive is taken as 1500 m/s. A sonar return without a
+    /// detected profile point keeps marking it as undetected.
+    pub fn correct_sound_velocity(&mut self, sound_velocity: f32) -> Result<()> {
+        if !sound_velocity::valid(sound_velocity) || sound_velocity <= 0.0 {
+            return Err(sound_velocity::out_of_range(sound_velocity).into());
+        }
+        let sound_velocity = (sound_velocity * 10.0).round() / 10.0;
+
+        let recorded = match self.header.sound_velocity() > 0.0 {
+            true => self.header.sound_velocity(),
+            false => sound_velocity::V_VALUE,
+        };
+        let header = self.sonar_return.header_mut();
+        if header.profile_detected() {
+            header.profile_range *= sound_velocity / recorded;
+        }
+        self.header.set_sound_velocity(sound_velocity);
+        Ok(())
+    }
+
+    /// Check every value of the shot against its specification, returning a [Warning] for each
+    /// violation.
+    ///
+    /// Shots decoded in [DecodeMode::Lenient] may hold values that a strict decode rejects; the
+    /// warnings list them so they can be reported or corrected before writing.
+    pub fn validate(&self) -> Vec<Warning> {
+        let header = self.header.validate().into_iter().map(|w| w.within("header"));
+        let sonar_return =
+            self.sonar_return.validate().into_iter().map(|w| w.within("sonar_return"));
+        header.chain(sonar_return).collect()
+    }
+
+    /// The raw reserved bytes 111 to 227 following the [FileHeader], preserved as recorded.
+    #[inline]
+    pub fn reserved_111(&self) -> &[u8; 117] {
+        &self.reserved_111
+    }
+
+    #[inline]
+    pub fn reserved_111_mut(&mut self) -> &mut [u8; 117] {
+        &mut self.reserved_111
+    }
+
+    /// The raw reserved final byte 511 of the shot, preserved as recorded.
+    #[inline]
+    pub fn reserved_511(&self) -> &[u8; 1] {
+        &self.reserved_511
+    }
+
+    #[inline]
+    pub fn reserved_511_mut(&mut self) -> &mut [u8; 1] {
+        &mut self.reserved_511
+    }
+}
+
+#[cfg(feature = "pyo3")]
+#[pymethods]
+impl Shot {
+    #[pyo3(name = "echo")]
+    pub(crate) fn py_echo(&self) -> Echo {
+        self.echo()
+    }
+
+    #[pyo3(name = "echo_profile")]
+    pub(crate) fn py_echo_profile(&self) -> EchoProfile {
+        self.echo_profile()
+    }
+
+    #[pyo3(name = "profile_point")]
+    pub(crate) fn py_profile_point(&self) -> Option<ProfilePoint> {
+        self.profile_point()
+    }
+
+    #[pyo3(name = "attitude")]
+    pub(crate) fn py_attitude(&self) -> Attitude {
+        self.attitude()
+    }
+
+    #[pyo3(name = "level_profile_point")]
+    pub(crate) fn py_level_profile_point(&self) -> Option<ProfilePoint> {
+        self.level_profile_point()
+    }
+
+    #[pyo3(name = "correct_sound_velocity")]
+    pub(crate) fn py_correct_sound_velocity(&mut self, sound_velocity: f32) -> Result<()> {
+        self.correct_sound_velocity(sound_velocity)
+    }
+
+    #[pyo3(name = "validate")]
+    pub(crate) fn py_validate(&self) -> Vec<Warning> {
+        self.validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::shot_bytes;
+    use crate::ErrorKind;
+    use std::io::Cursor;
+
+    use log::info;
+    use test_log::test;
+
+    #[test]
+    fn correct_sound_velocity() {
+        let mut shot =
+            Shot::read(&mut Cursor::new(shot_bytes())).expect("It should not return an error");
+        shot.sonar_return.header_mut().profile_range = 0.5;
+        let ranges = shot.echo_profile().ranges().to_vec();
+
+        shot.correct_sound_velocity(1482.0).expect("It should not return an error");
+        info!("Got {:?}", shot.sonar_return.header());
+        assert_eq!(1482.0, shot.header.sound_velocity());
+        assert!((0.5 * 1482.0 / 1500.0 - shot.sonar_return.header().profile_range).abs() < 1e-6);
+
+        let scaled: Vec<f32> = ranges.iter().map(|range| range * 1482.0 / 1500.0).collect();
+        for (want, got) in scaled.iter().zip(shot.echo_profile().ranges()) {
+            assert!((want - got).abs() < 1e-5, "want {want}, got {got}");
+        }
+
+        let mut bytes = Vec::new();
+        shot.write(&mut Cursor::new(&mut bytes)).expect("It should not return an error");
+        let read = Shot::read(&mut Cursor::new(bytes)).expect("It should not return an error");
+        assert_eq!(1482.0, read.header.sound_velocity());
+    }
+
+    #[test]
+    fn correct_sound_velocity_undetected() {
+        let mut shot =
+            Shot::read(&mut Cursor::new(shot_bytes())).expect("It should not return an error");
+        shot.correct_sound_velocity(1600.0).expect("It should not return an error");
+        assert!(!shot.sonar_return.header().profile_detected());
+
+        let mut bytes = Vec::new();
+        shot.write(&mut Cursor::new(&mut bytes)).expect("It should not return an error");
+        assert_eq!([0x00, 0x00], bytes[236..238]);
+    }
+
+    #[test]
+    fn correct_sound_velocity_invalid() {
+        let mut shot =
+            Shot::read(&mut Cursor::new(shot_bytes())).expect("It should not return an error");
+
+        for sound_velocity in [0.0, -1.0, 3300.0] {
+            info!("Correcting to {sound_velocity} m/s, want error");
+            let error = shot.correct_sound_velocity(sound_velocity).unwrap_err();
+            assert!(matches!(error.kind(), ErrorKind::OutOfRange { .. }));
+        }
+        assert_eq!(1500.0, shot.header.sound_velocity());
+    }
 }
@@ -0,0 +1,845 @@
+//! A software stand-in for an IMAGENEX 831L sonar head inside a synthetic pipe.
+use crate::transport::Transport;
+use crate::types::primitive::{profile_range, sector_size, sound_velocity::V_VALUE, u14};
+use crate::types::{
+    Acceleration, Angle, DataBits, Direction, HeadPosition, Mode, MotorCalibrate,
+    ProfilePointDetection, SonarReturnHeader, SonarReturnMagic, SonarReturnStatus, SonarType,
+    StepDirection,
+};
+use crate::{Error, Result, SonarReturn, SwitchData, ENDIAN};
+use binrw::{BinRead, BinWrite};
+use std::io::{Cursor, ErrorKind, Read, Write};
+use std::time::Duration;
+
+/// The magic bytes every [SwitchData] begins with.
+const MAGIC: [u8; 2] = [0xFE, 0x44];
+
+/// The resolution of the head position in degrees.
+const TICK: f32 = 0.3;
+
+/// The number of head positions in half a turn.
+const HALF_TURN: i32 = 600;
+
+/// The reflectivity of the pipe wall, debris and water surface, as a fraction of full scale.
+const WALL: f32 = 0.8;
+const DEBRIS: f32 = 0.6;
+const SURFACE: f32 = 0.95;
+
+/// The strength of the reverberation following an echo, relative to its peak.
+const TAIL: f32 = 0.2;
+
+/// A piece of debris in a [SyntheticPipe], modelled as a circle in the cross-section.
+#[derive(Debug, Copy, Clone, PartialEq, derive_new::new)]
+pub struct Debris {
+    /// The horizontal position of its center from the pipe center in meters, positive to the
+    /// right.
+    pub x: f32,
+
+    /// The vertical position of its center from the pipe center in meters, positive upwards.
+    pub y: f32,
+
+    /// Its radius in meters.
+    pub radius: f32,
+}
+
+/// The cross-section of a pipe a [Simulator] is placed in.
+///
+/// Positions are in meters from the pipe center, with `x` to the right and `y` upwards, so that
+/// a head position of 0° points up and angles grow clockwise as in the
+/// [Geometry](crate::Geometry) of a [Transducer::Up](crate::types::Transducer::Up) head.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntheticPipe {
+    /// The nominal inside diameter in meters.
+    pub diameter: f32,
+
+    /// The difference of the widest and narrowest diameters over the nominal diameter. An oval
+    /// pipe is widest horizontally, as a pipe squashed by the ground above it.
+    pub ovality: f32,
+
+    /// The horizontal position of the head in meters.
+    pub head_x: f32,
+
+    /// The vertical position of the head in meters.
+    pub head_y: f32,
+
+    pub debris: Vec<Debris>,
+
+    /// The height of the water surface in meters, or `None` for a full pipe. A head above the
+    /// water receives no echoes.
+    pub water_level: Option<f32>,
+
+    /// The standard deviation of the noise added to every sample, as a fraction of full scale.
+    pub noise: f32,
+
+    /// The speed of sound in the water in m/s. The head assumes 1500 m/s, so any other velocity
+    /// misplaces the echoes as in a real pipe.
+    pub sound_velocity: f32,
+}
+
+impl Default for SyntheticPipe {
+    fn default() -> Self {
+        Self {
+            diameter: 0.6,
+            ovality: 0.0,
+            head_x: 0.0,
+            head_y: 0.0,
+            debris: Vec::new(),
+            water_level: None,
+            noise: 0.02,
+            sound_velocity: V_VALUE,
+        }
+    }
+}
+
+impl SyntheticPipe {
+    /// The distance to the first surface along a beam at `angle` degrees and its reflectivity,
+    /// or `None` if the beam never reaches one.
+    fn cast(&self, angle: f32) -> Option<(f32, f32)> {
+        let (x, y) = (self.head_x, self.head_y);
+        if self.water_level.is_some_and(|level| y >= level) {
+            return None;
+        }
+
+        let (dx, dy) = angle.to_radians().sin_cos();
+        let mut hits = Vec::with_capacity(self.debris.len() + 2);
+
+        let a = self.diameter / 2.0 * (1.0 + self.ovality / 2.0);
+        let b = self.diameter / 2.0 * (1.0 - self.ovality / 2.0);
+        let wall = nearest_root(
+            (dx / a).powi(2) + (dy / b).powi(2),
+            2.0 * (x * dx / a.powi(2) + y * dy / b.powi(2)),
+            (x / a).powi(2) + (y / b).powi(2) - 1.0,
+        );
+        hits.push(wall.map(|distance| (distance, WALL)));
+
+        for debris in &self.debris {
+            let (ox, oy) = (x - debris.x, y - debris.y);
+            let distance = nearest_root(
+                1.0,
+                2.0 * (ox * dx + oy * dy),
+                ox.powi(2) + oy.powi(2) - debris.radius.powi(2),
+            );
+            hits.push(distance.map(|distance| (distance, DEBRIS)));
+        }
+
+        if let Some(level) = self.water_level.filter(|_| dy > 0.0) {
+            hits.push(Some(((level - y) / dy, SURFACE)));
+        }
+
+        hits.into_iter().flatten().min_by(|a, b| a.0.total_cmp(&b.0))
+    }
+}
+
+/// The smallest positive root of `a·t² + b·t + c`, if any.
+fn nearest_root(a: f32, b: f32, c: f32) -> Option<f32> {
+    let discriminant = b * b - 4.0 * a * c;
+    if a == 0.0 || discriminant < 0.0 {
+        return None;
+    }
+
+    let root = discriminant.sqrt();
+    [(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)].into_iter().find(|&t| t > 0.0)
+}
+
+/// A xorshift generator, so that the noise of a [Simulator] repeats for its seed.
+#[derive(Debug, Clone)]
+struct Noise(u64);
+
+impl Noise {
+    fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    /// A uniform sample within `0.0..1.0`.
+    fn uniform(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// A standard normal sample, by the Box-Muller transform.
+    fn gaussian(&mut self) -> f32 {
+        let radius = (-2.0 * self.uniform().max(f32::EPSILON).ln()).sqrt();
+        radius * (std::f32::consts::TAU * self.uniform()).cos()
+    }
+}
+
+/// A software sonar head in a [SyntheticPipe], answering [SwitchData] with a [SonarReturn] as
+/// an 831L would.
+///
+/// Each switch data steps the head by its [StepSize](crate::types::StepSize) before the echo is
+/// acquired: continuously in the [StepDirection] for a 360° sector ([Mode::Polar]), or back and
+/// forth across the sector about the train angle otherwise ([Mode::Sector]). As in a real head,
+/// the reported [HeadPosition] is the one reached by the step, half a step past the beam.
+///
+/// The simulator is a [Transport] itself for use in-process, and [Simulator::serve] answers
+/// over any byte stream, such as a pseudo-terminal or TCP connection.
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    sonar_type: SonarType,
+    pipe: SyntheticPipe,
+    position: i32,
+    direction: Direction,
+    mode: Mode,
+    noise: Noise,
+    input: Vec<u8>,
+    output: Vec<u8>,
+}
+
+impl Simulator {
+    /// The seed of the noise by default.
+    pub const DEFAULT_SEED: u64 = 831;
+
+    /// Create a head of `sonar_type` at 0° in `pipe`.
+    pub fn new(sonar_type: SonarType, pipe: SyntheticPipe) -> Self {
+        Self {
+            sonar_type,
+            pipe,
+            position: 0,
+            direction: Direction::Clockwise,
+            mode: Mode::Polar,
+            noise: Noise::new(Self::DEFAULT_SEED),
+            input: Vec::new(),
+            output: Vec::new(),
+        }
+    }
+
+    /// Seed the noise with `seed`.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.noise = Noise::new(seed);
+        self
+    }
+
+    #[inline]
+    pub fn sonar_type(&self) -> SonarType {
+        self.sonar_type
+    }
+
+    #[inline]
+    pub fn pipe(&self) -> &SyntheticPipe {
+        &self.pipe
+    }
+
+    #[inline]
+    pub fn pipe_mut(&mut self) -> &mut SyntheticPipe {
+        &mut self.pipe
+    }
+
+    /// The [Mode] of the last [SwitchData].
+    #[inline]
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// The current position of the head.
+    pub fn head_position(&self) -> HeadPosition {
+        HeadPosition::new(self.position as f32 * TICK, self.direction)
+    }
+
+    /// Step the head for `switch_data` and return the [SonarReturn] it acquires.
+    pub fn respond(&mut self, switch_data: &SwitchData) -> SonarReturn {
+        let step = (switch_data.step_size.degrees() / TICK).round() as i32;
+        let angle = match switch_data.sonar_type {
+            SonarType::Scanning => {
+                self.step(switch_data, step);
+                let half_step = step as f32 * TICK / 2.0;
+                match self.direction {
+                    Direction::Clockwise => self.head_position().angle - half_step,
+                    Direction::Counterclockwise => self.head_position().angle + half_step,
+                }
+            },
+            SonarType::FixedPosition => {
+                self.position = 0;
+                0.0
+            },
+        };
+
+        let (data, profile_range) = self.echo(switch_data, angle);
+        let header = SonarReturnHeader::new(
+            SonarReturnMagic::IMX,
+            switch_data.sonar_type,
+            SonarReturnStatus { switches_accepted: true, ..Default::default() },
+            self.head_position(),
+            switch_data.range_index,
+            profile_range,
+            0,
+            Angle::from(0.0f32),
+            Angle::from(0.0f32),
+            Acceleration::from(0.0f32),
+            Acceleration::from(0.0f32),
+        );
+        SonarReturn::new(header, data)
+    }
+
+    /// Answer the switch data received on `stream` until it is closed, returning the number of
+    /// sonar returns sent. Bytes that do not form valid switch data are skipped.
+    pub fn serve<S: Read + Write>(&mut self, mut stream: S) -> Result<usize> {
+        let mut chunk = [0u8; 64];
+        let mut answered = 0;
+        let mut unflushed = None;
+
+        loop {
+            let result = stream.read(&mut chunk);
+            match &result {
+                Ok(0) => return Ok(answered),
+                Err(e) if hung_up(e.kind()) => return Ok(answered),
+                _ => {},
+            }
+
+            // a pseudo-terminal fails to drain once the client has hung up, so a failed flush
+            // is only an error if the client is still there
+            if let Some(error) = unflushed.take() {
+                return Err(error);
+            }
+
+            let received = match result {
+                Ok(received) => received,
+                Err(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock) => {
+                    continue
+                },
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            for frame in self.answer(&chunk[..received])? {
+                stream.write_all(&frame)?;
+                answered += 1;
+            }
+            unflushed = stream.flush().err().map(Error::from);
+        }
+    }
+
+    /// Step the head `step` positions within the sector of `switch_data`.
+    fn step(&mut self, switch_data: &SwitchData, step: i32) {
+        if switch_data.motor_calibrate == MotorCalibrate::Calibrate {
+            self.position = 0;
+        }
+
+        let start = match switch_data.command.step_direction {
+            StepDirection::Normal => Direction::Clockwise,
+            StepDirection::Reverse => Direction::Counterclockwise,
+        };
+
+        if switch_data.sector_size >= sector_size::MAX {
+            self.mode = Mode::Polar;
+            self.direction = start;
+            self.position = wrap(self.position + signed(step, start));
+            return;
+        }
+
+        self.mode = Mode::Sector;
+        let center = wrap((switch_data.train_angle as f32 / TICK).round() as i32);
+        let half = (switch_data.sector_size as f32 / TICK / 2.0).round() as i32;
+        let offset = wrap(self.position - center);
+
+        let offset = match offset.abs() > half {
+            // start a sweep from the edge of a new sector
+            true => {
+                self.direction = start;
+                signed(-half, start)
+            },
+            false => {
+                if (offset + signed(step, self.direction)).abs() > half {
+                    self.direction = reverse(self.direction);
+                }
+                (offset + signed(step, self.direction)).clamp(-half, half)
+            },
+        };
+        self.position = wrap(center + offset);
+    }
+
+    /// The packed echo data along a beam at `angle` degrees and its profile range.
+    ///
+    /// A beam that hits nothing, or hits a surface too close to time past the filter delay,
+    /// reports the [undetected](profile_range::undetected) profile range the head encodes as 0.
+    fn echo(&mut self, switch_data: &SwitchData, angle: f32) -> (Vec<u8>, f32) {
+        let velocity = match self.pipe.sound_velocity > 0.0 {
+            true => self.pipe.sound_velocity,
+            false => V_VALUE,
+        };
+        let range_code = switch_data.range_index;
+        let points = switch_data.data_points.points();
+
+        let pulse = velocity * switch_data.pulse_length as f32 * 1e-6 / 2.0;
+        let width = (pulse / 2.0).max(range_code.range() / points as f32);
+        let hit = self.pipe.cast(angle);
+
+        let full_scale = ((1u32 << switch_data.data_bits.bits()) - 1) as f32;
+        let samples: Vec<u16> = range_code
+            .ranges(velocity)
+            .iter()
+            .take(points)
+            .map(|&range| {
+                let amplitude = match hit {
+                    Some((distance, reflectivity)) => {
+                        let peak = (-0.5 * ((range - distance) / width).powi(2)).exp();
+                        let tail = match range > distance {
+                            true => TAIL * (-(range - distance) / (4.0 * width)).exp(),
+                            false => 0.0,
+                        };
+                        reflectivity * peak.max(tail)
+                    },
+                    None => 0.0,
+                };
+                let amplitude = amplitude + self.pipe.noise * self.noise.gaussian();
+                (amplitude.clamp(0.0, 1.0) * full_scale).round() as u16
+            })
+            .collect();
+
+        // the head times the echo assuming 1500 m/s
+        let profile_range = hit
+            .map(|(distance, _)| match switch_data.command.profile_point_detection {
+                ProfilePointDetection::CenterOfPulse => distance,
+                ProfilePointDetection::StartOfPulse => distance - pulse / 2.0,
+            })
+            .map(|distance| distance * V_VALUE / velocity)
+            .filter(|&range| switch_data.profile_min_range <= range && range <= range_code.range())
+            .filter(|&range| profile_range::detected(range, range_code))
+            .unwrap_or(profile_range::undetected(range_code));
+
+        (pack(&samples, switch_data.data_bits), profile_range)
+    }
+
+    /// Take the switch data in `bytes` after any received before, returning the encoded sonar
+    /// return for each complete one.
+    fn answer(&mut self, bytes: &[u8]) -> Result<Vec<Vec<u8>>> {
+        self.input.extend_from_slice(bytes);
+        let mut frames = Vec::new();
+
+        loop {
+            match self.input.windows(MAGIC.len()).position(|window| window == MAGIC) {
+                Some(start) => drop(self.input.drain(..start)),
+                None => {
+                    // keep a partial magic at the end
+                    let keep = usize::from(self.input.last() == Some(&MAGIC[0]));
+                    self.input.drain(..self.input.len() - keep);
+                    return Ok(frames);
+                },
+            }
+
+            if self.input.len() < SwitchData::LENGTH {
+                return Ok(frames);
+            }
+
+            let mut cursor = Cursor::new(&self.input[..SwitchData::LENGTH]);
+            match SwitchData::read_args(&mut cursor, (self.sonar_type,)) {
+                Ok(switch_data) => {
+                    self.input.drain(..SwitchData::LENGTH);
+                    let sonar_return = self.respond(&switch_data);
+                    let mut cursor = Cursor::new(Vec::new());
+                    sonar_return
+                        .write_options(&mut cursor, ENDIAN, ())
+                        .map_err(|e| Error::encode(e, 0))?;
+                    frames.push(cursor.into_inner());
+                },
+                Err(error) => {
+                    log::warn!("Skipping invalid switch data: {error}");
+                    self.input.drain(..1);
+                },
+            }
+        }
+    }
+}
+
+impl Transport for Simulator {
+    fn send(&mut self, bytes: &[u8]) -> Result<()> {
+        for frame in self.answer(bytes)? {
+            self.output.extend_from_slice(&frame);
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self, buffer: &mut [u8], timeout: Duration) -> Result<usize> {
+        if self.output.is_empty() {
+            std::thread::sleep(timeout);
+            return Ok(0);
+        }
+
+        let length = buffer.len().min(self.output.len());
+        for (byte, output) in buffer.iter_mut().zip(self.output.drain(..length)) {
+            *byte = output;
+        }
+        Ok(length)
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.output.clear();
+        Ok(())
+    }
+}
+
+/// Whether reading failed because the client hung up.
+fn hung_up(kind: ErrorKind) -> bool {
+    matches!(
+        kind,
+        ErrorKind::BrokenPipe
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::UnexpectedEof
+    )
+}
+
+/// Wrap a head position into `-180.0..180.0` degrees.
+fn wrap(position: i32) -> i32 {
+    (position + HALF_TURN).rem_euclid(2 * HALF_TURN) - HALF_TURN
+}
+
+/// `steps` in `direction`, clockwise being positive.
+fn signed(steps: i32, direction: Direction) -> i32 {
+    match direction {
+        Direction::Clockwise => steps,
+        Direction::Counterclockwise => -steps,
+    }
+}
+
+fn reverse(direction: Direction) -> Direction {
+    match direction {
+        Direction::Clockwise => Direction::Counterclockwise,
+        Direction::Counterclockwise => Direction::Clockwise,
+    }
+}
+
+/// Pack `samples` as the echo data of `data_bits`, the reverse of
+/// [Echo::decode](crate::Echo::decode).
+fn pack(samples: &[u16], data_bits: DataBits) -> Vec<u8> {
+    match data_bits {
+        DataBits::X4Bits => samples
+            .chunks(2)
+            .map(|pair| ((pair[0] << 4) | pair.get(1).copied().unwrap_or(0)) as u8)
+            .collect(),
+        DataBits::X8Bits => samples.iter().map(|&sample| sample as u8).collect(),
+        DataBits::X14Bits => samples.iter().flat_map(|&sample| u14::to_bytes(sample)).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{RangeCode, StepSize, Transducer, Zero};
+    use crate::{DecodeMode, Echo, Geometry, Sonar};
+
+    use log::info;
+    use test_log::test;
+
+    fn quiet(pipe: SyntheticPipe) -> SyntheticPipe {
+        SyntheticPipe { noise: 0.0, ..pipe }
+    }
+
+    fn decode(sonar_return: &SonarReturn) -> SonarReturn {
+        let mut cursor = Cursor::new(Vec::new());
+        sonar_return.write_options(&mut cursor, ENDIAN, ()).expect("It should not return an error");
+        cursor.set_position(0);
+        SonarReturn::read_options(&mut cursor, ENDIAN, (DecodeMode::Strict,))
+            .expect("It should not return an error")
+    }
+
+    fn switch_data() -> SwitchData {
+        SwitchData::builder(SonarType::Scanning).build().expect("It should not return an error")
+    }
+
+    #[test]
+    fn step_polar() {
+        let cases = vec![
+            (StepSize::Fast, StepDirection::Normal, 0.9f32, Direction::Clockwise),
+            (StepSize::Fastest, StepDirection::Normal, 2.4, Direction::Clockwise),
+            (StepSize::Slow, StepDirection::Reverse, -0.3, Direction::Counterclockwise),
+        ];
+
+        for (step_size, step_direction, step, direction) in cases {
+            info!("Stepping {step_size:?} {step_direction:?}, expecting {step}° {direction:?}");
+            let mut simulator = Simulator::new(SonarType::Scanning, SyntheticPipe::default());
+            let switch_data = SwitchData::builder(SonarType::Scanning)
+                .step_size(step_size)
+                .step_direction(step_direction)
+                .build()
+                .expect("It should not return an error");
+
+            let turn = (360.0 / step.abs()).round() as usize;
+            for i in 1..=turn {
+                let head_position = simulator.respond(&switch_data).header().head_position.clone();
+                let want = (i as f32 * step + 180.0).rem_euclid(360.0) - 180.0;
+                assert!((want - head_position.angle).abs() < 1e-3, "{want} != {head_position}");
+                assert_eq!(direction, head_position.direction);
+            }
+            assert_eq!(Mode::Polar, simulator.mode());
+            assert!(simulator.head_position().angle.abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn step_sector() {
+        let cases = vec![(0, 90, StepDirection::Normal), (180, 60, StepDirection::Reverse)];
+
+        for (train_angle, sector_size, step_direction) in cases {
+            info!("Sweeping {sector_size}° about {train_angle}° {step_direction:?}");
+            let mut simulator = Simulator::new(SonarType::Scanning, SyntheticPipe::default());
+            let switch_data = SwitchData::builder(SonarType::Scanning)
+                .train_angle(train_angle)
+                .sector_size(sector_size)
+                .step_direction(step_direction)
+                .build()
+                .expect("It should not return an error");
+
+            let mut reversals = 0;
+            let mut last = None;
+            for _ in 0..500 {
+                let head_position = simulator.respond(&switch_data).header().head_position.clone();
+                let offset =
+                    (head_position.angle - train_angle as f32 + 540.0).rem_euclid(360.0) - 180.0;
+                assert!(offset.abs() <= sector_size as f32 / 2.0 + 1e-3, "{head_position}");
+
+                if last.is_some_and(|last| last != head_position.direction) {
+                    reversals += 1;
+                }
+                last = Some(head_position.direction);
+            }
+            assert_eq!(Mode::Sector, simulator.mode());
+            assert!(reversals >= 4, "{reversals} reversals");
+        }
+    }
+
+    #[test]
+    fn profile() {
+        let cases = vec![
+            (quiet(SyntheticPipe { diameter: 1.0, ..Default::default() }), 0.0, 0.5),
+            (quiet(SyntheticPipe { diameter: 1.0, ..Default::default() }), 90.0, 0.5),
+            (quiet(SyntheticPipe { diameter: 1.0, ovality: 0.2, ..Default::default() }), 0.0, 0.45),
+            (
+                quiet(SyntheticPipe { diameter: 1.0, ovality: 0.2, ..Default::default() }),
+                -90.0,
+                0.55,
+            ),
+            (quiet(SyntheticPipe { diameter: 1.0, head_y: -0.2, ..Default::default() }), 0.0, 0.7),
+            (
+                quiet(SyntheticPipe {
+                    diameter: 1.0,
+                    debris: vec![Debris::new(0.0, -0.4, 0.1)],
+                    ..Default::default()
+                }),
+                180.0,
+                0.3,
+            ),
+            (
+                quiet(SyntheticPipe {
+                    diameter: 1.0,
+                    water_level: Some(0.25),
+                    ..Default::default()
+                }),
+                0.0,
+                0.25,
+            ),
+            (
+                quiet(SyntheticPipe {
+                    diameter: 1.0,
+                    sound_velocity: 1400.0,
+                    ..Default::default()
+                }),
+                0.0,
+                0.5 * 1500.0 / 1400.0,
+            ),
+        ];
+
+        let geometry = Geometry::new(Transducer::Up, Zero::Up, StepSize::Slow.degrees());
+        for (pipe, angle, want) in cases {
+            info!("Profiling {pipe:?} at {angle}°, expecting {want} m");
+            let mut simulator = Simulator::new(SonarType::Scanning, pipe);
+            let switch_data = SwitchData::builder(SonarType::Scanning)
+                .step_size(StepSize::Slow)
+                .build()
+                .expect("It should not return an error");
+
+            let sonar_return = (0..1200)
+                .map(|_| simulator.respond(&switch_data))
+                .find(|sonar_return| {
+                    let angle_got = geometry.angle(&sonar_return.header().head_position);
+                    ((angle_got - angle + 540.0).rem_euclid(360.0) - 180.0).abs() < 0.2
+                })
+                .expect("It should reach the angle");
+
+            let got = sonar_return.header().profile_range;
+            assert!((want - got).abs() < 0.01, "{want} != {got}");
+
+            let echo = Echo::decode(sonar_return.data(), DataBits::X8Bits, Default::default());
+            let ranges = RangeCode::X1m.ranges(V_VALUE);
+            let peak = (0..echo.len()).max_by_key(|&i| echo.raw()[i]).unwrap();
+            assert!((want - ranges[peak]).abs() < 0.02, "{want} != {}", ranges[peak]);
+        }
+    }
+
+    #[test]
+    fn profile_missing() {
+        let cases = vec![
+            (SyntheticPipe { diameter: 3.0, ..Default::default() }, true),
+            (SyntheticPipe { water_level: Some(-0.1), ..Default::default() }, true),
+            // a wall inside the filter delay echoes but cannot be timed
+            (SyntheticPipe { diameter: 0.02, ..Default::default() }, false),
+        ];
+
+        for (pipe, silent) in cases {
+            info!("Profiling {pipe:?}, expecting no profile point");
+            let mut simulator = Simulator::new(SonarType::Scanning, quiet(pipe));
+            let sonar_return = simulator.respond(&switch_data());
+            assert_eq!(silent, sonar_return.data().iter().all(|&sample| sample == 0));
+
+            let decoded = decode(&sonar_return);
+            assert_eq!(sonar_return, decoded);
+            assert!(!decoded.header().profile_detected());
+        }
+    }
+
+    #[test]
+    fn data_bits() {
+        let cases =
+            vec![(DataBits::X4Bits, 125), (DataBits::X8Bits, 250), (DataBits::X14Bits, 500)];
+
+        for (data_bits, want) in cases {
+            info!("Responding with {data_bits:?}, expecting {want} bytes");
+            let mut simulator = Simulator::new(SonarType::Scanning, SyntheticPipe::default());
+            let switch_data = SwitchData::builder(SonarType::Scanning)
+                .data_bits(data_bits)
+                .build()
+                .expect("It should not return an error");
+
+            let sonar_return = simulator.respond(&switch_data);
+            assert_eq!(want, sonar_return.data().len());
+            assert_eq!(want as u16, sonar_return.header().data_length);
+
+            let echo = Echo::decode(sonar_return.data(), data_bits, Default::default());
+            assert_eq!(250, echo.len());
+            let peak = (0..echo.len()).max_by_key(|&i| echo.raw()[i]).unwrap();
+            let range = RangeCode::X1m.ranges(V_VALUE)[peak];
+            assert!((0.3 - range).abs() < 0.02, "{range}");
+        }
+    }
+
+    #[test]
+    fn noise() {
+        let pipe = SyntheticPipe { noise: 0.1, ..Default::default() };
+        let respond = |seed| {
+            let mut simulator = Simulator::new(SonarType::Scanning, pipe.clone()).with_seed(seed);
+            simulator.respond(&switch_data())
+        };
+
+        assert_eq!(respond(1), respond(1));
+        assert_ne!(respond(1).data(), respond(2).data());
+        assert_ne!(
+            respond(1).data(),
+            Simulator::new(SonarType::Scanning, quiet(pipe.clone())).respond(&switch_data()).data()
+        );
+    }
+
+    #[test]
+    fn fixed_position() {
+        let mut simulator = Simulator::new(SonarType::FixedPosition, quiet(Default::default()));
+        let switch_data = SwitchData::builder(SonarType::FixedPosition)
+            .build()
+            .expect("It should not return an error");
+
+        for _ in 0..3 {
+            let sonar_return = simulator.respond(&switch_data);
+            assert_eq!(SonarType::FixedPosition, sonar_return.header().sonar_type);
+            assert_eq!(0.0, sonar_return.header().head_position.angle);
+            assert!((0.3 - sonar_return.header().profile_range).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn in_process() {
+        let simulator = Simulator::new(SonarType::Scanning, SyntheticPipe::default());
+        let mut sonar = Sonar::new(simulator);
+
+        let want: Vec<_> = {
+            let mut simulator = Simulator::new(SonarType::Scanning, SyntheticPipe::default());
+            (0..3).map(|_| simulator.respond(&switch_data())).collect()
+        };
+        for want in want {
+            let got = sonar.ping(&switch_data()).expect("It should not return an error");
+            assert_eq!(want, got);
+            assert!(got.validate().is_empty());
+        }
+    }
+
+    #[test]
+    fn answer_noisy() {
+        let mut bytes = Cursor::new(Vec::new());
+        switch_data().write(&mut bytes).expect("It should not return an error");
+        let command = bytes.into_inner();
+
+        let mut garbled = command.clone();
+        garbled[SwitchData::LENGTH - 1] = 0;
+
+        let mut stream = vec![0x00, 0xFE];
+        stream.extend_from_slice(&garbled);
+        stream.extend_from_slice(&command);
+        stream.extend_from_slice(&command[..1]);
+
+        let cases = vec![1, 7, stream.len()];
+        for chunk in cases {
+            info!("Answering {} bytes received {chunk} at a time", stream.len());
+            let mut simulator = Simulator::new(SonarType::Scanning, SyntheticPipe::default());
+            let answered: usize = stream
+                .chunks(chunk)
+                .map(|bytes| simulator.answer(bytes).expect("It should not return an error").len())
+                .sum();
+            assert_eq!(1, answered);
+            assert_eq!(vec![MAGIC[0]], simulator.input);
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn serve_pty() {
+        use crate::SerialTransport;
+        use serialport::{SerialPort, TTYPort};
+
+        let (master, mut slave) = TTYPort::pair().expect("It should not return an error");
+        slave.set_timeout(Duration::from_millis(100)).expect("It should not return an error");
+        let head = std::thread::spawn(move || {
+            let mut simulator = Simulator::new(SonarType::Scanning, SyntheticPipe::default());
+            simulator.serve(slave).expect("It should not return an error")
+        });
+
+        let transport = SerialTransport::from_port(Box::new(master));
+        let mut sonar = Sonar::new(transport).with_timeout(Duration::from_secs(2));
+        for _ in 0..5 {
+            let got = sonar.ping(&switch_data()).expect("It should not return an error");
+            assert!(got.header().profile_detected());
+        }
+
+        drop(sonar);
+        assert_eq!(5, head.join().expect("It should not panic"));
+    }
+
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn serve_tcp() {
+        use crate::{NetworkOptions, TcpTransport};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("It should not return an error");
+        let port = listener.local_addr().expect("It should not return an error").port();
+        let head = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().expect("It should not return an error");
+            let mut simulator = Simulator::new(SonarType::Scanning, SyntheticPipe::default());
+            simulator.serve(stream).expect("It should not return an error")
+        });
+
+        let transport = TcpTransport::connect("127.0.0.1", port, &NetworkOptions::default())
+            .expect("It should not return an error");
+        let mut sonar = Sonar::new(transport).with_timeout(Duration::from_secs(2));
+
+        let mut want = Simulator::new(SonarType::Scanning, SyntheticPipe::default());
+        for data_bits in [DataBits::X4Bits, DataBits::X8Bits, DataBits::X14Bits] {
+            let switch_data = SwitchData::builder(SonarType::Scanning)
+                .data_bits(data_bits)
+                .build()
+                .expect("It should not return an error");
+            let got = sonar.ping(&switch_data).expect("It should not return an error");
+            assert_eq!(want.respond(&switch_data), got);
+        }
+
+        drop(sonar);
+        assert_eq!(3, head.join().expect("It should not panic"));
+    }
+}
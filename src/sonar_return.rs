@@ -1,12 +1,14 @@
+use crate::error::ErrorKind;
 use crate::types::SonarReturnHeader;
+use crate::{validation::Warning, DecodeMode};
 use binrw::{BinRead, BinWrite};
 
 #[cfg(feature = "pyo3")]
 use pyo3::prelude::*;
 
 #[derive(Debug, BinRead, BinWrite, PartialEq, Clone)]
-#[br(assert(termination_byte == 0xFC))]
-#[bw(assert(* termination_byte == 0xFC))]
+#[br(import(mode: DecodeMode), assert(termination_byte == Self::TERMINATION_BYTE || mode.is_lenient()))]
+#[bw(assert(* termination_byte == Self::TERMINATION_BYTE))]
 #[cfg_attr(feature = "pyo3", pyclass(eq))]
 pub struct SonarReturn {
     #[cfg(not(feature = "pyo3"))]
@@ -32,3 +34,56 @@ pub struct SonarReturn {
     #[pyo3(get, set)]
     termination_byte: u8,
 }
+
+impl SonarReturn {
+    /// The byte terminating every sonar return.
+    pub const TERMINATION_BYTE: u8 = 0xFC;
+
+    /// Create a sonar return of `data` under `header`, setting the
+    /// [data_length](SonarReturnHeader::data_length) to match.
+    pub fn new(mut header: SonarReturnHeader, data: Vec<u8>) -> Self {
+        header.data_length = data.len() as u16;
+        Self { header, data, termination_byte: Self::TERMINATION_BYTE }
+    }
+
+    #[inline]
+    pub fn header(&self) -> &SonarReturnHeader {
+        &self.header
+    }
+
+    #[inline]
+    pub fn header_mut(&mut self) -> &mut SonarReturnHeader {
+        &mut self.header
+    }
+
+    /// The raw echo data.
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// The termination byte, always [SonarReturn::TERMINATION_BYTE] unless decoded in [DecodeMode::Lenient].
+    #[inline]
+    pub fn termination_byte(&self) -> u8 {
+        self.termination_byte
+    }
+
+    /// Check the [HeadPosition](crate::types::HeadPosition) and termination byte, returning a
+    /// [Warning] for each out of specification.
+    pub fn validate(&self) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+
+        let head_position = &self.header.head_position;
+        if !head_position.valid() {
+            let error = head_position.out_of_range();
+            warnings.push(Warning::out_of_range("head_position", error).within("header"));
+        }
+
+        if self.termination_byte != Self::TERMINATION_BYTE {
+            let message = format!("invalid termination byte {:#04X}", self.termination_byte);
+            warnings.push(Warning::new("termination_byte", ErrorKind::InvalidData, message));
+        }
+
+        warnings
+    }
+}
@@ -0,0 +1,448 @@
+//! Grouping of consecutive shots into sweeps of the sonar head.
+use crate::types::{Direction, Mode};
+use crate::Shot;
+use std::fmt::{Display, Formatter};
+
+#[cfg(feature = "pyo3")]
+use pyo3::prelude::*;
+
+/// A jump between consecutive head positions of more than this many steps ends a sweep.
+const MAX_GAP_STEPS: f32 = 2.5;
+
+/// How completely a [Sweep] covers its [Sweep::extent].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(
+    target_family = "wasm",
+    derive(tsify::Tsify, serde::Serialize, serde::Deserialize),
+    tsify(into_wasm_abi, from_wasm_abi)
+)]
+#[cfg_attr(
+    all(feature = "serde", not(target_family = "wasm")),
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "pyo3", pyclass(eq, eq_int))]
+pub enum SweepStatus {
+    /// The shots cover the extent to within half a step.
+    Complete,
+
+    /// The sweep ended early, by a reversal, a gap, a change of settings or the end of the data.
+    Incomplete,
+
+    /// The shots cover more than a step beyond the extent.
+    Overlapping,
+}
+
+impl Display for SweepStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                Self::Complete => "complete",
+                Self::Incomplete => "incomplete",
+                Self::Overlapping => "overlapping",
+            }
+        )
+    }
+}
+
+/// Consecutive shots forming one rotation in [Mode::Polar] or one pass in [Mode::Sector].
+///
+/// Shots in [Mode::Sidescan] do not move the head, so each forms a sweep of its own.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "pyo3", pyclass(eq))]
+pub struct Sweep {
+    shots: Vec<Shot>,
+    mode: Mode,
+    direction: Direction,
+    step: f32,
+    extent: f32,
+    travel: f32,
+    status: SweepStatus,
+}
+
+impl Sweep {
+    #[inline]
+    pub fn shots(&self) -> &[Shot] {
+        &self.shots
+    }
+
+    #[inline]
+    pub fn into_shots(self) -> Vec<Shot> {
+        self.shots
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.shots.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.shots.is_empty()
+    }
+
+    #[inline]
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// The direction the head stepped in during the sweep.
+    #[inline]
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// The size of each step of the head, in degrees.
+    #[inline]
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    /// The angle a complete sweep covers: 360° in [Mode::Polar] or the sector size in
+    /// [Mode::Sector], in degrees.
+    #[inline]
+    pub fn extent(&self) -> f32 {
+        self.extent
+    }
+
+    /// The angle covered by the shots, one step for each shot, in degrees.
+    #[inline]
+    pub fn coverage(&self) -> f32 {
+        self.travel + self.step
+    }
+
+    #[inline]
+    pub fn status(&self) -> SweepStatus {
+        self.status
+    }
+
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.status == SweepStatus::Complete
+    }
+
+    /// The head angle of the first shot, in degrees.
+    pub fn start_angle(&self) -> f32 {
+        self.shots.first().map(head_angle).unwrap_or_default()
+    }
+
+    /// The head angle of the last shot, in degrees.
+    pub fn end_angle(&self) -> f32 {
+        self.shots.last().map(head_angle).unwrap_or_default()
+    }
+}
+
+/// Assembles [Sweep]s from shots pushed in recording order.
+///
+/// A sweep ends when it covers its extent, when the head reverses, when consecutive head
+/// positions jump by more than a few steps, or when the mode, step size or sector size change.
+#[derive(Debug, Default)]
+pub struct SweepAssembler {
+    current: Option<Sweep>,
+}
+
+impl SweepAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the next shot, returning the sweep it completes or interrupts, if any.
+    pub fn push(&mut self, shot: Shot) -> Option<Sweep> {
+        let Some(sweep) = self.current.as_mut() else {
+            self.start(shot);
+            return None;
+        };
+
+        let last = sweep.shots.last().expect("a sweep always holds a shot");
+        let delta = wrap(head_angle(&shot) - head_angle(last));
+        let direction = shot.sonar_return.header().head_position.direction;
+
+        let continues = settings(&shot) == (sweep.mode, sweep.step, sweep.extent)
+            && sweep.mode != Mode::Sidescan
+            && direction == sweep.direction
+            && delta * sign(direction) >= 0.0
+            && delta.abs() <= MAX_GAP_STEPS * sweep.step;
+
+        if !continues {
+            let finished = self.finish();
+            self.start(shot);
+            return finished;
+        }
+
+        sweep.travel += delta.abs();
+        sweep.shots.push(shot);
+
+        match sweep.mode == Mode::Polar && sweep.coverage() >= sweep.extent - sweep.step / 2.0 {
+            true => self.finish(),
+            false => None,
+        }
+    }
+
+    /// End the sweep in progress, returning it if there is one.
+    pub fn finish(&mut self) -> Option<Sweep> {
+        let mut sweep = self.current.take()?;
+        let coverage = sweep.coverage();
+
+        sweep.status = if coverage > sweep.extent + sweep.step {
+            SweepStatus::Overlapping
+        } else if coverage >= sweep.extent - sweep.step / 2.0 {
+            SweepStatus::Complete
+        } else {
+            SweepStatus::Incomplete
+        };
+
+        if sweep.status != SweepStatus::Complete {
+            log::debug!(
+                "{} sweep of {} shots covering {coverage}° of {}°",
+                sweep.status,
+                sweep.len(),
+                sweep.extent
+            );
+        }
+
+        Some(sweep)
+    }
+
+    fn start(&mut self, shot: Shot) {
+        let (mode, step, extent) = settings(&shot);
+        let direction = shot.sonar_return.header().head_position.direction;
+
+        self.current = Some(Sweep {
+            shots: vec![shot],
+            mode,
+            direction,
+            step,
+            extent,
+            travel: 0.0,
+            status: SweepStatus::Incomplete,
+        });
+    }
+}
+
+/// An iterator over the [Sweep]s of an iterator of shots, such as a [crate::Reader].
+pub struct Sweeps<I> {
+    shots: I,
+    assembler: SweepAssembler,
+}
+
+impl<I: Iterator<Item = Shot>> Sweeps<I> {
+    pub fn new<T: IntoIterator<IntoIter = I>>(shots: T) -> Self {
+        Self { shots: shots.into_iter(), assembler: SweepAssembler::new() }
+    }
+}
+
+impl<I: Iterator<Item = Shot>> Iterator for Sweeps<I> {
+    type Item = Sweep;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for shot in self.shots.by_ref() {
+            if let Some(sweep) = self.assembler.push(shot) {
+                return Some(sweep);
+            }
+        }
+
+        self.assembler.finish()
+    }
+}
+
+#[inline]
+fn head_angle(shot: &Shot) -> f32 {
+    shot.sonar_return.header().head_position.angle
+}
+
+/// The mode, step size and extent of the sweep `shot` belongs to.
+fn settings(shot: &Shot) -> (Mode, f32, f32) {
+    let motion = shot.header.motion();
+    let extent = match motion.mode {
+        Mode::Sector => shot.header.sector_size() as f32,
+        Mode::Polar | Mode::Sidescan => 360.0,
+    };
+
+    (motion.mode, motion.step_size.degrees(), extent)
+}
+
+#[inline]
+fn sign(direction: Direction) -> f32 {
+    match direction {
+        Direction::Clockwise => 1.0,
+        Direction::Counterclockwise => -1.0,
+    }
+}
+
+/// Wrap an angle difference into `-180.0..180.0`.
+#[inline]
+fn wrap(delta: f32) -> f32 {
+    (delta + 180.0).rem_euclid(360.0) - 180.0
+}
+
+#[cfg(feature = "pyo3")]
+#[pymethods]
+impl SweepStatus {
+    pub(crate) fn __str__(&self) -> String {
+        self.to_string()
+    }
+}
+
+#[cfg(feature = "pyo3")]
+#[pymethods]
+impl Sweep {
+    #[getter(shots)]
+    pub(crate) fn py_shots(&self) -> Vec<Shot> {
+        self.shots.clone()
+    }
+
+    #[getter(mode)]
+    pub(crate) fn py_mode(&self) -> Mode {
+        self.mode
+    }
+
+    #[getter(direction)]
+    pub(crate) fn py_direction(&self) -> Direction {
+        self.direction
+    }
+
+    #[getter(status)]
+    pub(crate) fn py_status(&self) -> SweepStatus {
+        self.status
+    }
+
+    #[getter(extent)]
+    pub(crate) fn py_extent(&self) -> f32 {
+        self.extent
+    }
+
+    #[getter(coverage)]
+    pub(crate) fn py_coverage(&self) -> f32 {
+        self.coverage()
+    }
+
+    pub(crate) fn __len__(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::shot_bytes;
+    use crate::types::{HeadPosition, StepSize};
+    use binrw::BinRead;
+    use std::io::Cursor;
+
+    use log::info;
+    use test_log::test;
+
+    fn shot(mode: Mode, angle: f32, direction: Direction) -> Shot {
+        let mut bytes = shot_bytes();
+        bytes[39] = 30; // sector size: 90°
+
+        let mut shot = Shot::read(&mut Cursor::new(bytes)).expect("It should not return an error");
+        let motion = shot.header.motion_mut();
+        motion.mode = mode;
+        motion.step_size = StepSize::Slow;
+        shot.sonar_return.header_mut().head_position = HeadPosition::new(angle, direction);
+        shot
+    }
+
+    /// Shots stepping 0.3° from `start` for `count` steps in `direction`.
+    fn stepping(mode: Mode, start: f32, count: usize, direction: Direction) -> Vec<Shot> {
+        (0..count)
+            .map(|i| start + sign(direction) * 0.3 * i as f32)
+            .map(|angle| shot(mode, wrap(angle), direction))
+            .collect()
+    }
+
+    fn summary(shots: Vec<Shot>) -> Vec<(usize, SweepStatus)> {
+        let sweeps: Vec<_> = Sweeps::new(shots).collect();
+        for sweep in &sweeps {
+            info!(
+                "Sweep of {} shots from {}° to {}°, {}",
+                sweep.len(),
+                sweep.start_angle(),
+                sweep.end_angle(),
+                sweep.status()
+            );
+        }
+        sweeps.iter().map(|sweep| (sweep.len(), sweep.status())).collect()
+    }
+
+    #[test]
+    fn polar() {
+        let shots = stepping(Mode::Polar, 170.0, 2 * 1200 + 100, Direction::Clockwise);
+        let want = vec![
+            (1200, SweepStatus::Complete),
+            (1200, SweepStatus::Complete),
+            (100, SweepStatus::Incomplete),
+        ];
+        assert_eq!(want, summary(shots));
+    }
+
+    #[test]
+    fn polar_reversal() {
+        let mut shots = stepping(Mode::Polar, 0.0, 500, Direction::Counterclockwise);
+        shots.extend(stepping(Mode::Polar, -149.7, 1200, Direction::Clockwise));
+        let want = vec![(500, SweepStatus::Incomplete), (1200, SweepStatus::Complete)];
+        assert_eq!(want, summary(shots));
+    }
+
+    #[test]
+    fn polar_gap() {
+        let mut shots = stepping(Mode::Polar, 0.0, 600, Direction::Clockwise);
+        shots.extend(stepping(Mode::Polar, 200.0, 600, Direction::Clockwise));
+        let want = vec![(600, SweepStatus::Incomplete), (600, SweepStatus::Incomplete)];
+        assert_eq!(want, summary(shots));
+    }
+
+    #[test]
+    fn sector() {
+        let mut shots = stepping(Mode::Sector, -45.0, 300, Direction::Clockwise);
+        shots.extend(stepping(Mode::Sector, 44.7, 300, Direction::Counterclockwise));
+        shots.extend(stepping(Mode::Sector, -44.7, 100, Direction::Clockwise));
+        let want = vec![
+            (300, SweepStatus::Complete),
+            (300, SweepStatus::Complete),
+            (100, SweepStatus::Incomplete),
+        ];
+        assert_eq!(want, summary(shots));
+
+        let sweep =
+            Sweeps::new(stepping(Mode::Sector, -45.0, 300, Direction::Clockwise)).next().unwrap();
+        assert_eq!(Direction::Clockwise, sweep.direction());
+        assert_eq!(90.0, sweep.extent());
+        assert!((90.0 - sweep.coverage()).abs() < 0.01);
+    }
+
+    #[test]
+    fn sector_overlapping() {
+        let shots = stepping(Mode::Sector, -45.0, 320, Direction::Clockwise);
+        assert_eq!(vec![(320, SweepStatus::Overlapping)], summary(shots));
+    }
+
+    #[test]
+    fn mode_change() {
+        let mut shots = stepping(Mode::Sector, -45.0, 100, Direction::Clockwise);
+        shots.extend(stepping(Mode::Polar, -15.0, 100, Direction::Clockwise));
+        shots.push(shot(Mode::Sidescan, 0.0, Direction::Clockwise));
+        shots.push(shot(Mode::Sidescan, 0.0, Direction::Clockwise));
+        let want = vec![
+            (100, SweepStatus::Incomplete),
+            (100, SweepStatus::Incomplete),
+            (1, SweepStatus::Incomplete),
+            (1, SweepStatus::Incomplete),
+        ];
+        assert_eq!(want, summary(shots));
+    }
+
+    #[test]
+    fn wrap_delta() {
+        let cases = vec![(0.3, 0.3), (-0.3, -0.3), (359.7, -0.3), (-359.7, 0.3), (180.0, -180.0)];
+
+        for (delta, want) in cases {
+            info!("Wrapping {delta}°, want {want}°");
+            let got = wrap(delta);
+            assert!((want - got).abs() < 1e-4, "got {got}");
+        }
+    }
+}
@@ -1,43 +1,794 @@
-use crate::types::{Command, RangeCode};
-use binrw::{BinRead, BinWrite};
+use crate::types::util::primitive::{read_u8, write_u8};
+use crate::types::{
+    primitive::{
+        absorption, frequency, profile_min_range, pulse_length, sector_size, start_gain,
+        train_angle,
+    },
+    Command, DataBits, DataPoints, FileHeader, Mode, MotorCalibrate, ProfilePointDetection,
+    RangeCode, SonarType, StepDirection, StepSize,
+};
+use crate::{DecodeMode, Error, Result, Shot};
+use binrw::{parser, writer, BinRead, BinResult, BinWrite};
 
 #[cfg(feature = "pyo3")]
 use pyo3::prelude::*;
 
-#[derive(Debug, BinRead, BinWrite, Eq, PartialEq, derive_new::new)]
-#[brw(magic = b"\xFE\x44")]
+/// The command sent to the sonar head to configure and trigger a [SonarReturn](crate::SonarReturn).
+///
+/// The [SonarType] is not part of the message, so it must be given to decode the
+/// [SwitchData::frequency] with [BinRead::read_args].
+#[derive(Debug, BinRead, BinWrite, PartialEq, Clone)]
+#[brw(big, magic = b"\xFE\x44")]
+#[br(import(sonar_type: SonarType), assert(termination_byte == Self::TERMINATION_BYTE))]
+#[bw(assert(* termination_byte == Self::TERMINATION_BYTE))]
 #[cfg_attr(feature = "pyo3", pyclass(eq))]
 pub struct SwitchData {
     #[cfg(not(feature = "pyo3"))]
-    #[brw(pad_before = 1)]
+    #[br(calc = sonar_type)]
+    #[bw(ignore)]
+    pub sonar_type: SonarType,
+
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    #[br(calc = sonar_type)]
+    #[bw(ignore)]
+    pub sonar_type: SonarType,
+
+    /// The address of the sonar head on the bus.
+    #[cfg(not(feature = "pyo3"))]
+    pub head_id: u8,
+
+    /// The address of the sonar head on the bus.
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    pub head_id: u8,
+
+    #[cfg(not(feature = "pyo3"))]
+    #[brw(pad_after = 1)]
+    pub range_index: RangeCode,
+
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    #[brw(pad_after = 1)]
+    pub range_index: RangeCode,
+
+    #[cfg(not(feature = "pyo3"))]
+    #[brw(pad_after = 2)]
+    pub command: Command,
+
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    #[brw(pad_after = 2)]
+    pub command: Command,
+
+    #[cfg(not(feature = "pyo3"))]
+    #[br(parse_with = start_gain::parse, args(DecodeMode::Strict))]
+    #[bw(write_with = start_gain::write)]
+    #[brw(pad_after = 1)]
+    pub start_gain: u8,
+
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    #[br(parse_with = start_gain::parse, args(DecodeMode::Strict))]
+    #[bw(write_with = start_gain::write)]
+    #[brw(pad_after = 1)]
+    pub start_gain: u8,
+
+    #[cfg(not(feature = "pyo3"))]
+    #[br(parse_with = absorption::parse)]
+    #[bw(write_with = absorption::write)]
+    pub absorption: f32,
+
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    #[br(parse_with = absorption::parse)]
+    #[bw(write_with = absorption::write)]
+    pub absorption: f32,
+
+    #[cfg(not(feature = "pyo3"))]
+    #[br(parse_with = train_angle::parse, args(DecodeMode::Strict))]
+    #[bw(write_with = train_angle::write)]
+    pub train_angle: u16,
+
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    #[br(parse_with = train_angle::parse, args(DecodeMode::Strict))]
+    #[bw(write_with = train_angle::write)]
+    pub train_angle: u16,
+
+    #[cfg(not(feature = "pyo3"))]
+    #[br(parse_with = sector_size::parse, args(DecodeMode::Strict))]
+    #[bw(write_with = sector_size::write)]
+    pub sector_size: u16,
+
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    #[br(parse_with = sector_size::parse, args(DecodeMode::Strict))]
+    #[bw(write_with = sector_size::write)]
+    pub sector_size: u16,
+
+    #[cfg(not(feature = "pyo3"))]
+    #[br(parse_with = parse_step_size)]
+    #[bw(write_with = write_step_size)]
+    pub step_size: StepSize,
+
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    #[br(parse_with = parse_step_size)]
+    #[bw(write_with = write_step_size)]
+    pub step_size: StepSize,
+
+    #[cfg(not(feature = "pyo3"))]
+    #[br(parse_with = pulse_length::parse, args(DecodeMode::Strict))]
+    #[bw(write_with = pulse_length::write)]
+    pub pulse_length: u16,
+
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    #[br(parse_with = pulse_length::parse, args(DecodeMode::Strict))]
+    #[bw(write_with = pulse_length::write)]
+    pub pulse_length: u16,
+
+    #[cfg(not(feature = "pyo3"))]
+    #[br(parse_with = profile_min_range::parse)]
+    #[bw(write_with = profile_min_range::write)]
+    #[brw(pad_after = 3)]
+    pub profile_min_range: f32,
+
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    #[br(parse_with = profile_min_range::parse)]
+    #[bw(write_with = profile_min_range::write)]
+    #[brw(pad_after = 3)]
+    pub profile_min_range: f32,
+
+    #[cfg(not(feature = "pyo3"))]
+    #[br(parse_with = parse_data_points)]
+    #[bw(write_with = write_data_points)]
+    pub data_points: DataPoints,
+
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    #[br(parse_with = parse_data_points)]
+    #[bw(write_with = write_data_points)]
+    pub data_points: DataPoints,
+
+    #[cfg(not(feature = "pyo3"))]
+    #[br(parse_with = parse_data_bits)]
+    #[bw(write_with = write_data_bits)]
+    #[brw(pad_after = 1)]
+    pub data_bits: DataBits,
+
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    #[br(parse_with = parse_data_bits)]
+    #[bw(write_with = write_data_bits)]
+    #[brw(pad_after = 1)]
+    pub data_bits: DataBits,
+
+    #[cfg(not(feature = "pyo3"))]
+    #[br(parse_with = parse_motor_calibrate)]
+    #[bw(write_with = write_motor_calibrate)]
     #[brw(pad_after = 1)]
-    range_index: RangeCode,
+    pub motor_calibrate: MotorCalibrate,
 
     #[cfg(feature = "pyo3")]
     #[pyo3(get, set)]
-    #[brw(pad_before = 1)]
+    #[br(parse_with = parse_motor_calibrate)]
+    #[bw(write_with = write_motor_calibrate)]
     #[brw(pad_after = 1)]
-    range_index: RangeCode,
+    pub motor_calibrate: MotorCalibrate,
 
+    /// The operating frequency in MHz.
     #[cfg(not(feature = "pyo3"))]
+    #[br(parse_with = frequency::parse, args(sonar_type))]
+    #[bw(write_with = frequency::write, args(*sonar_type))]
     #[brw(pad_after = 1)]
-    command: Command,
+    pub frequency: f32,
 
+    /// The operating frequency in MHz.
     #[cfg(feature = "pyo3")]
     #[pyo3(get, set)]
+    #[br(parse_with = frequency::parse, args(sonar_type))]
+    #[bw(write_with = frequency::write, args(*sonar_type))]
     #[brw(pad_after = 1)]
-    command: Command,
+    pub frequency: f32,
+
+    #[cfg(not(feature = "pyo3"))]
+    termination_byte: u8,
+
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get)]
+    termination_byte: u8,
+}
+
+impl SwitchData {
+    /// The length of the encoded message in bytes.
+    pub const LENGTH: usize = 27;
+
+    /// The [SwitchData::head_id] of a single sonar head.
+    pub const HEAD_ID: u8 = 0x10;
+
+    /// The byte terminating every switch data message.
+    pub const TERMINATION_BYTE: u8 = 0xFD;
+
+    /// Create the switch data for a [SonarType] at `range_index` with `command`, with the other
+    /// settings at their defaults:
+    ///
+    /// | Setting           | Scanning  | Fixed position |
+    /// |-------------------|-----------|----------------|
+    /// | Start gain        | 3 dB      | 3 dB           |
+    /// | Absorption        | 0.6 dB/m  | 0.2 dB/m       |
+    /// | Train angle       | 0°        | 0°             |
+    /// | Sector size       | 360°      | 0°             |
+    /// | Step size         | Fast      | Fast           |
+    /// | Pulse length      | 20 μs     | 20 μs          |
+    /// | Profile min range | 0         | 0              |
+    /// | Data points       | 250       | 250            |
+    /// | Data bits         | 8         | 8              |
+    /// | Frequency         | 2.25 MHz  | 1.00 MHz       |
+    pub fn new(sonar_type: SonarType, range_index: RangeCode, command: Command) -> Self {
+        let (absorption, sector_size, frequency) = match sonar_type {
+            SonarType::Scanning => (0.6, sector_size::MAX, 2.25),
+            SonarType::FixedPosition => (0.2, 0, 1.0),
+        };
+
+        Self {
+            sonar_type,
+            head_id: Self::HEAD_ID,
+            range_index,
+            command,
+            start_gain: 3,
+            absorption,
+            train_angle: 0,
+            sector_size,
+            step_size: StepSize::Fast,
+            pulse_length: 20,
+            profile_min_range: 0.0,
+            data_points: DataPoints::default(),
+            data_bits: DataBits::X8Bits,
+            motor_calibrate: MotorCalibrate::default(),
+            frequency,
+            termination_byte: Self::TERMINATION_BYTE,
+        }
+    }
+
+    /// Start a [SwitchDataBuilder] from the defaults of [SwitchData::new] at 1 meter.
+    #[inline]
+    pub fn builder(sonar_type: SonarType) -> SwitchDataBuilder {
+        SwitchDataBuilder::new(sonar_type)
+    }
+
+    /// The termination byte, always [SwitchData::TERMINATION_BYTE].
+    #[inline]
+    pub fn termination_byte(&self) -> u8 {
+        self.termination_byte
+    }
+
+    /// Check that every setting is in range, can be encoded without loss and is supported by the
+    /// [SonarType].
+    pub fn validate(&self) -> Result<()> {
+        if !start_gain::valid(self.start_gain) {
+            return Err(start_gain::out_of_range(self.start_gain).into());
+        }
+        if !absorption::valid(self.absorption) {
+            return Err(absorption::out_of_range(self.absorption).into());
+        }
+        if !train_angle::valid(self.train_angle) {
+            return Err(train_angle::out_of_range(self.train_angle).into());
+        }
+        if !sector_size::valid(self.sector_size) {
+            return Err(sector_size::out_of_range(self.sector_size).into());
+        }
+        if !pulse_length::valid(self.pulse_length) {
+            return Err(pulse_length::out_of_range(self.pulse_length).into());
+        }
+        if !profile_min_range::valid(self.profile_min_range) {
+            return Err(profile_min_range::out_of_range(self.profile_min_range).into());
+        }
+        if !frequency::valid_for(self.sonar_type, self.frequency) {
+            return Err(frequency::out_of_range(self.sonar_type, self.frequency).into());
+        }
+
+        let increments = [
+            ("Train angle", self.train_angle, 3, "°"),
+            ("Sector size", self.sector_size, 3, "°"),
+            ("Pulse length", self.pulse_length, 10, " μs"),
+        ];
+        for (name, value, increment, unit) in increments {
+            if value % increment != 0 {
+                let message =
+                    format!("{name} {value}{unit} is not a multiple of {increment}{unit}");
+                return Err(Error::invalid_data(message));
+            }
+        }
+        if !absorption::on_increment(self.absorption) {
+            let message =
+                format!("Absorption {} dB/m is not a multiple of 0.01 dB/m", self.absorption);
+            return Err(Error::invalid_data(message));
+        }
+        if !frequency::on_increment_for(self.sonar_type, self.frequency) {
+            let message = format!("Frequency {} MHz is not on a 5 kHz increment", self.frequency);
+            return Err(Error::invalid_data(message));
+        }
+
+        match self.sonar_type {
+            SonarType::Scanning if self.sector_size == 0 => {
+                Err(Error::invalid_data("Scanning sonars need a sector size".to_string()))
+            },
+            SonarType::FixedPosition if self.sector_size != 0 || self.train_angle != 0 => {
+                let message = "Fixed position sonars do not support sector settings".to_string();
+                Err(Error::invalid_data(message))
+            },
+            SonarType::FixedPosition if self.motor_calibrate == MotorCalibrate::Calibrate => {
+                let message = "Fixed position sonars have no motor to calibrate".to_string();
+                Err(Error::invalid_data(message))
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A validated way to construct [SwitchData], starting from the defaults of [SwitchData::new].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwitchDataBuilder {
+    switch_data: SwitchData,
+}
+
+impl SwitchDataBuilder {
+    /// Start from the defaults of [SwitchData::new] at 1 meter, detecting profile points at the
+    /// center of the pulse and stepping in the normal direction.
+    pub fn new(sonar_type: SonarType) -> Self {
+        let command = Command::new(ProfilePointDetection::CenterOfPulse, StepDirection::Normal);
+        Self { switch_data: SwitchData::new(sonar_type, RangeCode::X1m, command) }
+    }
+
+    /// Start from the settings `header` was recorded with by a sonar of `sonar_type`.
+    ///
+    /// The profile point detection, step direction and profile minimum range are not recorded
+    /// and keep their defaults, as does a recorded frequency outside of the band of the
+    /// [SonarType] or off its 5 kHz increments. Heads often record such a value (see
+    /// [FileHeader::operating_frequency]), so the frequency of their surveys is not recoverable.
+    /// The sector settings of fixed position sonars are ignored.
+    pub fn from_file_header(sonar_type: SonarType, header: &FileHeader) -> Self {
+        let mut builder = Self::new(sonar_type)
+            .range_index(header.range_code())
+            .start_gain(header.start_gain())
+            .absorption(header.absorption())
+            .step_size(header.motion().step_size)
+            .pulse_length(header.pulse_length())
+            .data_points(header.data_size_index())
+            .data_bits(header.config().data_bits);
+
+        if sonar_type == SonarType::Scanning {
+            let sector_size = match header.motion().mode {
+                Mode::Polar => sector_size::MAX,
+                _ => header.sector_size(),
+            };
+            builder = builder.train_angle(header.train_angle()).sector_size(sector_size);
+        }
+
+        let frequency = header.operating_frequency() as f32 / 1000.0;
+        match frequency::valid_for(sonar_type, frequency)
+            && frequency::on_increment_for(sonar_type, frequency)
+        {
+            true => builder.frequency(frequency),
+            false => builder,
+        }
+    }
+
+    /// Start from the settings `shot` was recorded with, see [SwitchDataBuilder::from_file_header].
+    #[inline]
+    pub fn from_shot(shot: &Shot) -> Self {
+        Self::from_file_header(shot.sonar_return.header().sonar_type, &shot.header)
+    }
+
+    pub fn head_id(mut self, head_id: u8) -> Self {
+        self.switch_data.head_id = head_id;
+        self
+    }
+
+    pub fn range_index(mut self, range_index: RangeCode) -> Self {
+        self.switch_data.range_index = range_index;
+        self
+    }
+
+    pub fn command(mut self, command: Command) -> Self {
+        self.switch_data.command = command;
+        self
+    }
+
+    pub fn profile_point_detection(mut self, detection: ProfilePointDetection) -> Self {
+        self.switch_data.command.profile_point_detection = detection;
+        self
+    }
+
+    pub fn step_direction(mut self, step_direction: StepDirection) -> Self {
+        self.switch_data.command.step_direction = step_direction;
+        self
+    }
+
+    /// The gain applied at the start of the echo, in dB.
+    pub fn start_gain(mut self, start_gain: u8) -> Self {
+        self.switch_data.start_gain = start_gain;
+        self
+    }
+
+    /// The absorption of sound in water, in dB/m.
+    pub fn absorption(mut self, absorption: f32) -> Self {
+        self.switch_data.absorption = absorption;
+        self
+    }
+
+    /// The angle the sector is centered on, in multiples of 3°.
+    pub fn train_angle(mut self, train_angle: u16) -> Self {
+        self.switch_data.train_angle = train_angle;
+        self
+    }
+
+    /// The size of the sector scanned, in multiples of 3°.
+    pub fn sector_size(mut self, sector_size: u16) -> Self {
+        self.switch_data.sector_size = sector_size;
+        self
+    }
+
+    pub fn step_size(mut self, step_size: StepSize) -> Self {
+        self.switch_data.step_size = step_size;
+        self
+    }
+
+    /// The length of the transmitted pulse, in multiples of 10 μs.
+    pub fn pulse_length(mut self, pulse_length: u16) -> Self {
+        self.switch_data.pulse_length = pulse_length;
+        self
+    }
+
+    pub fn profile_min_range(mut self, profile_min_range: f32) -> Self {
+        self.switch_data.profile_min_range = profile_min_range;
+        self
+    }
+
+    pub fn data_points(mut self, data_points: DataPoints) -> Self {
+        self.switch_data.data_points = data_points;
+        self
+    }
+
+    pub fn data_bits(mut self, data_bits: DataBits) -> Self {
+        self.switch_data.data_bits = data_bits;
+        self
+    }
+
+    pub fn motor_calibrate(mut self, motor_calibrate: MotorCalibrate) -> Self {
+        self.switch_data.motor_calibrate = motor_calibrate;
+        self
+    }
+
+    /// The operating frequency, in MHz.
+    pub fn frequency(mut self, frequency: f32) -> Self {
+        self.switch_data.frequency = frequency;
+        self
+    }
+
+    /// The [SwitchData], if it passes [SwitchData::validate].
+    pub fn build(self) -> Result<SwitchData> {
+        self.switch_data.validate()?;
+        Ok(self.switch_data)
+    }
+}
+
+#[parser(reader)]
+fn parse_step_size() -> BinResult<StepSize> {
+    let raw = u8::read(reader)?;
+    read_u8(raw, reader.stream_position()?)
+}
+
+#[writer(writer)]
+fn write_step_size(step_size: &StepSize) -> BinResult<()> {
+    write_u8(*step_size, writer.stream_position()?)?.write(writer)
+}
+
+#[parser(reader)]
+fn parse_motor_calibrate() -> BinResult<MotorCalibrate> {
+    let raw = u8::read(reader)?;
+    read_u8(raw, reader.stream_position()?)
+}
+
+#[writer(writer)]
+fn write_motor_calibrate(motor_calibrate: &MotorCalibrate) -> BinResult<()> {
+    write_u8(*motor_calibrate, writer.stream_position()?)?.write(writer)
+}
+
+/// Parse the [DataPoints] from a byte of the points in tens.
+#[parser(reader)]
+fn parse_data_points() -> BinResult<DataPoints> {
+    let raw = u8::read(reader)?;
+    match raw as usize * 10 {
+        points if points == DataPoints::X250Points.points() => Ok(DataPoints::X250Points),
+        _ => Err(binrw::Error::AssertFail {
+            pos: reader.stream_position()?,
+            message: format!("invalid data points {raw}"),
+        }),
+    }
+}
+
+/// Write the [DataPoints] to a byte of the points in tens.
+#[writer(writer)]
+fn write_data_points(data_points: &DataPoints) -> BinResult<()> {
+    ((data_points.points() / 10) as u8).write(writer)
+}
+
+/// Parse the [DataBits] from a byte of the bits per sample.
+#[parser(reader)]
+fn parse_data_bits() -> BinResult<DataBits> {
+    let raw = u8::read(reader)?;
+    [DataBits::X4Bits, DataBits::X8Bits, DataBits::X14Bits]
+        .into_iter()
+        .find(|data_bits| data_bits.bits() == raw)
+        .ok_or(binrw::Error::AssertFail {
+            pos: reader.stream_position()?,
+            message: format!("invalid data bits {raw}"),
+        })
+}
+
+/// Write the [DataBits] to a byte of the bits per sample.
+#[writer(writer)]
+fn write_data_bits(data_bits: &DataBits) -> BinResult<()> {
+    data_bits.bits().write(writer)
 }
 
 #[cfg(feature = "pyo3")]
 #[pymethods]
 impl SwitchData {
     #[new]
-    fn py_new(range_index: RangeCode, command: Command) -> Self {
-        Self::new(range_index, command)
+    fn py_new(sonar_type: SonarType, range_index: RangeCode, command: Command) -> Self {
+        Self::new(sonar_type, range_index, command)
     }
 
     pub fn __repr__(&self) -> String {
-        format!("SwitchData(range_index = {:?}, command = {:?})", self.range_index, self.command)
+        format!(
+            "SwitchData(sonar_type = {:?}, range_index = {:?}, command = {:?})",
+            self.sonar_type, self.range_index, self.command
+        )
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "from_file_header")]
+    fn py_from_file_header(sonar_type: SonarType, header: &FileHeader) -> PyResult<Self> {
+        Ok(SwitchDataBuilder::from_file_header(sonar_type, header).build()?)
+    }
+
+    #[pyo3(name = "validate")]
+    fn py_validate(&self) -> PyResult<()> {
+        Ok(self.validate()?)
+    }
+
+    pub fn __bytes__(&self) -> PyResult<Vec<u8>> {
+        let mut buffer = std::io::Cursor::new(Vec::with_capacity(Self::LENGTH));
+        self.write(&mut buffer).map_err(crate::Error::from)?;
+        Ok(buffer.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::shot_bytes;
+    use crate::ErrorKind;
+    use std::io::Cursor;
+
+    use log::info;
+    use test_log::test;
+
+    fn command() -> Command {
+        Command::new(ProfilePointDetection::CenterOfPulse, StepDirection::Reverse)
+    }
+
+    #[rustfmt::skip]
+    const SCANNING: [u8; SwitchData::LENGTH] = [
+        0xFE, 0x44, 0x10, 10, 0, 0x40, 0, 0, 3, 0, 60, 0, 120, 2, 2, 0,
+        0, 0, 0, 25, 8, 0, 0, 0, 100, 0, 0xFD,
+    ];
+
+    fn encode(switch_data: &SwitchData) -> Vec<u8> {
+        let mut buffer = Cursor::new(Vec::new());
+        switch_data.write(&mut buffer).expect("It should not return an error");
+        buffer.into_inner()
+    }
+
+    #[test]
+    fn write() {
+        let switch_data = SwitchData::new(SonarType::Scanning, RangeCode::X1m, command());
+        assert_eq!(SCANNING.to_vec(), encode(&switch_data));
+    }
+
+    #[test]
+    fn read() {
+        let got = SwitchData::read_args(&mut Cursor::new(SCANNING), (SonarType::Scanning,))
+            .expect("It should not return an error");
+        let want = SwitchData::new(SonarType::Scanning, RangeCode::X1m, command());
+        assert_eq!(want, got);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut cases = Vec::new();
+        for sonar_type in [SonarType::Scanning, SonarType::FixedPosition] {
+            let mut switch_data = SwitchData::new(sonar_type, RangeCode::X0_75m, command());
+            cases.push(switch_data.clone());
+
+            switch_data.start_gain = 40;
+            switch_data.train_angle = 180;
+            switch_data.step_size = StepSize::Fastest;
+            switch_data.pulse_length = 1000;
+            switch_data.profile_min_range = 250.0;
+            switch_data.data_bits = DataBits::X14Bits;
+            switch_data.motor_calibrate = MotorCalibrate::Calibrate;
+            switch_data.frequency += 0.05;
+            cases.push(switch_data);
+        }
+
+        for want in cases {
+            info!("Round-tripping {want:?}");
+            let bytes = encode(&want);
+            assert_eq!(SwitchData::LENGTH, bytes.len());
+
+            let got = SwitchData::read_args(&mut Cursor::new(bytes), (want.sonar_type,))
+                .expect("It should not return an error");
+            assert!((want.frequency - got.frequency).abs() < 1e-6);
+            assert_eq!(SwitchData { frequency: want.frequency, ..got }, want);
+        }
+    }
+
+    #[test]
+    fn read_invalid() {
+        let cases = vec![
+            (0, 0xFF, "magic"),
+            (3, 11, "range index"),
+            (8, 41, "start gain"),
+            (13, 5, "step size"),
+            (19, 50, "data points"),
+            (20, 16, "data bits"),
+            (22, 2, "motor calibrate"),
+            (24, 121, "frequency"),
+            (26, 0xFC, "termination byte"),
+        ];
+
+        for (offset, value, field) in cases {
+            info!("Reading an invalid {field} of {value} at byte {offset}");
+            let mut bytes = SCANNING;
+            bytes[offset] = value;
+            let got = SwitchData::read_args(&mut Cursor::new(bytes), (SonarType::Scanning,));
+            assert!(got.is_err());
+        }
+    }
+
+    #[test]
+    fn build() {
+        let got = SwitchData::builder(SonarType::Scanning)
+            .range_index(RangeCode::X2m)
+            .start_gain(10)
+            .sector_size(90)
+            .train_angle(45)
+            .step_direction(StepDirection::Reverse)
+            .frequency(2.3)
+            .build()
+            .expect("It should not return an error");
+
+        let mut want = SwitchData::new(SonarType::Scanning, RangeCode::X2m, command());
+        want.start_gain = 10;
+        want.sector_size = 90;
+        want.train_angle = 45;
+        want.frequency = 2.3;
+        assert_eq!(want, got);
+
+        for sonar_type in [SonarType::Scanning, SonarType::FixedPosition] {
+            info!("Building the defaults for {sonar_type}");
+            SwitchData::builder(sonar_type).build().expect("It should not return an error");
+        }
+    }
+
+    #[test]
+    fn build_invalid() {
+        let scanning = || SwitchData::builder(SonarType::Scanning);
+        let fixed = || SwitchData::builder(SonarType::FixedPosition);
+        let (out_of_range, invalid_data) = (true, false);
+
+        let cases = vec![
+            ("start gain", scanning().start_gain(41), out_of_range),
+            ("absorption", scanning().absorption(2.6), out_of_range),
+            ("train angle", scanning().train_angle(363), out_of_range),
+            ("sector size", scanning().sector_size(363), out_of_range),
+            ("pulse length", scanning().pulse_length(1010), out_of_range),
+            ("profile minimum range", scanning().profile_min_range(251.0), out_of_range),
+            ("frequency", scanning().frequency(1.0), out_of_range),
+            ("frequency", fixed().frequency(2.25), out_of_range),
+            ("train angle increment", scanning().train_angle(10), invalid_data),
+            ("sector size increment", scanning().sector_size(100), invalid_data),
+            ("pulse length increment", scanning().pulse_length(25), invalid_data),
+            ("absorption increment", scanning().absorption(0.605), invalid_data),
+            ("frequency increment", scanning().frequency(2.252), invalid_data),
+            ("frequency increment", fixed().frequency(1.001), invalid_data),
+            ("scanning without sector", scanning().sector_size(0), invalid_data),
+            ("fixed with sector", fixed().sector_size(90), invalid_data),
+            ("fixed with train angle", fixed().train_angle(90), invalid_data),
+            ("fixed calibrating", fixed().motor_calibrate(MotorCalibrate::Calibrate), invalid_data),
+        ];
+
+        for (name, builder, want_out_of_range) in cases {
+            info!("Building with an invalid {name}");
+            let error = builder.build().expect_err("It should return an error");
+            match want_out_of_range {
+                true => assert!(matches!(error.kind, ErrorKind::OutOfRange { .. })),
+                false => assert_eq!(ErrorKind::InvalidData, error.kind),
+            }
+        }
+    }
+
+    #[test]
+    fn from_file_header() {
+        let shot =
+            Shot::read(&mut Cursor::new(shot_bytes())).expect("It should not return an error");
+        let got =
+            SwitchDataBuilder::from_shot(&shot).build().expect("It should not return an error");
+
+        assert_eq!(SonarType::Scanning, got.sonar_type);
+        assert_eq!(RangeCode::X1m, got.range_index);
+        assert_eq!(6, got.start_gain);
+        assert_eq!(1.7, got.absorption);
+        assert_eq!(0, got.train_angle);
+        assert_eq!(360, got.sector_size);
+        assert_eq!(StepSize::Fast, got.step_size);
+        assert_eq!(100, got.pulse_length);
+        assert_eq!(DataBits::X8Bits, got.data_bits);
+
+        // the recorded 8 is not a frequency in the band, so the default is kept
+        assert_eq!(8, shot.header.operating_frequency());
+        let default = SwitchData::builder(SonarType::Scanning)
+            .build()
+            .expect("It should not return an error");
+        assert_eq!(default.frequency, got.frequency);
+
+        let got = SwitchDataBuilder::from_file_header(SonarType::FixedPosition, &shot.header)
+            .build()
+            .expect("It should not return an error");
+        assert_eq!((0, 0), (got.sector_size, got.train_angle));
+        assert_eq!(1.0, got.frequency);
+    }
+
+    #[test]
+    fn from_file_header_frequency() {
+        const OPERATING_FREQUENCY_OFFSET: usize = 79;
+
+        let cases = vec![
+            (SonarType::Scanning, 2300u16, 2.3),
+            (SonarType::Scanning, 2150, 2.15),
+            (SonarType::Scanning, 2252, 2.25),
+            (SonarType::Scanning, 1050, 2.25),
+            (SonarType::FixedPosition, 1050, 1.05),
+            (SonarType::FixedPosition, 2300, 1.0),
+        ];
+
+        for (sonar_type, recorded, want) in cases {
+            info!("Starting from {recorded} kHz recorded by a {sonar_type} sonar, want {want} MHz");
+            let mut bytes = shot_bytes();
+            bytes[OPERATING_FREQUENCY_OFFSET..OPERATING_FREQUENCY_OFFSET + 2]
+                .copy_from_slice(&recorded.to_be_bytes());
+            let shot = Shot::read(&mut Cursor::new(bytes)).expect("It should not return an error");
+            assert_eq!(recorded, shot.header.operating_frequency());
+
+            let got = SwitchDataBuilder::from_file_header(sonar_type, &shot.header)
+                .build()
+                .expect("It should not return an error");
+            assert_eq!(want, got.frequency);
+        }
+    }
+
+    #[test]
+    fn write_invalid() {
+        let mut switch_data = SwitchData::new(SonarType::FixedPosition, RangeCode::X1m, command());
+        switch_data.frequency = 2.25;
+
+        let mut buffer = Cursor::new(Vec::new());
+        assert!(switch_data.write(&mut buffer).is_err());
     }
 }
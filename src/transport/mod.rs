@@ -0,0 +1,314 @@
+//! Live communication with IMAGENEX 831L sonar heads.
+#[cfg(not(target_family = "wasm"))]
+mod net;
+#[cfg(not(target_family = "wasm"))]
+mod serial;
+
+#[cfg(not(target_family = "wasm"))]
+pub use net::{NetworkOptions, TcpTransport, UdpTransport};
+#[cfg(not(target_family = "wasm"))]
+pub use serial::SerialTransport;
+
+use crate::types::SonarReturnHeader;
+use crate::{DecodeMode, Error, ErrorKind, Result, SonarReturn, SwitchData, ENDIAN};
+use binrw::{BinRead, BinWrite};
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+/// The magic bytes a [SonarReturn] can begin with.
+const MAGICS: [&[u8; 3]; 2] = [b"IMX", b"IPX"];
+
+/// The length of the [SonarReturnHeader] in bytes.
+const HEADER_LENGTH: usize = 32;
+
+/// A byte link to a sonar head, such as a serial port or network socket.
+pub trait Transport {
+    /// Send all of `bytes` to the sonar head.
+    fn send(&mut self, bytes: &[u8]) -> Result<()>;
+
+    /// Receive up to `buffer.len()` bytes, waiting at most `timeout` for the first of them.
+    ///
+    /// Returns the number of bytes received, or zero if none arrived in time.
+    fn receive(&mut self, buffer: &mut [u8], timeout: Duration) -> Result<usize>;
+
+    /// Discard any bytes received but not yet read.
+    fn clear(&mut self) -> Result<()>;
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    #[inline]
+    fn send(&mut self, bytes: &[u8]) -> Result<()> {
+        (**self).send(bytes)
+    }
+
+    #[inline]
+    fn receive(&mut self, buffer: &mut [u8], timeout: Duration) -> Result<usize> {
+        (**self).receive(buffer, timeout)
+    }
+
+    #[inline]
+    fn clear(&mut self) -> Result<()> {
+        (**self).clear()
+    }
+}
+
+/// A sonar head over a [Transport], commanded with [SwitchData] and answering with a
+/// [SonarReturn].
+#[derive(Debug)]
+pub struct Sonar<T: Transport> {
+    transport: T,
+    timeout: Duration,
+    retries: u32,
+    buffer: Vec<u8>,
+}
+
+impl<T: Transport> Sonar<T> {
+    /// How long to wait for a [SonarReturn] by default.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(500);
+
+    /// How many times to repeat an unanswered [SwitchData] by default.
+    pub const DEFAULT_RETRIES: u32 = 2;
+
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            timeout: Self::DEFAULT_TIMEOUT,
+            retries: Self::DEFAULT_RETRIES,
+            buffer: Vec::with_capacity(FRAME_CAPACITY),
+        }
+    }
+
+    /// Wait at most `timeout` for each [SonarReturn].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Repeat an unanswered or garbled [SwitchData] up to `retries` times.
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    #[inline]
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    #[inline]
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    #[inline]
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    #[inline]
+    pub fn transport_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+
+    /// Encode and send `switch_data` without waiting for the answer.
+    pub fn send(&mut self, switch_data: &SwitchData) -> Result<()> {
+        let mut cursor = Cursor::new(Vec::with_capacity(SwitchData::LENGTH));
+        switch_data.write(&mut cursor).map_err(|e| Error::encode(e, 0))?;
+        self.transport.send(cursor.get_ref())
+    }
+
+    /// Receive the next [SonarReturn], skipping any bytes before its magic.
+    pub fn receive(&mut self) -> Result<SonarReturn> {
+        let deadline = Instant::now() + self.timeout;
+        let mut chunk = [0u8; FRAME_CAPACITY];
+
+        loop {
+            if let Some(length) = self.frame_length()? {
+                let frame: Vec<u8> = self.buffer.drain(..length).collect();
+                let mut cursor = Cursor::new(frame);
+                return SonarReturn::read_options(&mut cursor, ENDIAN, (DecodeMode::Strict,))
+                    .map_err(|e| Error::decode(e, 0));
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let received = match remaining.is_zero() {
+                true => 0,
+                false => self.transport.receive(&mut chunk, remaining)?,
+            };
+
+            if received == 0 && Instant::now() >= deadline {
+                let message = format!("No sonar return within {} ms", self.timeout.as_millis());
+                return Err(Error::timeout(message));
+            }
+            self.buffer.extend_from_slice(&chunk[..received]);
+        }
+    }
+
+    /// Send `switch_data` and receive the [SonarReturn] it triggers, discarding stale input
+    /// first and repeating the command up to [Sonar::retries] times if the answer is late or
+    /// garbled.
+    pub fn ping(&mut self, switch_data: &SwitchData) -> Result<SonarReturn> {
+        let mut attempt = 0;
+        loop {
+            self.buffer.clear();
+            self.transport.clear()?;
+            self.send(switch_data)?;
+
+            match self.receive() {
+                Err(error) if attempt < self.retries && retryable(&error) => {
+                    attempt += 1;
+                    log::warn!("Retrying switch data ({attempt}/{}): {error}", self.retries);
+                },
+                result => return result,
+            }
+        }
+    }
+
+    /// Discard the bytes before the first magic, returning the length of the frame it starts
+    /// once all of it has been received.
+    fn frame_length(&mut self) -> Result<Option<usize>> {
+        let start = self.buffer.windows(3).position(|window| MAGICS.iter().any(|m| window == *m));
+        match start {
+            Some(start) => drop(self.buffer.drain(..start)),
+            None => {
+                // keep a partial magic at the end
+                let keep = self.buffer.len().min(MAGICS[0].len() - 1);
+                self.buffer.drain(..self.buffer.len() - keep);
+                return Ok(None);
+            },
+        }
+
+        if self.buffer.len() < HEADER_LENGTH {
+            return Ok(None);
+        }
+
+        let header = SonarReturnHeader::read_options(&mut Cursor::new(&self.buffer), ENDIAN, ())
+            .map_err(|e| Error::decode(e, 0))?;
+        let length = HEADER_LENGTH + header.data_length as usize + 1;
+        Ok((self.buffer.len() >= length).then_some(length))
+    }
+}
+
+/// The length of the largest frame, 250 data points of 14 bits, so that a datagram holding one
+/// is never cut short.
+const FRAME_CAPACITY: usize = HEADER_LENGTH + 2 * 250 + 1;
+
+/// Whether repeating the [SwitchData] may cure `error`.
+fn retryable(error: &Error) -> bool {
+    !matches!(error.kind(), ErrorKind::Io | ErrorKind::Other | ErrorKind::Logging)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{shot_bytes, sonar_return_bytes};
+    use crate::types::SonarType;
+    use crate::Shot;
+    use std::collections::VecDeque;
+
+    use log::info;
+    use test_log::test;
+
+    /// A [Transport] answering each request with the next of `responses`, split into `chunk`
+    /// bytes per receive.
+    struct Scripted {
+        responses: VecDeque<Vec<u8>>,
+        pending: VecDeque<u8>,
+        sent: Vec<Vec<u8>>,
+        chunk: usize,
+    }
+
+    impl Scripted {
+        fn new(responses: Vec<Vec<u8>>, chunk: usize) -> Self {
+            Self { responses: responses.into(), pending: VecDeque::new(), sent: vec![], chunk }
+        }
+    }
+
+    impl Transport for Scripted {
+        fn send(&mut self, bytes: &[u8]) -> Result<()> {
+            self.sent.push(bytes.to_vec());
+            self.pending.extend(self.responses.pop_front().unwrap_or_default());
+            Ok(())
+        }
+
+        fn receive(&mut self, buffer: &mut [u8], timeout: Duration) -> Result<usize> {
+            if self.pending.is_empty() {
+                std::thread::sleep(timeout);
+                return Ok(0);
+            }
+
+            let length = self.chunk.min(buffer.len()).min(self.pending.len());
+            for (byte, pending) in buffer.iter_mut().zip(self.pending.drain(..length)) {
+                *byte = pending;
+            }
+            Ok(length)
+        }
+
+        fn clear(&mut self) -> Result<()> {
+            self.pending.clear();
+            Ok(())
+        }
+    }
+
+    fn sonar_return() -> (SonarReturn, Vec<u8>) {
+        let shot =
+            Shot::read(&mut Cursor::new(shot_bytes())).expect("It should not return an error");
+        (shot.sonar_return, sonar_return_bytes())
+    }
+
+    fn switch_data() -> SwitchData {
+        SwitchData::builder(SonarType::Scanning).build().expect("It should not return an error")
+    }
+
+    #[test]
+    fn ping() {
+        let (want, frame) = sonar_return();
+        let mut noisy = b"\x00\xFFIM\xFC".to_vec();
+        noisy.extend_from_slice(&frame);
+
+        let cases = vec![(frame.clone(), 1), (frame.clone(), 512), (noisy, 7)];
+        for (response, chunk) in cases {
+            info!("Pinging for {} bytes received {chunk} at a time", response.len());
+            let mut sonar = Sonar::new(Scripted::new(vec![response], chunk));
+            let got = sonar.ping(&switch_data()).expect("It should not return an error");
+            assert_eq!(want, got);
+
+            let sent = &sonar.transport().sent;
+            assert_eq!(1, sent.len());
+            assert_eq!(SwitchData::LENGTH, sent[0].len());
+        }
+    }
+
+    #[test]
+    fn ping_retry() {
+        let (want, frame) = sonar_return();
+        let mut garbled = frame.clone();
+        garbled[frame.len() - 1] = 0;
+
+        let cases = vec![vec![vec![], frame.clone()], vec![garbled, frame.clone()]];
+        for responses in cases {
+            info!("Pinging with a first response of {} bytes", responses[0].len());
+            let transport = Scripted::new(responses, 64);
+            let mut sonar = Sonar::new(transport).with_timeout(Duration::from_millis(20));
+            let got = sonar.ping(&switch_data()).expect("It should not return an error");
+            assert_eq!(want, got);
+            assert_eq!(2, sonar.transport().sent.len());
+        }
+    }
+
+    #[test]
+    fn ping_timeout() {
+        let transport = Scripted::new(vec![], 64);
+        let mut sonar =
+            Sonar::new(transport).with_timeout(Duration::from_millis(10)).with_retries(1);
+
+        let error = sonar.ping(&switch_data()).expect_err("It should return an error");
+        assert_eq!(&ErrorKind::Timeout, error.kind());
+        assert_eq!(2, sonar.transport().sent.len());
+    }
+}
@@ -0,0 +1,392 @@
+use crate::transport::Transport;
+use crate::{Error, Result};
+use socket2::{SockRef, TcpKeepalive};
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+
+/// The port 831L Ethernet heads listen on.
+const PORT: u16 = 4040;
+
+/// The shortest socket timeout, since a zero timeout would block indefinitely.
+const MIN_TIMEOUT: Duration = Duration::from_millis(1);
+
+/// How a [TcpTransport] or [UdpTransport] connects to a sonar head.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkOptions {
+    /// How long to wait for the head to accept a TCP connection.
+    pub connect_timeout: Duration,
+
+    /// The idle time before TCP keepalive probes are sent, or `None` to disable them.
+    /// Ignored by [UdpTransport], which has no connection to keep alive.
+    pub keepalive: Option<Duration>,
+}
+
+impl Default for NetworkOptions {
+    fn default() -> Self {
+        Self { connect_timeout: Duration::from_secs(2), keepalive: Some(Duration::from_secs(5)) }
+    }
+}
+
+/// Resolve `host` and `port` to its socket addresses.
+fn resolve(host: &str, port: u16) -> Result<Vec<SocketAddr>> {
+    let addresses: Vec<_> = (host, port).to_socket_addrs()?.collect();
+    match addresses.is_empty() {
+        true => Err(Error::new(format!("No address found for `{host}:{port}`"))),
+        false => Ok(addresses),
+    }
+}
+
+/// Whether `error` is a socket read timing out.
+fn timed_out(error: &io::Error) -> bool {
+    matches!(error.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock)
+}
+
+/// A [Transport] over a TCP connection to an 831L Ethernet head.
+#[derive(Debug)]
+pub struct TcpTransport {
+    stream: TcpStream,
+}
+
+impl TcpTransport {
+    /// The port 831L Ethernet heads accept connections on.
+    pub const PORT: u16 = PORT;
+
+    /// Connect to the head at `host` and `port`, trying each of its addresses in turn.
+    pub fn connect(host: &str, port: u16, options: &NetworkOptions) -> Result<Self> {
+        let mut last_error = None;
+        for address in resolve(host, port)? {
+            match TcpStream::connect_timeout(&address, options.connect_timeout) {
+                Ok(stream) => {
+                    let transport = Self::from_stream(stream)?;
+                    transport.set_keepalive(options.keepalive)?;
+                    return Ok(transport);
+                },
+                Err(error) => last_error = Some(error),
+            }
+        }
+
+        Err(last_error.map(Error::from).unwrap_or_else(|| Error::new(String::from("No address"))))
+    }
+
+    /// Use an already connected stream.
+    pub fn from_stream(stream: TcpStream) -> Result<Self> {
+        stream.set_nodelay(true)?;
+        Ok(Self { stream })
+    }
+
+    #[inline]
+    pub fn stream(&self) -> &TcpStream {
+        &self.stream
+    }
+
+    /// Send keepalive probes after `idle` time without traffic, or stop sending them.
+    pub fn set_keepalive(&self, idle: Option<Duration>) -> Result<()> {
+        let socket = SockRef::from(&self.stream);
+        match idle {
+            Some(idle) => {
+                let keepalive = TcpKeepalive::new().with_time(idle).with_interval(idle);
+                socket.set_tcp_keepalive(&keepalive)?;
+            },
+            None => socket.set_keepalive(false)?,
+        }
+        Ok(())
+    }
+}
+
+impl Transport for TcpTransport {
+    fn send(&mut self, bytes: &[u8]) -> Result<()> {
+        self.stream.write_all(bytes)?;
+        self.stream.flush()?;
+        Ok(())
+    }
+
+    fn receive(&mut self, buffer: &mut [u8], timeout: Duration) -> Result<usize> {
+        self.stream.set_read_timeout(Some(timeout.max(MIN_TIMEOUT)))?;
+        match self.stream.read(buffer) {
+            Ok(0) if !buffer.is_empty() => {
+                let message = "The sonar head closed the connection";
+                Err(io::Error::new(ErrorKind::ConnectionAborted, message).into())
+            },
+            Ok(received) => Ok(received),
+            Err(e) if timed_out(&e) => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.stream.set_nonblocking(true)?;
+        let mut buffer = [0u8; 512];
+        let result = loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => break Ok(()),
+                Ok(_) => continue,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break Ok(()),
+                Err(e) => break Err(e),
+            }
+        };
+        self.stream.set_nonblocking(false)?;
+        Ok(result?)
+    }
+}
+
+/// A [Transport] over UDP datagrams to an 831L Ethernet head.
+///
+/// Each [SonarReturn](crate::SonarReturn) must arrive in a single datagram, which the
+/// [Sonar](crate::Sonar) receives whole for any number of data bits.
+#[derive(Debug)]
+pub struct UdpTransport {
+    socket: UdpSocket,
+}
+
+impl UdpTransport {
+    /// The port 831L Ethernet heads receive datagrams on.
+    pub const PORT: u16 = PORT;
+
+    /// Bind an ephemeral local port and exchange datagrams with the head at `host` and `port`
+    /// only.
+    pub fn connect(host: &str, port: u16, _options: &NetworkOptions) -> Result<Self> {
+        let address = resolve(host, port)?[0];
+        let local: SocketAddr = match address {
+            SocketAddr::V4(_) => ([0, 0, 0, 0], 0).into(),
+            SocketAddr::V6(_) => ([0u16; 8], 0).into(),
+        };
+
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(address)?;
+        Ok(Self::from_socket(socket))
+    }
+
+    /// Use an already connected socket.
+    pub fn from_socket(socket: UdpSocket) -> Self {
+        Self { socket }
+    }
+
+    #[inline]
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+}
+
+impl Transport for UdpTransport {
+    fn send(&mut self, bytes: &[u8]) -> Result<()> {
+        self.socket.send(bytes)?;
+        Ok(())
+    }
+
+    fn receive(&mut self, buffer: &mut [u8], timeout: Duration) -> Result<usize> {
+        self.socket.set_read_timeout(Some(timeout.max(MIN_TIMEOUT)))?;
+        match self.socket.recv(buffer) {
+            Ok(received) => Ok(received),
+            Err(e) if timed_out(&e) => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.socket.set_nonblocking(true)?;
+        let mut buffer = [0u8; 512];
+        let result = loop {
+            match self.socket.recv(&mut buffer) {
+                Ok(_) => continue,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break Ok(()),
+                Err(e) => break Err(e),
+            }
+        };
+        self.socket.set_nonblocking(false)?;
+        Ok(result?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{shot_bytes, sonar_return_bytes};
+    use crate::transport::Sonar;
+    use crate::types::{DataBits, SonarType};
+    use crate::{Shot, Simulator, SwitchData, SyntheticPipe};
+    use binrw::BinRead;
+    use std::io::Cursor;
+    use std::net::TcpListener;
+    use std::thread;
+
+    use log::info;
+    use test_log::test;
+
+    fn switch_data() -> SwitchData {
+        SwitchData::builder(SonarType::Scanning).build().expect("It should not return an error")
+    }
+
+    fn sonar_return() -> crate::SonarReturn {
+        let shot =
+            Shot::read(&mut Cursor::new(shot_bytes())).expect("It should not return an error");
+        shot.sonar_return
+    }
+
+    /// Decode the switch data a stand-in head received.
+    fn decode(bytes: &[u8]) -> SwitchData {
+        SwitchData::read_args(&mut Cursor::new(bytes), (SonarType::Scanning,))
+            .expect("It should not return an error")
+    }
+
+    /// A stand-in for the head accepting one TCP connection, answering each of `answer` switch
+    /// data with the fixture return if set, and closing once the client does.
+    fn tcp_head(answer: Vec<bool>) -> (u16, thread::JoinHandle<usize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("It should not return an error");
+        let port = listener.local_addr().expect("It should not return an error").port();
+
+        let head = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("It should not return an error");
+            for &answer in &answer {
+                let mut bytes = [0u8; SwitchData::LENGTH];
+                stream.read_exact(&mut bytes).expect("It should not return an error");
+                decode(&bytes);
+                if answer {
+                    stream.write_all(&sonar_return_bytes()).expect("It should not return an error");
+                }
+            }
+
+            let _ = stream.read(&mut [0]);
+            answer.len()
+        });
+        (port, head)
+    }
+
+    /// A stand-in for the head on a UDP socket, answering each of `answer` switch data with the
+    /// fixture return if set.
+    fn udp_head(answer: Vec<bool>) -> (u16, thread::JoinHandle<usize>) {
+        let socket = UdpSocket::bind("127.0.0.1:0").expect("It should not return an error");
+        let port = socket.local_addr().expect("It should not return an error").port();
+
+        let head = thread::spawn(move || {
+            for &answer in &answer {
+                let mut bytes = [0u8; 64];
+                let (length, client) =
+                    socket.recv_from(&mut bytes).expect("It should not return an error");
+                assert_eq!(SwitchData::LENGTH, length);
+                decode(&bytes[..length]);
+                if answer {
+                    socket
+                        .send_to(&sonar_return_bytes(), client)
+                        .expect("It should not return an error");
+                }
+            }
+            answer.len()
+        });
+        (port, head)
+    }
+
+    #[test]
+    fn tcp_ping() {
+        let cases = vec![vec![true, true], vec![false, true]];
+        for answer in cases {
+            info!("Pinging over TCP with answers {answer:?}");
+            let (port, head) = tcp_head(answer.clone());
+            let transport = TcpTransport::connect("localhost", port, &NetworkOptions::default())
+                .expect("It should not return an error");
+            let mut sonar = Sonar::new(transport).with_timeout(Duration::from_millis(200));
+
+            let pings = answer.iter().filter(|&&answer| answer).count();
+            for _ in 0..pings {
+                let got = sonar.ping(&switch_data()).expect("It should not return an error");
+                assert_eq!(sonar_return(), got);
+            }
+
+            drop(sonar);
+            assert_eq!(answer.len(), head.join().expect("It should not panic"));
+        }
+    }
+
+    #[test]
+    fn tcp_keepalive() {
+        let (port, head) = tcp_head(vec![]);
+        let options =
+            NetworkOptions { keepalive: Some(Duration::from_secs(3)), ..Default::default() };
+        let transport = TcpTransport::connect("127.0.0.1", port, &options)
+            .expect("It should not return an error");
+
+        let socket = SockRef::from(transport.stream());
+        assert!(socket.keepalive().expect("It should not return an error"));
+
+        transport.set_keepalive(None).expect("It should not return an error");
+        assert!(!socket.keepalive().expect("It should not return an error"));
+
+        drop(transport);
+        head.join().expect("It should not panic");
+    }
+
+    #[test]
+    fn tcp_closed() {
+        let (port, head) = tcp_head(vec![false]);
+        let transport = TcpTransport::connect("127.0.0.1", port, &NetworkOptions::default())
+            .expect("It should not return an error");
+        let mut sonar = Sonar::new(transport).with_timeout(Duration::from_secs(2));
+
+        sonar.send(&switch_data()).expect("It should not return an error");
+        sonar
+            .transport()
+            .stream()
+            .shutdown(std::net::Shutdown::Write)
+            .expect("It should not return an error");
+        head.join().expect("It should not panic");
+
+        let error = sonar.receive().expect_err("It should return an error");
+        assert_eq!(&crate::ErrorKind::Io, error.kind());
+    }
+
+    #[test]
+    fn udp_ping_data_bits() {
+        let socket = UdpSocket::bind("127.0.0.1:0").expect("It should not return an error");
+        let port = socket.local_addr().expect("It should not return an error").port();
+        let cases = [(DataBits::X4Bits, 125), (DataBits::X8Bits, 250), (DataBits::X14Bits, 500)];
+
+        let head = thread::spawn(move || {
+            let mut simulator = Simulator::new(SonarType::Scanning, SyntheticPipe::default());
+            for _ in cases {
+                let mut bytes = [0u8; 64];
+                let (length, client) =
+                    socket.recv_from(&mut bytes).expect("It should not return an error");
+                simulator.send(&bytes[..length]).expect("It should not return an error");
+
+                let mut frame = [0u8; 1024];
+                let length = simulator
+                    .receive(&mut frame, Duration::ZERO)
+                    .expect("It should not return an error");
+                socket.send_to(&frame[..length], client).expect("It should not return an error");
+            }
+        });
+
+        let transport = UdpTransport::connect("127.0.0.1", port, &NetworkOptions::default())
+            .expect("It should not return an error");
+        let mut sonar = Sonar::new(transport).with_timeout(Duration::from_millis(500));
+        for (data_bits, want) in cases {
+            info!("Pinging over UDP for {data_bits:?}, expecting {want} bytes of data");
+            let switch_data = SwitchData::builder(SonarType::Scanning)
+                .data_bits(data_bits)
+                .build()
+                .expect("It should not return an error");
+            let got = sonar.ping(&switch_data).expect("It should not return an error");
+            assert_eq!(want, got.data().len());
+        }
+        head.join().expect("It should not panic");
+    }
+
+    #[test]
+    fn udp_ping() {
+        let cases = vec![vec![true, true], vec![false, true]];
+        for answer in cases {
+            info!("Pinging over UDP with answers {answer:?}");
+            let (port, head) = udp_head(answer.clone());
+            let transport = UdpTransport::connect("127.0.0.1", port, &NetworkOptions::default())
+                .expect("It should not return an error");
+            let mut sonar = Sonar::new(transport).with_timeout(Duration::from_millis(200));
+
+            let pings = answer.iter().filter(|&&answer| answer).count();
+            for _ in 0..pings {
+                let got = sonar.ping(&switch_data()).expect("It should not return an error");
+                assert_eq!(sonar_return(), got);
+            }
+            assert_eq!(answer.len(), head.join().expect("It should not panic"));
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::transport::Transport;
+use crate::Result;
+use serialport::{ClearBuffer, DataBits, FlowControl, Parity, SerialPort, StopBits};
+use std::io::{ErrorKind, Read, Write};
+use std::time::Duration;
+
+/// A [Transport] over the RS-485 or RS-232 serial link of a sonar head.
+pub struct SerialTransport {
+    port: Box<dyn SerialPort>,
+}
+
+impl SerialTransport {
+    /// The baud rate of the 831L serial link.
+    pub const BAUD_RATE: u32 = 115_200;
+
+    /// Open the serial port at `path` with 8 data bits, no parity and one stop bit.
+    pub fn open(path: &str, baud_rate: u32) -> Result<Self> {
+        let port = serialport::new(path, baud_rate)
+            .data_bits(DataBits::Eight)
+            .parity(Parity::None)
+            .stop_bits(StopBits::One)
+            .flow_control(FlowControl::None)
+            .open()?;
+        Ok(Self::from_port(port))
+    }
+
+    /// Use an already opened and configured serial port.
+    pub fn from_port(port: Box<dyn SerialPort>) -> Self {
+        Self { port }
+    }
+
+    #[inline]
+    pub fn port(&self) -> &dyn SerialPort {
+        self.port.as_ref()
+    }
+}
+
+impl std::fmt::Debug for SerialTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SerialTransport").field("port", &self.port.name()).finish()
+    }
+}
+
+impl Transport for SerialTransport {
+    fn send(&mut self, bytes: &[u8]) -> Result<()> {
+        self.port.write_all(bytes)?;
+        self.port.flush()?;
+        Ok(())
+    }
+
+    fn receive(&mut self, buffer: &mut [u8], timeout: Duration) -> Result<usize> {
+        self.port.set_timeout(timeout)?;
+        match self.port.read(buffer) {
+            Ok(received) => Ok(received),
+            Err(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock) => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.port.clear(ClearBuffer::Input)?;
+        Ok(())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::fixtures::{shot_bytes, sonar_return_bytes};
+    use crate::transport::Sonar;
+    use crate::types::{RangeCode, SonarType};
+    use crate::{ErrorKind, Shot, SwitchData};
+    use binrw::BinRead;
+    use serialport::TTYPort;
+    use std::io::Cursor;
+    use std::thread;
+
+    use log::info;
+    use test_log::test;
+
+    /// A stand-in for the head on the far end of a pseudo-terminal, answering each of `answer`
+    /// switch data with the fixture return if set, and returning the switch data received once
+    /// the client hangs up.
+    fn head(mut port: TTYPort, answer: Vec<bool>) -> thread::JoinHandle<Vec<SwitchData>> {
+        thread::spawn(move || {
+            let frame = sonar_return_bytes();
+            port.set_timeout(Duration::from_secs(5)).expect("It should not return an error");
+            let mut received = Vec::new();
+            for answer in answer {
+                let mut bytes = [0u8; SwitchData::LENGTH];
+                port.read_exact(&mut bytes).expect("It should not return an error");
+                let switch_data =
+                    SwitchData::read_args(&mut Cursor::new(bytes), (SonarType::Scanning,))
+                        .expect("It should not return an error");
+                received.push(switch_data);
+
+                if answer {
+                    port.write_all(&frame).expect("It should not return an error");
+                }
+            }
+
+            // closing early would hang up before the client has read the last answer
+            let _ = port.read(&mut [0]);
+            received
+        })
+    }
+
+    fn pair() -> (SerialTransport, TTYPort) {
+        let (master, slave) = TTYPort::pair().expect("It should not return an error");
+        (SerialTransport::from_port(Box::new(master)), slave)
+    }
+
+    #[test]
+    fn ping() {
+        let (transport, port) = pair();
+        let head = head(port, vec![true, true]);
+
+        let mut sonar = Sonar::new(transport).with_timeout(Duration::from_secs(2));
+        let shot =
+            Shot::read(&mut Cursor::new(shot_bytes())).expect("It should not return an error");
+
+        let ranges = [RangeCode::X1m, RangeCode::X2m];
+        for range_index in ranges {
+            info!("Pinging the head at {range_index}");
+            let switch_data = SwitchData::builder(SonarType::Scanning)
+                .range_index(range_index)
+                .build()
+                .expect("It should not return an error");
+            let got = sonar.ping(&switch_data).expect("It should not return an error");
+            assert_eq!(shot.sonar_return, got);
+        }
+
+        drop(sonar);
+        let received = head.join().expect("It should not panic");
+        let got: Vec<_> = received.iter().map(|switch_data| switch_data.range_index).collect();
+        assert_eq!(ranges.to_vec(), got);
+    }
+
+    #[test]
+    fn ping_retry() {
+        let (transport, port) = pair();
+        let head = head(port, vec![false, true]);
+
+        let mut sonar = Sonar::new(transport).with_timeout(Duration::from_millis(200));
+        let switch_data = SwitchData::builder(SonarType::Scanning)
+            .build()
+            .expect("It should not return an error");
+        sonar.ping(&switch_data).expect("It should not return an error");
+        drop(sonar);
+        assert_eq!(2, head.join().expect("It should not panic").len());
+    }
+
+    #[test]
+    fn ping_timeout() {
+        let (transport, port) = pair();
+        let head = head(port, vec![false]);
+
+        let mut sonar =
+            Sonar::new(transport).with_timeout(Duration::from_millis(100)).with_retries(0);
+        let switch_data = SwitchData::builder(SonarType::Scanning)
+            .build()
+            .expect("It should not return an error");
+        let error = sonar.ping(&switch_data).expect_err("It should return an error");
+        assert_eq!(&ErrorKind::Timeout, error.kind());
+        drop(sonar);
+        assert_eq!(1, head.join().expect("It should not panic").len());
+    }
+}
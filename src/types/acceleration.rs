@@ -74,7 +74,7 @@ impl BinWrite for Acceleration {
         endian: Endian,
         args: Self::Args<'_>,
     ) -> BinResult<()> {
-        let raw = (self.acceleration / Acceleration::SCALE) as i16;
+        let raw = (self.acceleration / Acceleration::SCALE).round() as i16;
         let values = (raw, self.new_data, self.error_alarm);
         i14f2::write(&values, writer, endian, args)
     }
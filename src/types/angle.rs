@@ -37,6 +37,18 @@ impl Angle {
     pub fn valid(&self) -> bool {
         Self::valid_angle(self.angle)
     }
+
+    /// Whether the angle was updated since the previous sonar return.
+    #[inline]
+    pub fn new_data(&self) -> bool {
+        self.new_data
+    }
+
+    /// Whether the sensor reported an error with the angle.
+    #[inline]
+    pub fn error_alarm(&self) -> bool {
+        self.error_alarm
+    }
 }
 
 impl Display for Angle {
@@ -86,7 +98,7 @@ impl BinWrite for Angle {
         endian: Endian,
         args: Self::Args<'_>,
     ) -> BinResult<()> {
-        let raw = (self.angle / Self::SCALE) as i16;
+        let raw = (self.angle / Self::SCALE).round() as i16;
         let values = (raw, self.new_data, self.error_alarm);
         i14f2::write(&values, writer, endian, args)
     }
@@ -3,10 +3,12 @@ use crate::types::{
         absorption, datetime, pulse_length, real_time_prf, sector_size, sound_velocity, start_gain,
         train_angle,
     },
-    Config, DataPoints, MotionConfig, RangeCode, SensorAvailable, SensorInformation,
+    Config, DataPoints, MotionConfig, RangeCode, SensorAvailable, SensorInformation, SubFormat,
 };
+use crate::{validation::Warning, DecodeMode};
 use binrw::{BinRead, BinWrite};
 use chrono::{DateTime, Utc};
+use std::ops::RangeInclusive;
 
 #[cfg(feature = "pyo3")]
 use pyo3::prelude::*;
@@ -27,6 +29,7 @@ use pyo3::prelude::*;
 )]
 #[brw(big)]
 #[br(
+    import(mode: DecodeMode),
     assert(total_length == Self::VALID_TOTAL_LENGTH),
     assert(data_length == Self::VALID_DATA_LENGTH)
 )]
@@ -57,28 +60,53 @@ pub struct FileHeader {
     #[pyo3(get, set)]
     data_length: u16,
 
+    #[cfg(not(feature = "pyo3"))]
+    #[br(restore_position, parse_with = datetime::parse_sub_format)]
+    #[bw(ignore)]
+    sub_format: SubFormat,
+
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    #[br(restore_position, parse_with = datetime::parse_sub_format)]
+    #[bw(ignore)]
+    sub_format: SubFormat,
+
     #[cfg(not(feature = "pyo3"))]
     #[br(parse_with = datetime::parse)]
-    #[bw(write_with = datetime::write)]
-    #[brw(pad_after = 1)]
+    #[bw(write_with = datetime::write, args(*sub_format))]
     datetime: DateTime<Utc>,
 
     #[cfg(feature = "pyo3")]
     #[pyo3(get, set)]
     #[br(parse_with = datetime::parse)]
-    #[bw(write_with = datetime::write)]
-    #[brw(pad_after = 1)]
+    #[bw(write_with = datetime::write, args(*sub_format))]
     datetime: DateTime<Utc>,
 
+    /// Reserved byte 33.
+    #[cfg(not(feature = "pyo3"))]
+    reserved_33: [u8; 1],
+
+    /// Reserved byte 33.
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    reserved_33: [u8; 1],
+
     #[cfg(not(feature = "pyo3"))]
-    #[brw(pad_after = 2)]
     sensor_available: SensorAvailable,
 
     #[cfg(feature = "pyo3")]
     #[pyo3(get, set)]
-    #[brw(pad_after = 2)]
     sensor_available: SensorAvailable,
 
+    /// Reserved bytes 35 to 36.
+    #[cfg(not(feature = "pyo3"))]
+    reserved_35: [u8; 2],
+
+    /// Reserved bytes 35 to 36.
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    reserved_35: [u8; 2],
+
     #[cfg(not(feature = "pyo3"))]
     motion: MotionConfig,
 
@@ -87,35 +115,35 @@ pub struct FileHeader {
     motion: MotionConfig,
 
     #[cfg(not(feature = "pyo3"))]
-    #[br(parse_with = start_gain::parse)]
+    #[br(parse_with = start_gain::parse, args(mode))]
     #[bw(write_with = start_gain::write)]
     start_gain: u8,
 
     #[cfg(feature = "pyo3")]
     #[pyo3(get, set)]
-    #[br(parse_with = start_gain::parse)]
+    #[br(parse_with = start_gain::parse, args(mode))]
     #[bw(write_with = start_gain::write)]
     start_gain: u8,
 
     #[cfg(not(feature = "pyo3"))]
-    #[br(parse_with = sector_size::parse)]
+    #[br(parse_with = sector_size::parse, args(mode))]
     #[bw(write_with = sector_size::write)]
     sector_size: u16,
 
     #[cfg(feature = "pyo3")]
     #[pyo3(get, set)]
-    #[br(parse_with = sector_size::parse)]
+    #[br(parse_with = sector_size::parse, args(mode))]
     #[bw(write_with = sector_size::write)]
     sector_size: u16,
 
     #[cfg(not(feature = "pyo3"))]
-    #[br(parse_with = train_angle::parse)]
+    #[br(parse_with = train_angle::parse, args(mode))]
     #[bw(write_with = train_angle::write)]
     train_angle: u16,
 
     #[cfg(feature = "pyo3")]
     #[pyo3(get, set)]
-    #[br(parse_with = train_angle::parse)]
+    #[br(parse_with = train_angle::parse, args(mode))]
     #[bw(write_with = train_angle::write)]
     train_angle: u16,
 
@@ -145,31 +173,45 @@ pub struct FileHeader {
     config: Config,
 
     #[cfg(not(feature = "pyo3"))]
-    #[br(parse_with = pulse_length::parse)]
+    #[br(parse_with = pulse_length::parse, args(mode))]
     #[bw(write_with = pulse_length::write)]
-    #[brw(pad_after = 1)]
     pulse_length: u16,
 
     #[cfg(feature = "pyo3")]
     #[pyo3(get, set)]
-    #[br(parse_with = pulse_length::parse)]
+    #[br(parse_with = pulse_length::parse, args(mode))]
     #[bw(write_with = pulse_length::write)]
-    #[brw(pad_after = 1)]
     pulse_length: u16,
 
+    /// Reserved byte 45.
+    #[cfg(not(feature = "pyo3"))]
+    reserved_45: [u8; 1],
+
+    /// Reserved byte 45.
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    reserved_45: [u8; 1],
+
     #[cfg(not(feature = "pyo3"))]
     #[br(parse_with = sound_velocity::parse)]
     #[bw(write_with = sound_velocity::write)]
-    #[brw(pad_after = 31)]
     sound_velocity: f32,
 
     #[cfg(feature = "pyo3")]
     #[pyo3(get, set)]
     #[br(parse_with = sound_velocity::parse)]
     #[bw(write_with = sound_velocity::write)]
-    #[brw(pad_after = 31)]
     sound_velocity: f32,
 
+    /// Reserved bytes 48 to 78.
+    #[cfg(not(feature = "pyo3"))]
+    reserved_48: [u8; 31],
+
+    /// Reserved bytes 48 to 78.
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    reserved_48: [u8; 31],
+
     #[cfg(not(feature = "pyo3"))]
     operating_frequency: u16,
 
@@ -180,16 +222,23 @@ pub struct FileHeader {
     #[cfg(not(feature = "pyo3"))]
     #[br(parse_with = real_time_prf::parse)]
     #[bw(write_with = real_time_prf::write)]
-    #[brw(pad_after = 15)]
     real_time_prf: f32,
 
     #[cfg(feature = "pyo3")]
     #[pyo3(get, set)]
     #[br(parse_with = real_time_prf::parse)]
     #[bw(write_with = real_time_prf::write)]
-    #[brw(pad_after = 15)]
     real_time_prf: f32,
 
+    /// Reserved bytes 83 to 97.
+    #[cfg(not(feature = "pyo3"))]
+    reserved_83: [u8; 15],
+
+    /// Reserved bytes 83 to 97.
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    reserved_83: [u8; 15],
+
     #[cfg(not(feature = "pyo3"))]
     sensor_information: SensorInformation,
 
@@ -222,4 +271,244 @@ pub struct FileHeader {
 impl FileHeader {
     pub(crate) const VALID_TOTAL_LENGTH: u16 = 512;
     pub(crate) const VALID_DATA_LENGTH: u16 = 283;
+
+    /// The number of points in the echo data.
+    #[inline]
+    pub fn data_size_index(&self) -> DataPoints {
+        self.data_size_index
+    }
+
+    /// The date and time the shot was recorded.
+    #[inline]
+    pub fn datetime(&self) -> DateTime<Utc> {
+        self.datetime
+    }
+
+    /// The format of the sub-seconds of the [FileHeader::datetime], as recorded.
+    #[inline]
+    pub fn sub_format(&self) -> SubFormat {
+        self.sub_format
+    }
+
+    /// Change the format the sub-seconds of the [FileHeader::datetime] are written in.
+    ///
+    /// Converting to [SubFormat::Hundredths] truncates the milliseconds.
+    #[inline]
+    pub fn set_sub_format(&mut self, sub_format: SubFormat) {
+        self.sub_format = sub_format;
+    }
+
+    /// The range of *PipeSonarL* versions that could have recorded the shot, inferred from the
+    /// [FileHeader::sub_format].
+    #[inline]
+    pub fn pipe_sonar_versions(&self) -> RangeInclusive<u16> {
+        self.sub_format.pipe_sonar_versions()
+    }
+
+    /// The speed of sound in water the shot was recorded with, in m/s.
+    #[inline]
+    pub fn sound_velocity(&self) -> f32 {
+        self.sound_velocity
+    }
+
+    /// Change the recorded speed of sound, without rescaling any ranges.
+    #[inline]
+    pub(crate) fn set_sound_velocity(&mut self, sound_velocity: f32) {
+        self.sound_velocity = sound_velocity;
+    }
+
+    #[inline]
+    pub fn motion(&self) -> &MotionConfig {
+        &self.motion
+    }
+
+    #[inline]
+    pub fn motion_mut(&mut self) -> &mut MotionConfig {
+        &mut self.motion
+    }
+
+    /// The size of the sector scanned in [Mode::Sector](crate::types::Mode::Sector), in degrees.
+    #[inline]
+    pub fn sector_size(&self) -> u16 {
+        self.sector_size
+    }
+
+    /// The gain applied at the start of the echo, in dB.
+    #[inline]
+    pub fn start_gain(&self) -> u8 {
+        self.start_gain
+    }
+
+    /// The angle the sector is centered on, in degrees.
+    #[inline]
+    pub fn train_angle(&self) -> u16 {
+        self.train_angle
+    }
+
+    #[inline]
+    pub fn range_code(&self) -> RangeCode {
+        self.range_code
+    }
+
+    /// The absorption of sound in water the shot was recorded with, in dB/m.
+    #[inline]
+    pub fn absorption(&self) -> f32 {
+        self.absorption
+    }
+
+    /// The length of the transmitted pulse, in μs.
+    #[inline]
+    pub fn pulse_length(&self) -> u16 {
+        self.pulse_length
+    }
+
+    /// The frequency the head was operating at, in kHz when it lies in the band of the
+    /// [SonarType](crate::types::SonarType). Heads also record values outside of any band, such
+    /// as the 8 of the sample recording, from which the frequency cannot be recovered.
+    #[inline]
+    pub fn operating_frequency(&self) -> u16 {
+        self.operating_frequency
+    }
+
+    #[inline]
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Whether an external pitch, roll and distance sensor is present.
+    #[inline]
+    pub fn sensor_available(&self) -> SensorAvailable {
+        self.sensor_available
+    }
+
+    /// Which of the pitch, roll and distance sensor readings are valid.
+    #[inline]
+    pub fn sensor_information(&self) -> SensorInformation {
+        self.sensor_information
+    }
+
+    /// The pitch from the external sensor in degrees, valid only when
+    /// [SensorInformation::pitch_valid] is set.
+    #[inline]
+    pub fn pitch(&self) -> f32 {
+        self.pitch
+    }
+
+    /// The roll from the external sensor in degrees, valid only when
+    /// [SensorInformation::roll_valid] is set.
+    #[inline]
+    pub fn roll(&self) -> f32 {
+        self.roll
+    }
+
+    /// The distance travelled along the pipe, valid only when
+    /// [SensorInformation::distance_valid] is set.
+    #[inline]
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    /// Check every ranged value, returning a [Warning] for each out of range.
+    ///
+    /// Shots decoded in [DecodeMode::Strict] never produce warnings.
+    pub fn validate(&self) -> Vec<Warning> {
+        let checks = [
+            (
+                "start_gain",
+                start_gain::valid(self.start_gain),
+                start_gain::out_of_range(self.start_gain),
+            ),
+            (
+                "sector_size",
+                sector_size::valid(self.sector_size),
+                sector_size::out_of_range(self.sector_size),
+            ),
+            (
+                "train_angle",
+                train_angle::valid(self.train_angle),
+                train_angle::out_of_range(self.train_angle),
+            ),
+            (
+                "absorption",
+                absorption::valid(self.absorption),
+                absorption::out_of_range(self.absorption),
+            ),
+            (
+                "pulse_length",
+                pulse_length::valid(self.pulse_length),
+                pulse_length::out_of_range(self.pulse_length),
+            ),
+            (
+                "sound_velocity",
+                sound_velocity::valid(self.sound_velocity),
+                sound_velocity::out_of_range(self.sound_velocity),
+            ),
+            (
+                "real_time_prf",
+                real_time_prf::valid(self.real_time_prf),
+                real_time_prf::out_of_range(self.real_time_prf),
+            ),
+        ];
+
+        checks
+            .into_iter()
+            .filter(|(_, valid, _)| !valid)
+            .map(|(field, _, error)| Warning::out_of_range(field, error))
+            .collect()
+    }
+
+    /// The raw reserved byte 33, preserved as recorded.
+    #[inline]
+    pub fn reserved_33(&self) -> &[u8; 1] {
+        &self.reserved_33
+    }
+
+    #[inline]
+    pub fn reserved_33_mut(&mut self) -> &mut [u8; 1] {
+        &mut self.reserved_33
+    }
+
+    /// The raw reserved bytes 35 to 36, preserved as recorded.
+    #[inline]
+    pub fn reserved_35(&self) -> &[u8; 2] {
+        &self.reserved_35
+    }
+
+    #[inline]
+    pub fn reserved_35_mut(&mut self) -> &mut [u8; 2] {
+        &mut self.reserved_35
+    }
+
+    /// The raw reserved byte 45, preserved as recorded.
+    #[inline]
+    pub fn reserved_45(&self) -> &[u8; 1] {
+        &self.reserved_45
+    }
+
+    #[inline]
+    pub fn reserved_45_mut(&mut self) -> &mut [u8; 1] {
+        &mut self.reserved_45
+    }
+
+    /// The raw reserved bytes 48 to 78, preserved as recorded.
+    #[inline]
+    pub fn reserved_48(&self) -> &[u8; 31] {
+        &self.reserved_48
+    }
+
+    #[inline]
+    pub fn reserved_48_mut(&mut self) -> &mut [u8; 31] {
+        &mut self.reserved_48
+    }
+
+    /// The raw reserved bytes 83 to 97, preserved as recorded.
+    #[inline]
+    pub fn reserved_83(&self) -> &[u8; 15] {
+        &self.reserved_83
+    }
+
+    #[inline]
+    pub fn reserved_83_mut(&mut self) -> &mut [u8; 15] {
+        &mut self.reserved_83
+    }
 }
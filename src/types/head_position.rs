@@ -1,3 +1,4 @@
+use crate::error::OutOfRangeError;
 use crate::types::{
     util::primitive::{read_u8_bits, write_u8_bits},
     Direction,
@@ -49,6 +50,11 @@ impl HeadPosition {
     pub fn valid(&self) -> bool {
         Self::MIN <= self.angle && self.angle <= Self::MAX
     }
+
+    #[inline]
+    pub(crate) fn out_of_range(&self) -> OutOfRangeError {
+        OutOfRangeError::new("Head position", self.angle, Self::MIN, Self::MAX, "°")
+    }
 }
 
 impl Default for HeadPosition {
@@ -115,7 +121,7 @@ impl BinWrite for HeadPosition {
         endian: Endian,
         args: Self::Args<'_>,
     ) -> BinResult<()> {
-        let value = ((self.angle / 0.3).round() as u16).saturating_add(600);
+        let value = ((self.angle / 0.3).round() as i32 + 600).clamp(0, u16::MAX as i32) as u16;
 
         // extract the high and low parts
         let high = ((value >> 8) & 0xFF) as u8;
@@ -25,7 +25,7 @@ mod sonar_type;
 mod step_direction;
 mod step_size;
 mod transducer;
-mod util;
+pub(crate) mod util;
 mod zero;
 
 pub use acceleration::Acceleration;
@@ -41,6 +41,7 @@ pub use logf::Logf;
 pub use mode::Mode;
 pub use motion_config::MotionConfig;
 pub use motor_calibrate::MotorCalibrate;
+pub use primitive::datetime::SubFormat;
 pub use profile_grid::ProfileGrid;
 pub use profile_point_detection::ProfilePointDetection;
 pub use range_code::RangeCode;
@@ -86,6 +87,7 @@ pub(crate) fn types(module: &Bound<'_, PyModule>) -> PyResult<()> {
     module.add_class::<SonarType>()?;
     module.add_class::<StepDirection>()?;
     module.add_class::<StepSize>()?;
+    module.add_class::<SubFormat>()?;
     module.add_class::<Transducer>()?;
     module.add_class::<Zero>()?;
     Ok(())
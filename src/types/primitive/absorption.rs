@@ -1,6 +1,7 @@
 //! Utilities for the **Absorption** primitive, the recorded loss of sound energy as the sonar waves
 //! propagate through water.
-use binrw::{parser, writer, BinRead, BinResult, BinWrite, Error};
+use crate::error::OutOfRangeError;
+use binrw::{parser, writer, BinRead, BinResult, BinWrite};
 
 pub(crate) const MIN: f32 = 0.0;
 pub(crate) const MAX: f32 = 2.55;
@@ -11,6 +12,18 @@ pub fn valid(absorption: f32) -> bool {
     (MIN..=MAX).contains(&absorption)
 }
 
+/// Check that the **Absorption** falls on a 0.01 dB/m increment, so writing it does not round it.
+#[inline]
+pub fn on_increment(absorption: f32) -> bool {
+    let raw = absorption * 100.0;
+    (raw - raw.round()).abs() < 1e-3
+}
+
+#[inline]
+pub(crate) fn out_of_range(absorption: f32) -> OutOfRangeError {
+    OutOfRangeError::new("Absorption", absorption, MIN, MAX, " dB/m")
+}
+
 /// Parse **Absorption** from a byte.
 #[parser(reader)]
 pub fn parse() -> BinResult<f32> {
@@ -19,10 +32,7 @@ pub fn parse() -> BinResult<f32> {
 
     if !valid(absorption) {
         let pos = reader.stream_position()?;
-        return Err(Error::AssertFail {
-            pos,
-            message: "Absorption exceeds range of 0.0 to 2.55 dB/m".to_string(),
-        });
+        return Err(out_of_range(absorption).at(pos));
     }
 
     Ok(absorption)
@@ -33,10 +43,7 @@ pub fn parse() -> BinResult<f32> {
 pub fn write(absorption: &f32) -> BinResult<()> {
     if !valid(*absorption) {
         let pos = writer.stream_position()?;
-        return Err(Error::AssertFail {
-            pos,
-            message: "Absorption exceeds range of 0.0 to 2.55 dB/m".to_string(),
-        });
+        return Err(out_of_range(*absorption).at(pos));
     }
 
     let raw = (*absorption * 100.0).round() as u8;
@@ -70,6 +77,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_on_increment() {
+        let cases = vec![(0.0, true), (1.7, true), (2.55, true), (0.605, false), (1.001, false)];
+
+        for (absorption, want) in cases {
+            info!("Checking the increment of {absorption:?}, expecting {want:?}");
+            let got = on_increment(absorption);
+            assert_eq!(want, got);
+        }
+    }
+
     #[test]
     fn test_parse() {
         for &(want, ref bytes) in BINARY_CASES.iter() {
@@ -9,8 +9,14 @@
 //!     - *PipeSonarL* version 1013 and above: `mmm` (_milliseconds_)
 use crate::types::primitive::string;
 use crate::ENDIAN;
-use binrw::{parser, writer, BinResult, Error};
+use binrw::{parser, writer, BinRead, BinResult, Error};
 use chrono::{DateTime, NaiveDate, NaiveTime, TimeDelta, Utc};
+use std::fmt::{Display, Formatter};
+use std::io::SeekFrom;
+use std::ops::RangeInclusive;
+
+#[cfg(feature = "pyo3")]
+use pyo3::prelude::*;
 
 pub(crate) const DATE_FORMAT: &str = "%d-%b-%Y";
 pub(crate) const TIME_FORMAT: &str = "%H:%M:%S";
@@ -21,19 +27,76 @@ pub(crate) const SUB_LENGTH: usize = 4;
 
 const SCALE_NANOSECONDS_TO_HUNDREDTHS: u32 = 10_000_000;
 
+/// The version of *PipeSonarL* that introduced the [SubFormat::Milliseconds] format.
+pub const MILLISECONDS_VERSION: u16 = 1013;
+
 /// The format of the sub-seconds component of the **Date Time** primitive.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(
+    target_family = "wasm",
+    derive(tsify::Tsify, serde::Serialize, serde::Deserialize),
+    tsify(into_wasm_abi, from_wasm_abi)
+)]
+#[cfg_attr(
+    all(feature = "serde", not(target_family = "wasm")),
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "pyo3", pyclass(eq))]
 pub enum SubFormat {
     /// Hundredths of Seconds (`.hh`) for *PipeSonarL* up to version 1012.
+    #[default]
     Hundredths,
 
     /// Milliseconds (`mmm`) for *PipeSonarL* version 1013 and above.
     Milliseconds,
 }
 
-impl Default for SubFormat {
-    fn default() -> Self {
-        Self::Hundredths
+impl SubFormat {
+    /// The range of *PipeSonarL* versions that record this format.
+    pub fn pipe_sonar_versions(&self) -> RangeInclusive<u16> {
+        match *self {
+            Self::Hundredths => 0..=MILLISECONDS_VERSION - 1,
+            Self::Milliseconds => MILLISECONDS_VERSION..=u16::MAX,
+        }
+    }
+}
+
+impl Display for SubFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Hundredths => {
+                write!(f, "hundredths (PipeSonarL {} and below)", MILLISECONDS_VERSION - 1)
+            },
+            Self::Milliseconds => {
+                write!(f, "milliseconds (PipeSonarL {MILLISECONDS_VERSION} and above)")
+            },
+        }
+    }
+}
+
+#[cfg(feature = "pyo3")]
+#[pymethods]
+impl SubFormat {
+    pub(crate) fn __str__(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Detect the [SubFormat] of a **Date Time** from its sub-seconds component.
+///
+/// The reader is left after the sub-seconds component; use `restore_position` to parse the
+/// **Date Time** itself afterward.
+#[parser(reader)]
+pub fn parse_sub_format() -> BinResult<SubFormat> {
+    reader.seek(SeekFrom::Current((DATE_LENGTH + TIME_LENGTH) as i64))?;
+    let sub = <[u8; SUB_LENGTH]>::read(reader)?;
+
+    // The presence of the starting `.` indicates this is the hundredths format.
+    if sub[0] == b'.' {
+        Ok(SubFormat::Hundredths)
+    } else {
+        Ok(SubFormat::Milliseconds)
     }
 }
 
@@ -80,7 +143,7 @@ pub fn write(datetime: &DateTime<Utc>, sub_format: SubFormat) -> BinResult<()> {
             format!(".{hundredths:02}")
         },
         SubFormat::Milliseconds => {
-            let milliseconds = datetime.timestamp_millis();
+            let milliseconds = datetime.timestamp_subsec_millis();
             format!("{milliseconds:03}")
         },
     };
@@ -97,7 +160,6 @@ mod tests {
     use std::str::from_utf8;
 
     use binrw::io::Cursor;
-    use binrw::Endian;
     use chrono::{TimeDelta, TimeZone, Utc};
     use log::info;
     use test_log::test;
@@ -107,21 +169,71 @@ mod tests {
         assert_eq!(SubFormat::Hundredths, SubFormat::default());
     }
 
+    #[test]
+    fn sub_format_pipe_sonar_versions() {
+        let cases = [(SubFormat::Hundredths, 1012, true), (SubFormat::Hundredths, 1013, false)];
+        let cases = cases
+            .into_iter()
+            .chain([(SubFormat::Milliseconds, 1012, false), (SubFormat::Milliseconds, 1013, true)]);
+
+        for (sub_format, version, want) in cases {
+            info!("Checking {sub_format} contains version {version}, want {want:?}");
+            assert_eq!(want, sub_format.pipe_sonar_versions().contains(&version));
+        }
+    }
+
+    #[test]
+    fn test_parse_sub_format() {
+        let cases: [(&[u8], SubFormat); 3] = [
+            (b"01-JAN-2023\x0012:34:56\0.23\0", SubFormat::Hundredths),
+            (b"01-JAN-2023\x0012:34:56\x00230\0", SubFormat::Milliseconds),
+            (b"01-JAN-2023\x0012:34:56\x00009\0", SubFormat::Milliseconds),
+        ];
+
+        for (bytes, want) in cases {
+            info!("Detecting the sub-second format of {bytes:?}, want {want:?}");
+            let mut cursor = Cursor::new(bytes);
+            let got =
+                parse_sub_format(&mut cursor, ENDIAN, ()).expect("It should not return an error");
+            assert_eq!(want, got);
+        }
+    }
+
+    #[test]
+    fn test_milliseconds_round_trip() {
+        let cases: [&[u8]; 3] = [
+            b"01-JAN-2023\x0012:34:56\x00000\0",
+            b"01-JAN-2023\x0012:34:56\x00230\0",
+            b"01-JAN-2023\x0012:34:56\x00999\0",
+        ];
+
+        for want in cases {
+            info!("Round-tripping {want:?}");
+            let datetime =
+                parse(&mut Cursor::new(want), ENDIAN, ()).expect("It should not return an error");
+
+            let mut cursor = Cursor::new(Vec::new());
+            write(&datetime, &mut cursor, ENDIAN, (SubFormat::Milliseconds,))
+                .expect("It should not return an error");
+            assert_eq!(want, cursor.into_inner());
+        }
+    }
+
     #[test]
     fn test_parse() {
         let cases = [
             (
-                b"01-JAN-2023\012:34:56\0.23\0",
+                b"01-JAN-2023\x0012:34:56\0.23\0",
                 Utc.with_ymd_and_hms(2023, 1, 1, 12, 34, 56).unwrap()
                     + TimeDelta::nanoseconds((23 * SCALE_NANOSECONDS_TO_HUNDREDTHS) as i64),
             ),
             (
-                b"01-JAN-2023\012:34:56\0.99\0",
+                b"01-JAN-2023\x0012:34:56\0.99\0",
                 Utc.with_ymd_and_hms(2023, 1, 1, 12, 34, 56).unwrap()
                     + TimeDelta::nanoseconds(990_000_000), // Near 1 second
             ),
             (
-                b"01-JAN-2023\012:34:57\0.00\0",
+                b"01-JAN-2023\x0012:34:57\0.00\0",
                 Utc.with_ymd_and_hms(2023, 1, 1, 12, 34, 56).unwrap()
                     + TimeDelta::nanoseconds(1_000_000_000), // Exactly 1 second
             ),
@@ -139,17 +251,17 @@ mod tests {
     fn test_write() {
         let cases = [
             (
-                b"01-JAN-2023\012:34:56\0.23\0",
+                b"01-JAN-2023\x0012:34:56\0.23\0",
                 Utc.with_ymd_and_hms(2023, 1, 1, 12, 34, 56).unwrap()
                     + TimeDelta::nanoseconds((23 * SCALE_NANOSECONDS_TO_HUNDREDTHS) as i64),
             ),
             (
-                b"01-JAN-2023\012:34:56\0.99\0",
+                b"01-JAN-2023\x0012:34:56\0.99\0",
                 Utc.with_ymd_and_hms(2023, 1, 1, 12, 34, 56).unwrap()
                     + TimeDelta::nanoseconds(999_000_000), // Near 1 second
             ),
             (
-                b"01-JAN-2023\012:34:57\0.00\0",
+                b"01-JAN-2023\x0012:34:57\0.00\0",
                 Utc.with_ymd_and_hms(2023, 1, 1, 12, 34, 56).unwrap()
                     + TimeDelta::nanoseconds(1_000_000_000), // Exactly 1 second
             ),
@@ -2,9 +2,12 @@
 //!
 //! ## Wire format
 //! - [SonarType.Scanning]: 2.15 MHz to 2.35 MHz in 5 kHz increments, and
-//! - [SonarType.FixedPosition]: 900 kHz to 1.10 MHz in 5 kHz increments.
+//! - [SonarType.FixedPosition]: 900 kHz to 1.10 MHz in 5 kHz increments,
+//!
+//! centered on 100 ⇔ 2.25 MHz and 1.00 MHz respectively.
+use crate::error::OutOfRangeError;
 use crate::types::SonarType;
-use binrw::{parser, writer, BinRead, BinResult, BinWrite, Error};
+use binrw::{parser, writer, BinRead, BinResult, BinWrite};
 
 pub(crate) const MIN_SCANNING: f32 = 2.15;
 pub(crate) const MAX_SCANNING: f32 = 2.35;
@@ -12,9 +15,13 @@ pub(crate) const MAX_SCANNING: f32 = 2.35;
 pub(crate) const MIN_FIXED_POSITION: f32 = 0.9;
 pub(crate) const MAX_FIXED_POSITION: f32 = 1.1;
 
-const ERR_MESSAGE_RANGE: &str = "frequency exceeds range";
+/// The byte of the center **Frequency**.
+const CENTER: f32 = 100.0;
+
+/// The **Frequency** increment of the byte, in kHz.
+const INCREMENT: f32 = 5.0;
 
-/// Obtain the center **Frequency** offset for the specified [SonarType].
+/// Obtain the center **Frequency** offset in kHz for the specified [SonarType].
 fn offset_for(sonar_type: SonarType) -> f32 {
     match sonar_type {
         SonarType::Scanning => 2250.0,
@@ -22,23 +29,41 @@ fn offset_for(sonar_type: SonarType) -> f32 {
     }
 }
 
-/// Validate the **Frequency** for the specified [SonarType].
-pub fn valid_for(sonar_type: SonarType, frequency: f32) -> bool {
+/// Obtain the valid range of the **Frequency** for the specified [SonarType].
+fn range_for(sonar_type: SonarType) -> (f32, f32) {
     match sonar_type {
-        SonarType::Scanning => (MIN_SCANNING..=MAX_SCANNING).contains(&frequency),
-        SonarType::FixedPosition => (MIN_FIXED_POSITION..=MAX_FIXED_POSITION).contains(&frequency),
+        SonarType::Scanning => (MIN_SCANNING, MAX_SCANNING),
+        SonarType::FixedPosition => (MIN_FIXED_POSITION, MAX_FIXED_POSITION),
     }
 }
 
+/// Validate the **Frequency** for the specified [SonarType].
+pub fn valid_for(sonar_type: SonarType, frequency: f32) -> bool {
+    let (min, max) = range_for(sonar_type);
+    (min..=max).contains(&frequency)
+}
+
+/// Check that the **Frequency** falls on a 5 kHz increment, so writing it does not round it.
+pub fn on_increment_for(sonar_type: SonarType, frequency: f32) -> bool {
+    let steps = (frequency * 1000.0 - offset_for(sonar_type)) / INCREMENT;
+    (steps - steps.round()).abs() < 1e-3
+}
+
+#[inline]
+pub(crate) fn out_of_range(sonar_type: SonarType, frequency: f32) -> OutOfRangeError {
+    let (min, max) = range_for(sonar_type);
+    OutOfRangeError::new("Frequency", frequency, min, max, " MHz")
+}
+
 /// Parse the **Frequency** for the specified [SonarType] from a byte.
 #[parser(reader)]
 pub fn parse(sonar_type: SonarType) -> BinResult<f32> {
     let raw = u8::read(reader)?;
-    let frequency = ((raw as f32 - offset_for(sonar_type)) / 5.0) + 100.0;
+    let frequency = (offset_for(sonar_type) + (raw as f32 - CENTER) * INCREMENT) / 1000.0;
 
     if !valid_for(sonar_type, frequency) {
         let pos = reader.stream_position()?;
-        return Err(Error::AssertFail { pos, message: ERR_MESSAGE_RANGE.to_string() });
+        return Err(out_of_range(sonar_type, frequency).at(pos));
     }
 
     Ok(frequency)
@@ -49,10 +74,10 @@ pub fn parse(sonar_type: SonarType) -> BinResult<f32> {
 pub fn write(frequency: &f32, sonar_type: SonarType) -> BinResult<()> {
     if !valid_for(sonar_type, *frequency) {
         let pos = writer.stream_position()?;
-        return Err(Error::AssertFail { pos, message: ERR_MESSAGE_RANGE.to_string() });
+        return Err(out_of_range(sonar_type, *frequency).at(pos));
     }
 
-    let raw = ((*frequency - 100.0) * 5.0 + offset_for(sonar_type)).ceil() as u8;
+    let raw = ((*frequency * 1000.0 - offset_for(sonar_type)) / INCREMENT + CENTER).round() as u8;
     raw.write(writer)?;
     Ok(())
 }
@@ -60,6 +85,8 @@ pub fn write(frequency: &f32, sonar_type: SonarType) -> BinResult<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use binrw::Endian;
+    use std::io::Cursor;
 
     use log::info;
     use test_log::test;
@@ -75,6 +102,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_on_increment_for() {
+        let cases = vec![
+            (SonarType::Scanning, 2.25, true),
+            (SonarType::Scanning, 2.305, true),
+            (SonarType::Scanning, MIN_SCANNING, true),
+            (SonarType::Scanning, 2.252, false),
+            (SonarType::FixedPosition, 0.995, true),
+            (SonarType::FixedPosition, MAX_FIXED_POSITION, true),
+            (SonarType::FixedPosition, 1.001, false),
+        ];
+
+        for (sonar_type, frequency, want) in cases {
+            info!("Checking the increment of {frequency} for {sonar_type:?}, expecting {want}");
+            let got = on_increment_for(sonar_type, frequency);
+            assert_eq!(want, got);
+        }
+    }
+
     #[test]
     fn test_valid_for() {
         let cases = vec![
@@ -98,4 +144,39 @@ mod tests {
             assert_eq!(want, got);
         }
     }
+
+    #[test]
+    fn test_parse_write() {
+        let cases = vec![
+            (SonarType::Scanning, 100, 2.25),
+            (SonarType::Scanning, 80, 2.15),
+            (SonarType::Scanning, 120, 2.35),
+            (SonarType::Scanning, 101, 2.255),
+            (SonarType::FixedPosition, 100, 1.0),
+            (SonarType::FixedPosition, 80, 0.9),
+            (SonarType::FixedPosition, 120, 1.1),
+        ];
+
+        for (sonar_type, raw, want) in cases {
+            info!("Parsing {raw} for {sonar_type:?}, expecting {want} MHz");
+            let got = parse(&mut Cursor::new([raw]), Endian::Big, (sonar_type,))
+                .expect("It should not return an error");
+            assert!((want - got).abs() < 1e-6);
+
+            let mut buffer = Cursor::new(Vec::new());
+            write(&got, &mut buffer, Endian::Big, (sonar_type,))
+                .expect("It should not return an error");
+            assert_eq!(vec![raw], buffer.into_inner());
+        }
+    }
+
+    #[test]
+    fn test_parse_write_out_of_range() {
+        let got = parse(&mut Cursor::new([79]), Endian::Big, (SonarType::Scanning,));
+        assert!(got.is_err());
+
+        let mut buffer = Cursor::new(Vec::new());
+        let got = write(&2.5, &mut buffer, Endian::Big, (SonarType::Scanning,));
+        assert!(got.is_err());
+    }
 }
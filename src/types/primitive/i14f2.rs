@@ -21,9 +21,8 @@
 //! </tr>
 //! </table>
 
-use binrw::{parser, writer, BinRead, BinResult, BinWrite, Error};
-use const_format::concatcp;
-use std::io::{Error as IOError, ErrorKind::InvalidData};
+use crate::error::OutOfRangeError;
+use binrw::{parser, writer, BinRead, BinResult, BinWrite};
 
 pub(crate) const MAX: i16 = 8191;
 pub(crate) const MIN: i16 = -8192;
@@ -38,8 +37,6 @@ const MASK_I14_LOW: u8 = 0b1111_1111;
 const SIGN_I14: u16 = 0b0010_0000_0000_0000;
 const SIGN_I16_FILL: u16 = 0b1100_0000_0000_0000;
 
-const ERR_MESSAGE_RANGE: &str = concatcp!("Invalid i14, exceeds range from ", MIN, " to ", MAX);
-
 /// Validate if the provided value can fit in an `i14`.
 #[inline]
 pub fn valid_i14(i14: i16) -> bool {
@@ -73,8 +70,7 @@ pub fn parse() -> BinResult<(i16, bool, bool)> {
 
     if !valid_i14(i14) {
         let pos = reader.stream_position()?;
-        let message = format!("{} ({})", ERR_MESSAGE_RANGE, i14);
-        return Err(Error::Custom { pos, err: Box::new(IOError::new(InvalidData, message)) });
+        return Err(OutOfRangeError::new("i14", i14, MIN, MAX, "").at(pos));
     }
 
     Ok((i14, flag1, flag2))
@@ -87,8 +83,7 @@ pub fn write(values: &(i16, bool, bool)) -> BinResult<()> {
 
     if !valid_i14(i14) {
         let pos = writer.stream_position()?;
-        let message = format!("{} ({})", ERR_MESSAGE_RANGE, i14);
-        return Err(Error::Custom { pos, err: Box::new(IOError::new(InvalidData, message)) });
+        return Err(OutOfRangeError::new("i14", i14, MIN, MAX, "").at(pos));
     }
 
     let mut high = (i14 as u16 >> 8) as u8 & MASK_I14_HIGH;
@@ -111,7 +106,6 @@ mod tests {
     use super::*;
     use crate::ENDIAN;
 
-    use binrw::Endian;
     use log::info;
     use std::io::Cursor;
     use test_log::test;
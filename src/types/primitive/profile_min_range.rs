@@ -1,25 +1,29 @@
 //! Utilities for the **Profile Minimum Range** primitive, the minimum range for profile point digitization.
 //!
 //! The byte format is 0―250 ⇔ 0―2.5 meters in 0.001 meter increments.
-use binrw::{parser, writer, BinRead, BinResult, BinWrite, Error};
+use crate::error::OutOfRangeError;
+use binrw::{parser, writer, BinRead, BinResult, BinWrite};
 
 pub(crate) const MIN: f32 = 0.0;
 pub(crate) const MAX: f32 = 250.0;
 
-const ERR_MESSAGE_RANGE: &str = "profile minimum range exceeds range from 0.0m to 250.0m";
-
 #[inline]
 pub fn valid(profile_min_range: f32) -> bool {
     (MIN..=MAX).contains(&profile_min_range)
 }
 
+#[inline]
+pub(crate) fn out_of_range(profile_min_range: f32) -> OutOfRangeError {
+    OutOfRangeError::new("Profile minimum range", profile_min_range, MIN, MAX, " m")
+}
+
 #[parser(reader)]
 pub fn parse() -> BinResult<f32> {
     let profile_min_range = u8::read(reader)? as f32;
 
     if !valid(profile_min_range) {
         let pos = reader.stream_position()?;
-        return Err(Error::AssertFail { pos, message: ERR_MESSAGE_RANGE.to_string() });
+        return Err(out_of_range(profile_min_range).at(pos));
     }
 
     Ok(profile_min_range)
@@ -29,7 +33,7 @@ pub fn parse() -> BinResult<f32> {
 pub fn write(profile_min_range: &f32) -> BinResult<()> {
     if !valid(*profile_min_range) {
         let pos = writer.stream_position()?;
-        return Err(Error::AssertFail { pos, message: ERR_MESSAGE_RANGE.to_string() });
+        return Err(out_of_range(*profile_min_range).at(pos));
     }
 
     (*profile_min_range as u8).write(writer)
@@ -38,7 +42,7 @@ pub fn write(profile_min_range: &f32) -> BinResult<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use binrw::{io::Cursor, BinRead, BinWrite, Endian};
+    use binrw::{io::Cursor, Endian, Error};
 
     use log::info;
     use test_log::test;
@@ -83,7 +87,7 @@ mod tests {
             info!("Parsing {bytes:?}, want error");
             let mut cursor = Cursor::new(bytes);
             let error = parse(&mut cursor, BINARY_ENDIAN, ()).unwrap_err();
-            assert!(matches!(error, Error::AssertFail { .. }));
+            assert!(matches!(error, Error::Custom { .. }));
         }
     }
 
@@ -92,7 +96,7 @@ mod tests {
         for (profile_min_range, want) in BINARY_CASES.iter() {
             info!("Writing {profile_min_range:?}, want {want:?}");
             let mut cursor = Cursor::new(Vec::new());
-            write(&profile_min_range, &mut cursor, BINARY_ENDIAN, ())
+            write(profile_min_range, &mut cursor, BINARY_ENDIAN, ())
                 .expect("It should not return an error");
             let inner = cursor.into_inner();
             let got = inner.as_slice();
@@ -111,7 +115,7 @@ mod tests {
             info!("Writing {profile_min_range:?}, want error");
             let mut cursor = Cursor::new(Vec::new());
             let error = write(profile_min_range, &mut cursor, BINARY_ENDIAN, ()).unwrap_err();
-            assert!(matches!(error, Error::AssertFail { .. }));
+            assert!(matches!(error, Error::Custom { .. }));
         }
     }
 }
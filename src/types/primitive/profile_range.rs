@@ -2,65 +2,124 @@
 //! threshold in sample numbers.
 //!
 //! Profile Range (meters) = (Profile Range (u14) * Scale (0.0005)) + Filter Delay.
+//!
+//! A raw value of 0 marks that no profile point was detected, and decodes to the filter delay.
 use crate::types::{primitive::u14, RangeCode};
 use binrw::{parser, writer, BinResult};
 
 const SCALE: f32 = 0.0005;
 
+/// The **Profile Range** of a return that detected no profile point, the raw value 0.
+#[inline]
+pub fn undetected(range_index: RangeCode) -> f32 {
+    range_index.filter_delay()
+}
+
+/// Check whether the **Profile Range** encodes a detected profile point, a raw value above 0.
+#[inline]
+pub fn detected(profile_range: f32, range_index: RangeCode) -> bool {
+    raw(profile_range, range_index) > 0
+}
+
+/// The raw u14 value of the **Profile Range**, saturating at 0 below the filter delay.
+fn raw(profile_range: f32, range_index: RangeCode) -> u16 {
+    ((profile_range - range_index.filter_delay()) / SCALE).round() as u16
+}
+
 #[parser(reader, endian)]
 pub fn parse(range_index: RangeCode) -> BinResult<f32> {
     let value = u14::parse(reader, endian, ())?;
-    let profile_range = (value as f32 * SCALE).floor() + range_index.filter_delay();
+    let profile_range = value as f32 * SCALE + range_index.filter_delay();
     Ok(profile_range)
 }
 
 #[writer(writer, endian)]
 pub fn write(profile_range: &f32, range_index: &RangeCode) -> BinResult<()> {
-    let value = ((*profile_range - range_index.filter_delay()) / SCALE).floor() as u16;
+    let value = raw(*profile_range, *range_index);
     u14::write(&value, writer, endian, ())?;
     Ok(())
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-//     use crate::types::primitive::u14;
-//     use crate::types::RangeIndex;
-//     use binrw::{io::Cursor, BinRead, BinWrite, Endian};
-//     use std::ops::Range;
-//
-//     use log::info;
-//     use test_log::test;
-//
-//     const BINARY_ENDIAN: Endian = Endian::Little;
-//     const BINARY_CASES: [(f32, [u8; 2], RangeIndex); 3] =
-//         [
-//             (0.0, [0x00, 0x00], RangeIndex::X25cm),
-//             (16.383, [0b0111_1111, 0b0111_1111], RangeIndex::X2m),
-//             (0.682625, [0b0010_1010, 0b0101_0101],RangeIndex::X12_5cm)
-//         ];
-//
-//     #[test]
-//     fn test_parse() {
-//         for &(want, bytes, range_index) in BINARY_CASES.iter() {
-//             info!("Parsing {bytes:?} with RangeIndex {range_index:?}, want {want:?}");
-//             let mut cursor = Cursor::new(bytes);
-//             let got = parse(&mut cursor, BINARY_ENDIAN, (range_index,))
-//                 .expect("It should not return an error");
-//             assert!((got - want).abs() < f32::EPSILON);
-//         }
-//     }
-//
-//     #[test]
-//     fn test_write() {
-//         for (profile_range, want, range_index) in BINARY_CASES.iter() {
-//             info!("Writing {profile_range:?} with RangeIndex {range_index:?}, want {want:?}");
-//             let mut cursor = Cursor::new(Vec::new());
-//             write(&profile_range, &mut cursor, BINARY_ENDIAN, (range_index,))
-//                 .expect("It should not return an error");
-//             let inner = cursor.into_inner();
-//             let got = inner.as_slice();
-//             assert_eq!(want, got);
-//         }
-//     }
-// }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binrw::{io::Cursor, Endian};
+
+    use log::info;
+    use test_log::test;
+
+    const BINARY_ENDIAN: Endian = Endian::Big;
+    const BINARY_CASES: [(f32, [u8; 2], RangeCode); 4] = [
+        (0.020, [0x00, 0x00], RangeCode::X1m),
+        (0.0205, [0x01, 0x00], RangeCode::X1m),
+        (8.2155, [0b0111_1111, 0b0111_1111], RangeCode::X2m),
+        (5.477, [0b0010_1010, 0b0101_0101], RangeCode::X0_25m),
+    ];
+
+    #[test]
+    fn test_parse() {
+        for &(want, bytes, range_code) in BINARY_CASES.iter() {
+            info!("Parsing {bytes:?} at {range_code:?}, want {want:?}");
+            let mut cursor = Cursor::new(bytes);
+            let got = parse(&mut cursor, BINARY_ENDIAN, (range_code,))
+                .expect("It should not return an error");
+            assert!((want - got).abs() < 1e-4, "{want} != {got}");
+        }
+    }
+
+    #[test]
+    fn test_write() {
+        for &(profile_range, want, range_code) in BINARY_CASES.iter() {
+            info!("Writing {profile_range:?} at {range_code:?}, want {want:?}");
+            let mut cursor = Cursor::new(Vec::new());
+            write(&profile_range, &mut cursor, BINARY_ENDIAN, (&range_code,))
+                .expect("It should not return an error");
+            assert_eq!(want, cursor.into_inner().as_slice());
+        }
+    }
+
+    #[test]
+    fn test_write_rounds() {
+        let cases = vec![(0.02049, 1), (0.02024, 0), (0.02074, 1), (1.00001, 1960)];
+
+        for (profile_range, want) in cases {
+            info!("Writing {profile_range:?}, want {want:?}");
+            let mut cursor = Cursor::new(Vec::new());
+            write(&profile_range, &mut cursor, BINARY_ENDIAN, (&RangeCode::X1m,))
+                .expect("It should not return an error");
+            let bytes = cursor.into_inner();
+            assert_eq!(want, u14::from_bytes([bytes[0], bytes[1]]));
+        }
+    }
+
+    #[test]
+    fn test_detected() {
+        let cases = vec![
+            (undetected(RangeCode::X1m), RangeCode::X1m, false),
+            (undetected(RangeCode::X6m), RangeCode::X6m, false),
+            (0.0, RangeCode::X1m, false),
+            (0.02024, RangeCode::X1m, false),
+            (0.0205, RangeCode::X1m, true),
+            (0.5, RangeCode::X0_25m, true),
+        ];
+
+        for (profile_range, range_code, want) in cases {
+            info!("Checking {profile_range:?} at {range_code:?} for a detection, want {want:?}");
+            assert_eq!(want, detected(profile_range, range_code));
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for value in (0..=u14::MAX).step_by(97) {
+            let bytes = u14::to_bytes(value);
+            let profile_range = parse(&mut Cursor::new(bytes), BINARY_ENDIAN, (RangeCode::X6m,))
+                .expect("It should not return an error");
+
+            let mut cursor = Cursor::new(Vec::new());
+            write(&profile_range, &mut cursor, BINARY_ENDIAN, (&RangeCode::X6m,))
+                .expect("It should not return an error");
+            assert_eq!(bytes, cursor.into_inner().as_slice(), "{value} lost in {profile_range}");
+        }
+    }
+}
@@ -1,23 +1,28 @@
 //! Utilities for the **Pulse Length** primitive, the length of an acoustic transmit pulse.
-use binrw::{parser, writer, BinRead, BinResult, BinWrite, Error};
-use const_format::concatcp;
+use crate::error::OutOfRangeError;
+use crate::DecodeMode;
+use binrw::{parser, writer, BinRead, BinResult, BinWrite};
 
 pub(crate) const MAX: u16 = 1_000;
-const ERR_MESSAGE_RANGE: &str = concatcp!("pulse length exceeds maximum of ", MAX, " μs");
 
 #[inline]
 pub fn valid(pulse_length: u16) -> bool {
     pulse_length <= MAX
 }
 
+#[inline]
+pub(crate) fn out_of_range(pulse_length: u16) -> OutOfRangeError {
+    OutOfRangeError::new("Pulse length", pulse_length, 0, MAX, " μs")
+}
+
 #[parser(reader)]
-pub fn parse() -> BinResult<u16> {
+pub fn parse(mode: DecodeMode) -> BinResult<u16> {
     let raw = u8::read(reader)?;
     let pulse_length = raw as u16 * 10;
 
-    if !valid(pulse_length) {
+    if !valid(pulse_length) && mode.is_strict() {
         let pos = reader.stream_position()?;
-        return Err(Error::AssertFail { pos, message: ERR_MESSAGE_RANGE.to_string() });
+        return Err(out_of_range(pulse_length).at(pos));
     }
 
     Ok(pulse_length)
@@ -27,7 +32,7 @@ pub fn parse() -> BinResult<u16> {
 pub fn write(pulse_length: &u16) -> BinResult<()> {
     if !valid(*pulse_length) {
         let pos = writer.stream_position()?;
-        return Err(Error::AssertFail { pos, message: ERR_MESSAGE_RANGE.to_string() });
+        return Err(out_of_range(*pulse_length).at(pos));
     }
 
     let raw = (*pulse_length / 10) as u8;
@@ -38,7 +43,7 @@ pub fn write(pulse_length: &u16) -> BinResult<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use binrw::{io::Cursor, Endian};
+    use binrw::{io::Cursor, Endian, Error};
 
     use log::info;
     use test_log::test;
@@ -66,7 +71,8 @@ mod tests {
         for &(want, bytes) in BINARY_CASES.iter() {
             info!("Parsing {bytes:?}, want {want:?}");
             let mut cursor = Cursor::new(bytes);
-            let got = parse(&mut cursor, BINARY_ENDIAN, ()).expect("It should not return an error");
+            let got = parse(&mut cursor, BINARY_ENDIAN, (DecodeMode::Strict,))
+                .expect("It should not return an error");
             assert_eq!(want, got);
         }
     }
@@ -81,8 +87,21 @@ mod tests {
         for bytes in cases.iter() {
             info!("Parsing {bytes:?}, want error");
             let mut cursor = Cursor::new(bytes);
-            let error = parse(&mut cursor, BINARY_ENDIAN, ()).unwrap_err();
-            assert!(matches!(error, Error::AssertFail { .. }));
+            let error = parse(&mut cursor, BINARY_ENDIAN, (DecodeMode::Strict,)).unwrap_err();
+            assert!(matches!(error, Error::Custom { .. }));
+        }
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        let cases = [(1_010, [0x65]), (2_550, [0xFF])];
+
+        for (want, bytes) in cases {
+            info!("Parsing {bytes:?} leniently, want {want:?}");
+            let mut cursor = Cursor::new(bytes);
+            let got = parse(&mut cursor, BINARY_ENDIAN, (DecodeMode::Lenient,))
+                .expect("It should not return an error");
+            assert_eq!(want, got);
         }
     }
 
@@ -110,7 +129,7 @@ mod tests {
             info!("Writing {pulse_length:?}, want error");
             let mut cursor = Cursor::new(Vec::new());
             let error = write(pulse_length, &mut cursor, BINARY_ENDIAN, ()).unwrap_err();
-            assert!(matches!(error, Error::AssertFail { .. }));
+            assert!(matches!(error, Error::Custom { .. }));
         }
     }
 }
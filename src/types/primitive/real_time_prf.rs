@@ -1,6 +1,7 @@
 //! Utilities for the **Real-Time PRF**/**Real-Time Repetition Rate** primitive,
 //! the rate at which sonar transmits pulses into the water measured in Hz.
-use binrw::{parser, writer, BinRead, BinResult, BinWrite, Error};
+use crate::error::OutOfRangeError;
+use binrw::{parser, writer, BinRead, BinResult, BinWrite};
 
 pub(crate) const MIN: f32 = 0.0;
 pub(crate) const MAX: f32 = 327.67;
@@ -12,6 +13,11 @@ pub fn valid(real_time_prf: f32) -> bool {
     (MIN..=MAX).contains(&real_time_prf)
 }
 
+#[inline]
+pub(crate) fn out_of_range(real_time_prf: f32) -> OutOfRangeError {
+    OutOfRangeError::new("Real-time PRF", real_time_prf, MIN, MAX, " Hz")
+}
+
 /// Parse a **Real-Time PRF** from two bytes.
 #[parser(reader, endian)]
 pub fn parse() -> BinResult<f32> {
@@ -25,10 +31,7 @@ pub fn parse() -> BinResult<f32> {
 pub fn write(real_time_prf: &f32) -> BinResult<()> {
     if !valid(*real_time_prf) {
         let pos = writer.stream_position()?;
-        return Err(Error::AssertFail {
-            pos,
-            message: "Real-Time PRF exceeds range of 0.0 to 327.67 Hz".to_string(),
-        });
+        return Err(out_of_range(*real_time_prf).at(pos));
     }
 
     let raw = (*real_time_prf * 100.0).round() as u16 & MASK;
@@ -42,7 +45,7 @@ mod tests {
     use log::info;
     use test_log::test;
 
-    use binrw::{io::Cursor, Endian};
+    use binrw::{io::Cursor, Endian, Error};
 
     #[test]
     fn test_valid() {
@@ -95,7 +98,7 @@ mod tests {
         for prf in cases.iter() {
             let mut buffer = Cursor::new(Vec::new());
             let error = write(prf, &mut buffer, BINARY_ENDIAN, ()).unwrap_err();
-            assert!(matches!(error, Error::AssertFail { .. }));
+            assert!(matches!(error, Error::Custom { .. }));
         }
     }
 }
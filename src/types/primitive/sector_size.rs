@@ -1,23 +1,28 @@
 //! Utilities for the **Sector Size** primitive. *Ignored in Fixed-transducer sonars*.
-use binrw::{parser, writer, BinRead, BinResult, BinWrite, Error};
-use const_format::concatcp;
+use crate::error::OutOfRangeError;
+use crate::DecodeMode;
+use binrw::{parser, writer, BinRead, BinResult, BinWrite};
 
 pub(crate) const MAX: u16 = 360;
-const ERR_RANGE: &str = concatcp!("sector size exceeds maximum of ", MAX);
 
 #[inline]
 pub fn valid(sector_size: u16) -> bool {
     sector_size <= MAX
 }
 
+#[inline]
+pub(crate) fn out_of_range(sector_size: u16) -> OutOfRangeError {
+    OutOfRangeError::new("Sector size", sector_size, 0, MAX, "°")
+}
+
 #[parser(reader)]
-pub fn parse() -> BinResult<u16> {
+pub fn parse(mode: DecodeMode) -> BinResult<u16> {
     let raw = u8::read(reader)?;
     let sector_size = raw as u16 * 3;
 
-    if !valid(sector_size) {
+    if !valid(sector_size) && mode.is_strict() {
         let pos = reader.stream_position()?;
-        return Err(Error::AssertFail { pos, message: ERR_RANGE.to_string() });
+        return Err(out_of_range(sector_size).at(pos));
     }
 
     Ok(sector_size)
@@ -27,7 +32,7 @@ pub fn parse() -> BinResult<u16> {
 pub fn write(sector_size: &u16) -> BinResult<()> {
     if !valid(*sector_size) {
         let pos = writer.stream_position()?;
-        return Err(Error::AssertFail { pos, message: ERR_RANGE.to_string() });
+        return Err(out_of_range(*sector_size).at(pos));
     }
 
     let raw = (*sector_size / 3) as u8;
@@ -63,7 +68,8 @@ mod tests {
         for (want, bytes) in BINARY_CASES {
             info!("Parsing {bytes:?}, want {want:?}");
             let mut cursor = Cursor::new(bytes);
-            let got = parse(&mut cursor, BINARY_ENDIAN, ()).expect("It should not return an error");
+            let got = parse(&mut cursor, BINARY_ENDIAN, (DecodeMode::Strict,))
+                .expect("It should not return an error");
             assert_eq!(want, got);
         }
     }
@@ -71,10 +77,18 @@ mod tests {
     #[test]
     fn test_parse_invalid() {
         let mut cursor = Cursor::new([122u8]);
-        let got = parse(&mut cursor, BINARY_ENDIAN, ());
+        let got = parse(&mut cursor, BINARY_ENDIAN, (DecodeMode::Strict,));
         assert!(got.is_err());
     }
 
+    #[test]
+    fn test_parse_lenient() {
+        let mut cursor = Cursor::new([122u8]);
+        let got = parse(&mut cursor, BINARY_ENDIAN, (DecodeMode::Lenient,))
+            .expect("It should not return an error");
+        assert_eq!(366, got);
+    }
+
     #[test]
     fn test_write() {
         for (sector_size, want) in BINARY_CASES {
@@ -1,6 +1,7 @@
 //! Utilities for the **Sound Velocity** primitive, the speed at which sound is traveling through
 //! the water.
-use binrw::{parser, writer, BinRead, BinResult, BinWrite, Error};
+use crate::error::OutOfRangeError;
+use binrw::{parser, writer, BinRead, BinResult, BinWrite};
 
 pub(crate) const MIN: f32 = 0.0;
 pub(crate) const MAX: f32 = 3276.7;
@@ -15,6 +16,31 @@ pub fn valid(sound_velocity: f32) -> bool {
     (MIN..=MAX).contains(&sound_velocity)
 }
 
+/// The speed of sound in water at `temperature` °C, `salinity` parts per thousand and `depth`
+/// meters, per the equation of Coppens (1981).
+///
+/// The equation holds from 0 to 35 °C, 0 to 45 ppt and 0 to 4000 m, covering fresh, brackish and
+/// sea water.
+pub fn coppens(temperature: f32, salinity: f32, depth: f32) -> f32 {
+    let t = temperature as f64 / 10.0;
+    let s = salinity as f64 - 35.0;
+    let d = depth as f64 / 1000.0;
+
+    let surface = 1449.05 + 45.7 * t - 5.21 * t.powi(2)
+        + 0.23 * t.powi(3)
+        + (1.333 - 0.126 * t + 0.009 * t.powi(2)) * s;
+    let velocity = surface
+        + (16.23 + 0.253 * t) * d
+        + (0.213 - 0.1 * t) * d.powi(2)
+        + (0.016 + 0.0002 * s) * s * t * d;
+    velocity as f32
+}
+
+#[inline]
+pub(crate) fn out_of_range(sound_velocity: f32) -> OutOfRangeError {
+    OutOfRangeError::new("Sound velocity", sound_velocity, MIN, MAX, " m/s")
+}
+
 /// Parse **Sound Velocity** from two bytes.
 #[parser(reader, endian)]
 pub fn parse() -> BinResult<f32> {
@@ -33,10 +59,7 @@ pub fn parse() -> BinResult<f32> {
 pub fn write(sound_velocity: &f32) -> BinResult<()> {
     if !valid(*sound_velocity) {
         let pos = writer.stream_position()?;
-        return Err(Error::AssertFail {
-            pos,
-            message: "Sound Velocity exceeds range of 0.0 to 3,276.7 m/s".to_string(),
-        });
+        return Err(out_of_range(*sound_velocity).at(pos));
     }
 
     if *sound_velocity == V_VALUE {
@@ -44,7 +67,7 @@ pub fn write(sound_velocity: &f32) -> BinResult<()> {
         return Ok(());
     }
 
-    let raw = (*sound_velocity * 10.0) as u16 & MASK;
+    let raw = (*sound_velocity * 10.0).round() as u16 & MASK;
     raw.write_options(writer, endian, ())?;
     Ok(())
 }
@@ -52,15 +75,14 @@ pub fn write(sound_velocity: &f32) -> BinResult<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use binrw::{io::Cursor, Endian};
+    use binrw::{io::Cursor, Endian, Error};
 
     use log::info;
     use test_log::test;
 
     #[test]
     fn test_valid() {
-        let cases =
-            vec![(0.0, true), (1500.0, true), (3276.7, true), (-1.0, false), (3276.8, false)];
+        let cases = [(0.0, true), (1500.0, true), (3276.7, true), (-1.0, false), (3276.8, false)];
 
         for &(sound_velocity, want) in cases.iter() {
             info!("Testing validity of {sound_velocity:?}, want {want:?}");
@@ -92,7 +114,7 @@ mod tests {
         for (sound_velocity, want) in BINARY_CASES.iter() {
             info!("Writing {sound_velocity:?}, want {want:?}");
             let mut cursor = Cursor::new(Vec::new());
-            write(&sound_velocity, &mut cursor, BINARY_ENDIAN, ())
+            write(sound_velocity, &mut cursor, BINARY_ENDIAN, ())
                 .expect("It should not return an error");
             let inner = cursor.into_inner();
             let got = inner.as_slice();
@@ -100,6 +122,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_coppens() {
+        let cases = [
+            (20.0, 0.0, 0.0, 1482.36),  // fresh water
+            (0.0, 35.0, 0.0, 1449.05),  // cold sea water
+            (25.0, 35.0, 0.0, 1534.33), // warm sea water
+            (10.0, 10.0, 0.0, 1459.37), // brackish water
+            (10.0, 35.0, 1000.0, 1506.37),
+        ];
+
+        for (temperature, salinity, depth, want) in cases {
+            info!("Getting the sound velocity at {temperature} °C, {salinity} ppt, {depth} m, want {want:?}");
+            let got = coppens(temperature, salinity, depth);
+            assert!((want - got).abs() < 0.01, "got {got}");
+        }
+    }
+
     #[test]
     fn test_write_invalid() {
         let cases = [
@@ -111,7 +150,7 @@ mod tests {
             info!("Writing {sound_velocity:?}, want error");
             let mut cursor = Cursor::new(Vec::new());
             let error = write(sound_velocity, &mut cursor, BINARY_ENDIAN, ()).unwrap_err();
-            assert!(matches!(error, Error::AssertFail { .. }));
+            assert!(matches!(error, Error::Custom { .. }));
         }
     }
 }
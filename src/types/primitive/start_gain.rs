@@ -1,7 +1,9 @@
 //! Utilities for the **Start Gain** primitive, the initial amplification applied to the received
 //! echo signal at the beginning of its travel through the sonar receiver.
+use crate::error::OutOfRangeError;
+use crate::DecodeMode;
 use crate::ENDIAN;
-use binrw::{parser, writer, BinRead, BinResult, BinWrite, Error};
+use binrw::{parser, writer, BinRead, BinResult, BinWrite};
 
 pub(crate) const MAX: u8 = 40;
 
@@ -11,17 +13,19 @@ pub fn valid(value: u8) -> bool {
     value <= MAX
 }
 
+#[inline]
+pub(crate) fn out_of_range(start_gain: u8) -> OutOfRangeError {
+    OutOfRangeError::new("Start gain", start_gain, 0, MAX, " dB")
+}
+
 /// Parse **Start Gain** from a byte.
 #[parser(reader)]
-pub fn parse() -> BinResult<u8> {
+pub fn parse(mode: DecodeMode) -> BinResult<u8> {
     let start_gain = u8::read_options(reader, ENDIAN, ())?;
 
-    if !valid(start_gain) {
+    if !valid(start_gain) && mode.is_strict() {
         let pos = reader.stream_position()?;
-        return Err(Error::AssertFail {
-            pos,
-            message: "Start gain exceeds range of 0 to 40 dB".to_string(),
-        });
+        return Err(out_of_range(start_gain).at(pos));
     }
 
     Ok(start_gain)
@@ -32,10 +36,7 @@ pub fn parse() -> BinResult<u8> {
 pub fn write(start_gain: &u8) -> BinResult<()> {
     if !valid(*start_gain) {
         let pos = writer.stream_position()?;
-        return Err(Error::AssertFail {
-            pos,
-            message: "Start gain exceeds range of 0 to 40 dB".to_string(),
-        });
+        return Err(out_of_range(*start_gain).at(pos));
     }
 
     (*start_gain).write_options(writer, ENDIAN, ())?;
@@ -69,7 +70,20 @@ mod tests {
         for (want, bytes) in BINARY_CASES {
             info!("Parsing {bytes:?}, expecting {want:?}");
             let mut cursor = Cursor::new(bytes);
-            let got = parse(&mut cursor, ENDIAN, ()).expect("Should succeed");
+            let got = parse(&mut cursor, ENDIAN, (DecodeMode::Strict,)).expect("Should succeed");
+            assert_eq!(want, got);
+        }
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        let cases = [(41, [41]), (255, [255])];
+
+        for (want, bytes) in cases {
+            info!("Parsing {bytes:?} leniently, want {want:?}");
+            let mut cursor = Cursor::new(bytes);
+            let got = parse(&mut cursor, ENDIAN, (DecodeMode::Lenient,))
+                .expect("It should not return an error");
             assert_eq!(want, got);
         }
     }
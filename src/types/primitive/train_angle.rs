@@ -1,7 +1,9 @@
 //! Utilities for the **Train Angle** primitive, the orientation of direction in which the sonar
 //! transducer is pointed relative to the platform's fixed baseline.
 //! *Ignored in Fixed-transducer sonars*.
-use binrw::{parser, writer, BinRead, BinResult, BinWrite, Error};
+use crate::error::OutOfRangeError;
+use crate::DecodeMode;
+use binrw::{parser, writer, BinRead, BinResult, BinWrite};
 
 pub(crate) const MAX: u16 = 360;
 
@@ -11,18 +13,20 @@ pub fn valid(value: u16) -> bool {
     value <= MAX
 }
 
+#[inline]
+pub(crate) fn out_of_range(train_angle: u16) -> OutOfRangeError {
+    OutOfRangeError::new("Train angle", train_angle, 0, MAX, "°")
+}
+
 /// Parse the **Train Angle** from two bytes.
 #[parser(reader)]
-pub fn parse() -> BinResult<u16> {
+pub fn parse(mode: DecodeMode) -> BinResult<u16> {
     let raw = u8::read(reader)?;
     let train_angle = raw as u16 * 3;
 
-    if !valid(train_angle) {
+    if !valid(train_angle) && mode.is_strict() {
         let pos = reader.stream_position()?;
-        return Err(Error::AssertFail {
-            pos,
-            message: "Train Angle exceeds range of 0 to 360°".to_string(),
-        });
+        return Err(out_of_range(train_angle).at(pos));
     }
 
     Ok(train_angle)
@@ -33,10 +37,7 @@ pub fn parse() -> BinResult<u16> {
 pub fn write(train_angle: &u16) -> BinResult<()> {
     if !valid(*train_angle) {
         let pos = writer.stream_position()?;
-        return Err(Error::AssertFail {
-            pos,
-            message: "Train Angle exceeds range of 0 to 360°".to_string(),
-        });
+        return Err(out_of_range(*train_angle).at(pos));
     }
 
     let raw = (*train_angle / 3) as u8;
@@ -48,7 +49,7 @@ pub fn write(train_angle: &u16) -> BinResult<()> {
 mod tests {
     use super::*;
     use crate::ENDIAN;
-    use binrw::io::Cursor;
+    use binrw::{io::Cursor, Error};
 
     use log::info;
     use test_log::test;
@@ -76,7 +77,7 @@ mod tests {
         for &(want, bytes) in BINARY_CASES.iter() {
             info!("Parsing {bytes:?}, want {want:?}");
             let mut cursor = Cursor::new(bytes);
-            let got = parse(&mut cursor, ENDIAN, ()).expect("Should succeed");
+            let got = parse(&mut cursor, ENDIAN, (DecodeMode::Strict,)).expect("Should succeed");
             assert_eq!(want, got);
         }
     }
@@ -91,8 +92,21 @@ mod tests {
         for bytes in cases.iter() {
             info!("Parsing {bytes:?}, want error");
             let mut cursor = Cursor::new(bytes);
-            let error = parse(&mut cursor, ENDIAN, ()).unwrap_err();
-            assert!(matches!(error, Error::AssertFail { .. }));
+            let error = parse(&mut cursor, ENDIAN, (DecodeMode::Strict,)).unwrap_err();
+            assert!(matches!(error, Error::Custom { .. }));
+        }
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        let cases = [(363, [0x79]), (765, [0xFF])];
+
+        for (want, bytes) in cases {
+            info!("Parsing {bytes:?} leniently, want {want:?}");
+            let mut cursor = Cursor::new(bytes);
+            let got = parse(&mut cursor, ENDIAN, (DecodeMode::Lenient,))
+                .expect("It should not return an error");
+            assert_eq!(want, got);
         }
     }
 
@@ -101,7 +115,7 @@ mod tests {
         for (train_angle, want) in BINARY_CASES.iter() {
             info!("Writing {train_angle:?}, want {want:?}");
             let mut cursor = Cursor::new(Vec::new());
-            write(&train_angle, &mut cursor, ENDIAN, ()).expect("It should not return an error");
+            write(train_angle, &mut cursor, ENDIAN, ()).expect("It should not return an error");
             let inner = cursor.into_inner();
             let got = inner.as_slice();
             assert_eq!(want, got);
@@ -119,7 +133,7 @@ mod tests {
             info!("Writing {train_angle:?}, want error");
             let mut cursor = Cursor::new(Vec::new());
             let error = write(train_angle, &mut cursor, ENDIAN, ()).unwrap_err();
-            assert!(matches!(error, Error::AssertFail { .. }));
+            assert!(matches!(error, Error::Custom { .. }));
         }
     }
 }
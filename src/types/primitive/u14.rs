@@ -21,9 +21,8 @@
 //!     <td><em>u14 LOW BIT 0</em></td>
 //! </tr>
 //! </table>
-use binrw::{parser, writer, BinRead, BinResult, BinWrite, Error};
-use const_format::concatcp;
-use std::io::{Error as IOError, ErrorKind::InvalidData};
+use crate::error::OutOfRangeError;
+use binrw::{parser, writer, BinRead, BinResult, BinWrite};
 
 pub(crate) const MAX: u16 = 0b0011_1111_1111_1111;
 
@@ -35,8 +34,6 @@ const SHIFT_HIGH_L: usize = 7;
 
 const MASK_LOW: u8 = 0b0111_1111;
 
-const ERR_MESSAGE_RANGE: &str = concatcp!("u14 exceeds maximum of ", MAX);
-
 /// Validate if the provided value can fit in a `u14`.
 #[inline]
 pub fn valid(value: u16) -> bool {
@@ -47,16 +44,16 @@ pub fn valid(value: u16) -> bool {
 #[parser(reader, endian)]
 pub fn parse() -> BinResult<u16> {
     let raw = u16::read_options(reader, endian, ())?;
+    Ok(from_bytes([(raw >> 8) as u8, (raw & 0xFF) as u8]))
+}
 
-    // extract the high and low parts
-    let high = (raw & 0xFF) as u8;
-    let low = (raw >> 8) as u8;
-
+/// Assemble a `u14` from its low and high bytes, in wire order.
+#[inline]
+pub(crate) fn from_bytes([low, high]: [u8; 2]) -> u16 {
     let high_part = (high & MASK_HIGH) >> SHIFT_HIGH;
     let low_part = (low & MASK_LOW) | ((high & MASK_HIGH_L) << SHIFT_HIGH_L);
 
-    let value = ((high_part as u16) << 8) | (low_part as u16);
-    Ok(value)
+    ((high_part as u16) << 8) | (low_part as u16)
 }
 
 /// Write an 831L-formatted `u14` to two bytes.
@@ -64,26 +61,30 @@ pub fn parse() -> BinResult<u16> {
 pub fn write(u14: &u16) -> BinResult<()> {
     if !valid(*u14) {
         let pos = writer.stream_position()?;
-        return Err(Error::Custom {
-            pos,
-            err: Box::new(IOError::new(InvalidData, ERR_MESSAGE_RANGE)),
-        });
+        return Err(OutOfRangeError::new("u14", *u14, 0, MAX, "").at(pos));
     }
 
-    let high = (*u14 >> 8) as u8;
-    let low = (*u14 & 0xFF) as u8;
+    let [low, high] = to_bytes(*u14);
+    let raw = ((low as u16) << 8) | (high as u16);
+    raw.write_options(writer, endian, ())
+}
+
+/// Split a `u14` into its low and high bytes, in wire order.
+#[inline]
+pub(crate) fn to_bytes(u14: u16) -> [u8; 2] {
+    let high = (u14 >> 8) as u8;
+    let low = (u14 & 0xFF) as u8;
 
     let high_part = ((high << SHIFT_HIGH) & MASK_HIGH) | ((low >> SHIFT_HIGH_L) & MASK_HIGH_L);
     let low_part = low & MASK_LOW;
 
-    let raw = ((low_part as u16) << 8) | (high_part as u16);
-    raw.write_options(writer, endian, ())
+    [low_part, high_part]
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use binrw::{io::Cursor, BinRead, BinWrite, Endian};
+    use binrw::{io::Cursor, Endian, Error};
 
     use log::info;
     use test_log::test;
@@ -121,7 +122,7 @@ mod tests {
         for (u14_value, want) in BINARY_CASES.iter() {
             info!("Writing {u14_value:?}, want {want:?}");
             let mut cursor = Cursor::new(Vec::new());
-            write(&u14_value, &mut cursor, BINARY_ENDIAN, ())
+            write(u14_value, &mut cursor, BINARY_ENDIAN, ())
                 .expect("It should not return an error");
             let inner = cursor.into_inner();
             let got = inner.as_slice();
@@ -1,4 +1,4 @@
-use crate::types::{range_table, RangeTable};
+use crate::types::{primitive::sound_velocity, range_table, RangeTable};
 use binrw::{BinRead, BinWrite};
 use num_derive::{FromPrimitive, ToPrimitive};
 use std::fmt::{Display, Formatter};
@@ -103,6 +103,23 @@ impl RangeCode {
             Self::X4m | Self::X5m | Self::X6m => 0.030,
         }
     }
+
+    /// The [RangeCode::table] corrected for the [RangeCode::filter_delay] and scaled from the
+    /// 1500 m/s the tables assume to `sound_velocity` in m/s.
+    ///
+    /// The filter delay is added as in the
+    /// [profile range](crate::types::primitive::profile_range), so that the echo of a target
+    /// peaks at its decoded profile range. A `sound_velocity` that is not positive is taken as
+    /// 1500 m/s.
+    pub fn ranges(&self, sound_velocity: f32) -> RangeTable {
+        let scale = match sound_velocity > 0.0 {
+            true => sound_velocity / sound_velocity::V_VALUE,
+            false => 1.0,
+        };
+
+        let delay = self.filter_delay();
+        self.table().map(|range| (range + delay) * scale)
+    }
 }
 
 impl Display for RangeCode {
@@ -137,6 +154,11 @@ impl RangeCode {
     pub(crate) fn py_filter_delay(&self) -> f32 {
         self.filter_delay()
     }
+
+    #[pyo3(name = "ranges")]
+    pub(crate) fn py_ranges(&self, sound_velocity: f32) -> RangeTable {
+        self.ranges(sound_velocity)
+    }
 }
 
 #[cfg(test)]
@@ -212,6 +234,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn ranges() {
+        let cases = vec![
+            (RangeCode::X1m, 1500.0, [(0, 0.024), (4, 0.04), (5, 0.044), (249, 1.02)]),
+            (RangeCode::X6m, 1500.0, [(0, 0.054), (1, 0.078), (2, 0.102), (249, 6.03)]),
+            (RangeCode::X1m, 1450.0, [(0, 0.0232), (5, 0.042_533), (124, 0.502_667), (249, 0.986)]),
+            (RangeCode::X1m, 0.0, [(0, 0.024), (4, 0.04), (5, 0.044), (249, 1.02)]),
+        ];
+
+        for (range, sound_velocity, want) in cases {
+            info!("Getting ranges for {range:?} at {sound_velocity} m/s, want {want:?}");
+            let got = range.ranges(sound_velocity);
+            for (i, want) in want {
+                assert!((want - got[i]).abs() < 1e-5, "range {i}: want {want}, got {}", got[i]);
+            }
+        }
+    }
+
     #[test]
     fn display() {
         let cases = vec![
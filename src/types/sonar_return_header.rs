@@ -7,7 +7,8 @@ use binrw::{BinRead, BinWrite};
 #[cfg(feature = "pyo3")]
 use pyo3::prelude::*;
 
-#[derive(Debug, BinRead, BinWrite, PartialEq, Clone)]
+#[allow(clippy::too_many_arguments)] // the derived constructor takes every recorded field
+#[derive(Debug, BinRead, BinWrite, PartialEq, Clone, derive_new::new)]
 #[cfg_attr(
     target_family = "wasm",
     derive(tsify::Tsify, serde::Serialize, serde::Deserialize),
@@ -72,16 +73,25 @@ pub struct SonarReturnHeader {
     #[cfg(not(feature = "pyo3"))]
     #[br(parse_with = u14::parse)]
     #[bw(write_with = u14::write)]
-    #[brw(pad_after = 4)]
     pub data_length: u16,
 
     #[cfg(feature = "pyo3")]
     #[pyo3(get, set)]
     #[br(parse_with = u14::parse)]
     #[bw(write_with = u14::write)]
-    #[brw(pad_after = 4)]
     pub data_length: u16,
 
+    /// Reserved bytes 12 to 15.
+    #[cfg(not(feature = "pyo3"))]
+    #[new(default)]
+    reserved_12: [u8; 4],
+
+    /// Reserved bytes 12 to 15.
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    #[new(default)]
+    reserved_12: [u8; 4],
+
     #[cfg(not(feature = "pyo3"))]
     pub roll_angle: Angle,
 
@@ -104,11 +114,51 @@ pub struct SonarReturnHeader {
     pub roll_acceleration: Acceleration,
 
     #[cfg(not(feature = "pyo3"))]
-    #[brw(pad_after = 8)]
     pub pitch_acceleration: Acceleration,
 
     #[cfg(feature = "pyo3")]
     #[pyo3(get, set)]
-    #[brw(pad_after = 8)]
     pub pitch_acceleration: Acceleration,
+
+    /// Reserved bytes 24 to 31.
+    #[cfg(not(feature = "pyo3"))]
+    #[new(default)]
+    reserved_24: [u8; 8],
+
+    /// Reserved bytes 24 to 31.
+    #[cfg(feature = "pyo3")]
+    #[pyo3(get, set)]
+    #[new(default)]
+    reserved_24: [u8; 8],
+}
+
+impl SonarReturnHeader {
+    /// Whether the head detected a profile point, which it marks with a raw
+    /// [profile range](profile_range) above 0.
+    #[inline]
+    pub fn profile_detected(&self) -> bool {
+        profile_range::detected(self.profile_range, self.range_code)
+    }
+
+    /// The raw reserved bytes 12 to 15, preserved as recorded.
+    #[inline]
+    pub fn reserved_12(&self) -> &[u8; 4] {
+        &self.reserved_12
+    }
+
+    #[inline]
+    pub fn reserved_12_mut(&mut self) -> &mut [u8; 4] {
+        &mut self.reserved_12
+    }
+
+    /// The raw reserved bytes 24 to 31, preserved as recorded.
+    #[inline]
+    pub fn reserved_24(&self) -> &[u8; 8] {
+        &self.reserved_24
+    }
+
+    #[inline]
+    pub fn reserved_24_mut(&mut self) -> &mut [u8; 8] {
+        &mut self.reserved_24
+    }
 }
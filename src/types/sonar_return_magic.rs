@@ -1,7 +1,7 @@
 use binrw::meta::{EndianKind, ReadEndian, WriteEndian};
 use binrw::{BinRead, BinResult, BinWrite, Endian, Error};
 use std::fmt::{Display, Formatter};
-use std::io::{Error as IOError, ErrorKind::InvalidData, Read, Seek, Write};
+use std::io::{Read, Seek, Write};
 
 #[cfg(feature = "pyo3")]
 use pyo3::prelude::*;
@@ -67,10 +67,7 @@ impl BinRead for SonarReturnMagic {
         match &raw {
             b"IMX" => Ok(Self::IMX),
             b"IPX" => Ok(Self::IPX),
-            _ => Err(Error::Custom {
-                pos,
-                err: Box::new(IOError::new(InvalidData, "Invalid return data magic")),
-            }),
+            _ => Err(Error::BadMagic { pos, found: Box::new(raw) }),
         }
     }
 
@@ -172,7 +169,7 @@ mod tests {
             info!("Parsing {bytes:?}, want error");
             let mut cursor = Cursor::new(bytes);
             let error = SonarReturnMagic::read_options(&mut cursor, BINARY_ENDIAN, ()).unwrap_err();
-            assert!(matches!(error, Error::Custom { .. }));
+            assert!(matches!(error, Error::BadMagic { .. }));
         }
     }
 
@@ -1,18 +1,17 @@
+use crate::error::InvalidDiscriminantError;
 use binrw::{BinResult, Error};
 use num_traits::{FromPrimitive, ToPrimitive};
 use std::any::type_name;
 
-const ERR_MESSAGE_INVALID_VALUE: &str = "invalid value for";
-
 const ERR_MESSAGE_CONVERT: &str = "failed to convert raw value for ";
 
 pub(crate) fn read_u8<T>(raw: u8, pos: u64) -> BinResult<T>
 where
     T: FromPrimitive,
 {
-    T::from_u8(raw).ok_or_else(|| Error::AssertFail {
-        pos,
-        message: format!("{} {}", ERR_MESSAGE_INVALID_VALUE, type_name::<T>().to_lowercase()),
+    T::from_u8(raw).ok_or_else(|| {
+        let type_name = type_name::<T>().rsplit("::").next().unwrap_or_default();
+        InvalidDiscriminantError { type_name, value: raw as u64 }.at(pos)
     })
 }
 
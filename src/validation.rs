@@ -0,0 +1,115 @@
+//! Strict and lenient decoding, and the [Warning]s produced by validating decoded values.
+use crate::error::{ErrorKind, OutOfRangeError};
+use binrw::{BinRead, BinWrite};
+use std::fmt;
+
+#[cfg(feature = "pyo3")]
+use pyo3::prelude::*;
+
+/// How values outside of their specified range are handled while decoding.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, BinRead, BinWrite)]
+#[repr(u8)]
+#[brw(repr = u8)]
+#[cfg_attr(
+    target_family = "wasm",
+    derive(tsify::Tsify, serde::Serialize, serde::Deserialize),
+    tsify(into_wasm_abi, from_wasm_abi)
+)]
+#[cfg_attr(
+    all(feature = "serde", not(target_family = "wasm")),
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[cfg_attr(feature = "pyo3", pyclass(eq, eq_int))]
+pub enum DecodeMode {
+    /// Fail to decode a shot with any value out of range.
+    #[default]
+    Strict = 0,
+
+    /// Keep out-of-range values as recorded, to be reported by validation.
+    ///
+    /// Structural errors, such as bad magic, invalid lengths or unknown enumeration values,
+    /// still fail. Shots decoded leniently may not be writable until their values are corrected.
+    Lenient = 1,
+}
+
+impl DecodeMode {
+    #[inline]
+    pub fn is_strict(&self) -> bool {
+        *self == Self::Strict
+    }
+
+    #[inline]
+    pub fn is_lenient(&self) -> bool {
+        *self == Self::Lenient
+    }
+}
+
+/// A value out of specification, found by validating a decoded shot.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "pyo3", pyclass)]
+pub struct Warning {
+    field: String,
+    kind: ErrorKind,
+    message: String,
+}
+
+impl Warning {
+    pub(crate) fn new(field: &str, kind: ErrorKind, message: String) -> Self {
+        Self { field: field.to_string(), kind, message }
+    }
+
+    pub(crate) fn out_of_range(field: &str, error: OutOfRangeError) -> Self {
+        let kind = ErrorKind::OutOfRange { value: error.value, min: error.min, max: error.max };
+        Self::new(field, kind, error.to_string())
+    }
+
+    /// Nest the [Warning::field] under `parent`.
+    pub(crate) fn within(mut self, parent: &str) -> Self {
+        self.field = format!("{parent}.{}", self.field);
+        self
+    }
+
+    /// The path of the field (e.g. `header.start_gain`).
+    #[inline]
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    #[inline]
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+#[cfg(feature = "pyo3")]
+#[pymethods]
+impl Warning {
+    #[getter(field)]
+    pub(crate) fn py_field(&self) -> &str {
+        self.field()
+    }
+
+    #[getter(message)]
+    pub(crate) fn py_message(&self) -> &str {
+        self.message()
+    }
+
+    pub(crate) fn __repr__(&self) -> String {
+        format!("<imagenex831l.Warning {self}>")
+    }
+
+    pub(crate) fn __str__(&self) -> String {
+        self.to_string()
+    }
+}
@@ -0,0 +1,403 @@
+//! Stacking of sidescan returns into waterfall images.
+use crate::image::{Colormap, Image};
+use crate::types::Mode;
+use crate::Shot;
+use chrono::{DateTime, Utc};
+
+/// What the lines of a [Waterfall] are stacked along.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(
+    target_family = "wasm",
+    derive(tsify::Tsify, serde::Serialize, serde::Deserialize),
+    tsify(into_wasm_abi, from_wasm_abi)
+)]
+#[cfg_attr(
+    all(feature = "serde", not(target_family = "wasm")),
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum WaterfallAxis {
+    /// One line per shot, in recording order.
+    #[default]
+    Time,
+
+    /// One line per [WaterfallOptions::line_spacing] of the distance travelled along the pipe.
+    Distance,
+}
+
+/// How a [WaterfallBuilder] stacks sidescan returns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaterfallOptions {
+    /// What the lines are stacked along.
+    pub axis: WaterfallAxis,
+
+    /// The number of ground range cells across each line.
+    pub columns: usize,
+
+    /// The ground range of the last cell in meters, or `None` for the
+    /// [RangeCode](crate::types::RangeCode) of the first shot.
+    pub range: Option<f32>,
+
+    /// The distance between lines along [WaterfallAxis::Distance], in meters.
+    pub line_spacing: f32,
+
+    /// Whether to correct the slant ranges of the samples to ground ranges.
+    pub slant_correction: bool,
+
+    /// The height of the head above the imaged surface in meters, or `None` for the profile
+    /// range detected by each shot.
+    pub altitude: Option<f32>,
+}
+
+impl Default for WaterfallOptions {
+    fn default() -> Self {
+        Self {
+            axis: WaterfallAxis::default(),
+            columns: 250,
+            range: None,
+            line_spacing: 0.01,
+            slant_correction: true,
+            altitude: None,
+        }
+    }
+}
+
+/// Sidescan returns stacked line by line, with the normalized intensity of each ground range
+/// cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Waterfall {
+    columns: usize,
+    range: f32,
+    positions: Vec<f32>,
+    intensities: Vec<f32>,
+}
+
+impl Waterfall {
+    /// The number of cells across each line.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.columns
+    }
+
+    /// The number of lines.
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.positions.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// The ground range of the last cell, in meters.
+    #[inline]
+    pub fn range(&self) -> f32 {
+        self.range
+    }
+
+    /// The ground range at the middle of cell `column`, in meters.
+    pub fn ground_range(&self, column: usize) -> f32 {
+        (column as f32 + 0.5) * self.range / self.columns as f32
+    }
+
+    /// The position of each line along the [WaterfallAxis]: seconds since the first shot, or
+    /// meters travelled since the first shot.
+    #[inline]
+    pub fn positions(&self) -> &[f32] {
+        &self.positions
+    }
+
+    /// The intensities of all lines, row by row.
+    #[inline]
+    pub fn intensities(&self) -> &[f32] {
+        &self.intensities
+    }
+
+    /// The intensities of line `row`, or `None` past the last line.
+    pub fn line(&self, row: usize) -> Option<&[f32]> {
+        self.intensities.chunks_exact(self.columns).nth(row)
+    }
+
+    /// The intensities as one vector per line.
+    pub fn to_array(&self) -> Vec<Vec<f32>> {
+        self.intensities.chunks_exact(self.columns).map(<[f32]>::to_vec).collect()
+    }
+
+    /// Render one pixel per cell, with the first line at the top and the head on the left.
+    pub fn render(&self, colormap: Colormap) -> Image {
+        let mut image = Image::new(self.width() as u32, self.height() as u32, colormap.color(0.0));
+        for (row, line) in self.intensities.chunks_exact(self.columns).enumerate() {
+            for (column, &intensity) in line.iter().enumerate() {
+                image.set_pixel(column as i64, row as i64, colormap.color(intensity));
+            }
+        }
+        image
+    }
+}
+
+/// Builds a [Waterfall] from the shots in [Mode::Sidescan] pushed in recording order.
+#[derive(Debug, Clone)]
+pub struct WaterfallBuilder {
+    options: WaterfallOptions,
+    range: Option<f32>,
+    start: Option<(DateTime<Utc>, f32)>,
+    positions: Vec<f32>,
+    intensities: Vec<f32>,
+    counts: Vec<u32>,
+}
+
+impl WaterfallBuilder {
+    pub fn new(options: WaterfallOptions) -> Self {
+        let range = options.range.filter(|&range| range > 0.0);
+        Self {
+            options,
+            range,
+            start: None,
+            positions: Vec::new(),
+            intensities: Vec::new(),
+            counts: Vec::new(),
+        }
+    }
+
+    /// The number of lines stacked so far.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Stack `shot`, returning whether it was used.
+    ///
+    /// Shots not in [Mode::Sidescan] are ignored, as are shots without a valid distance or behind
+    /// the last line along [WaterfallAxis::Distance]. Shots falling on the same line are averaged
+    /// and lines skipped over repeat the line before them.
+    pub fn push(&mut self, shot: &Shot) -> bool {
+        let header = &shot.header;
+        if header.motion().mode != Mode::Sidescan || self.options.columns == 0 {
+            return false;
+        }
+
+        let distance_valid = header.sensor_information().distance_valid;
+        if self.options.axis == WaterfallAxis::Distance && !distance_valid {
+            return false;
+        }
+
+        let (start_time, start_distance) =
+            *self.start.get_or_insert((header.datetime(), header.distance()));
+        let range =
+            *self.range.get_or_insert_with(|| shot.sonar_return.header().range_code.range());
+
+        let line = self.resample(shot, range);
+        match self.options.axis {
+            WaterfallAxis::Time => {
+                let elapsed = header.datetime() - start_time;
+                self.append(elapsed.num_milliseconds() as f32 / 1000.0, line);
+            },
+            WaterfallAxis::Distance => {
+                let spacing = self.options.line_spacing;
+                let row = ((header.distance() - start_distance) / spacing).round();
+                let next = self.len() as f32;
+                if !row.is_finite() || row < next - 1.0 {
+                    return false;
+                }
+                if row == next - 1.0 {
+                    self.average(line);
+                    return true;
+                }
+
+                for skipped in next as usize..row as usize {
+                    let previous =
+                        self.intensities[self.intensities.len() - self.options.columns..].to_vec();
+                    self.append(skipped as f32 * spacing, previous);
+                }
+                self.append(row * spacing, line);
+            },
+        }
+
+        true
+    }
+
+    /// The echo of `shot` sampled at the ground range of each cell up to `range`.
+    fn resample(&self, shot: &Shot, range: f32) -> Vec<f32> {
+        let altitude = match self.options.altitude {
+            Some(altitude) => altitude,
+            None => shot.sonar_return.header().profile_range,
+        }
+        .max(0.0);
+
+        let profile = shot.echo_profile();
+        let ranges = profile.ranges();
+        let intensities = profile.echo().normalized();
+        let columns = self.options.columns;
+
+        (0..columns)
+            .map(|column| (column as f32 + 0.5) * range / columns as f32)
+            .map(|ground| match self.options.slant_correction {
+                true => ground.hypot(altitude),
+                false => ground,
+            })
+            .map(|slant| ranges.partition_point(|&r| r < slant))
+            .map(|i| intensities.get(i).copied().unwrap_or_default())
+            .collect()
+    }
+
+    fn append(&mut self, position: f32, line: Vec<f32>) {
+        self.positions.push(position);
+        self.intensities.extend(line);
+        self.counts.push(1);
+    }
+
+    fn average(&mut self, line: Vec<f32>) {
+        let count = self.counts.last_mut().expect("a line to average into");
+        *count += 1;
+        let weight = 1.0 / *count as f32;
+
+        let start = self.intensities.len() - line.len();
+        for (cell, value) in self.intensities[start..].iter_mut().zip(line) {
+            *cell += (value - *cell) * weight;
+        }
+    }
+
+    /// Finish the [Waterfall] of the lines stacked so far.
+    pub fn build(self) -> Waterfall {
+        Waterfall {
+            columns: self.options.columns,
+            range: self.range.unwrap_or_default(),
+            positions: self.positions,
+            intensities: self.intensities,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::shot_bytes;
+    use binrw::BinRead;
+    use std::io::Cursor;
+
+    use log::info;
+    use test_log::test;
+
+    const EPSILON: f32 = 1e-5;
+
+    /// The fixture shot in [Mode::Sidescan] at `distance` meters, with every sample `level`.
+    fn shot(distance: f32, level: Option<u8>) -> Shot {
+        let mut bytes = shot_bytes();
+        bytes[107..111].copy_from_slice(&distance.to_be_bytes());
+        if let Some(level) = level {
+            bytes[260..510].fill(level);
+        }
+        sidescan(bytes)
+    }
+
+    fn sidescan(bytes: Vec<u8>) -> Shot {
+        let mut shot = Shot::read(&mut Cursor::new(bytes)).expect("It should not return an error");
+        shot.header.motion_mut().mode = Mode::Sidescan;
+        shot
+    }
+
+    fn uncorrected() -> WaterfallOptions {
+        WaterfallOptions { range: Some(1.0), slant_correction: false, ..Default::default() }
+    }
+
+    #[test]
+    fn ignores_other_modes() {
+        let mut builder = WaterfallBuilder::new(WaterfallOptions::default());
+        let mut polar = shot(0.0, None);
+        polar.header.motion_mut().mode = Mode::Polar;
+
+        assert!(!builder.push(&polar));
+        assert!(builder.push(&shot(0.0, None)));
+        assert_eq!(1, builder.len());
+    }
+
+    #[test]
+    fn time() {
+        let mut builder = WaterfallBuilder::new(uncorrected());
+        for _ in 0..3 {
+            builder.push(&shot(0.0, None));
+        }
+
+        let waterfall = builder.build();
+        assert_eq!((250, 3), (waterfall.width(), waterfall.height()));
+        assert_eq!(&[0.0; 3], waterfall.positions());
+
+        let profile = shot(0.0, None).echo_profile();
+        let line = waterfall.line(0).expect("It should have a first line");
+        for (column, &got) in line.iter().enumerate() {
+            let ground = waterfall.ground_range(column);
+            let want = profile.iter().find(|&(range, _)| range >= ground).map_or(0.0, |(_, n)| n);
+            assert!((want - got).abs() < EPSILON, "column {column}: want {want}, got {got}");
+        }
+    }
+
+    #[test]
+    fn slant_correction() {
+        let options =
+            WaterfallOptions { slant_correction: true, altitude: Some(0.5), ..uncorrected() };
+        let mut builder = WaterfallBuilder::new(options);
+        builder.push(&shot(0.0, None));
+        let waterfall = builder.build();
+
+        let profile = shot(0.0, None).echo_profile();
+        let cases = vec![0, 50, 125, 249];
+        for column in cases {
+            let ground = waterfall.ground_range(column);
+            let slant = ground.hypot(0.5);
+            let want = profile.iter().find(|&(range, _)| range >= slant).map_or(0.0, |(_, n)| n);
+            let got = waterfall.line(0).unwrap()[column];
+            info!("Column {column} at {ground} m ground, {slant} m slant, want {want}, got {got}");
+            assert!((want - got).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn distance() {
+        let options = WaterfallOptions { axis: WaterfallAxis::Distance, ..uncorrected() };
+        let mut builder = WaterfallBuilder::new(options);
+
+        let cases = vec![
+            (0.0, 0, true),
+            (0.004, 255, true),
+            (0.02, 51, true),
+            (0.005, 0, false),
+            (0.03, 102, true),
+        ];
+        for (distance, level, want) in cases {
+            info!("Pushing a shot at {distance} m, want {want}");
+            assert_eq!(want, builder.push(&shot(distance, Some(level))));
+        }
+
+        let mut bytes = shot_bytes();
+        bytes[98] = 0b0000_0011; // sensor information: distance invalid
+        assert!(!builder.push(&sidescan(bytes)));
+
+        let waterfall = builder.build();
+        let want = [0.5, 0.5, 0.2, 0.4];
+        let got: Vec<f32> = waterfall.to_array().iter().map(|line| line[200]).collect();
+        info!("Got lines {got:?}");
+        assert_eq!(4, waterfall.height());
+        assert!(want.iter().zip(&got).all(|(want, got)| (want - got).abs() < EPSILON));
+
+        let positions = waterfall.positions();
+        assert!((positions[3] - 0.03).abs() < EPSILON);
+    }
+
+    #[test]
+    fn render() {
+        let mut builder = WaterfallBuilder::new(uncorrected());
+        builder.push(&shot(0.0, Some(0)));
+        builder.push(&shot(0.0, Some(255)));
+
+        let image = builder.build().render(Colormap::Grayscale);
+        assert_eq!((250, 2), (image.width(), image.height()));
+        assert_eq!(Some([0; 3]), image.pixel(200, 0));
+        assert_eq!(Some([255; 3]), image.pixel(200, 1));
+    }
+}